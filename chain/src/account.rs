@@ -1,11 +1,12 @@
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 
-use eth_trie::{EthTrie, Trie};
+use eth_trie::{EthTrie, Trie, DB};
 use ethereum_types::{H256, U256};
 use types::account::{Account, AccountData};
-use types::block::BlockNumber;
 use types::bytes::Bytes;
-use utils::crypto::to_address;
+use utils::crypto::{hash, to_address};
 
 use crate::helpers::{deserialize, serialize};
 use crate::{
@@ -16,15 +17,83 @@ use crate::{
 #[derive(Debug)]
 pub(crate) struct AccountStorage {
     pub(crate) trie: EthTrie<Storage>,
+    storage: Arc<Storage>,
 }
 
 impl AccountStorage {
     pub(crate) fn new(storage: Arc<Storage>) -> Self {
         Self {
             trie: EthTrie::new(Arc::clone(&storage)),
+            storage,
         }
     }
 
+    /// Open a view of the accounts as they were at a previous state root.
+    /// Writes to the view never affect the current state.
+    pub(crate) fn at_root(&self, root: H256) -> Result<Self> {
+        let trie = EthTrie::from(Arc::clone(&self.storage), root.to_fixed_bytes().into())
+            .map_err(|e| ChainError::StateNotFound(format!("{:?}: {}", root, e)))?;
+
+        Ok(Self {
+            trie,
+            storage: Arc::clone(&self.storage),
+        })
+    }
+
+    pub(crate) fn storage(&self) -> &Arc<Storage> {
+        &self.storage
+    }
+
+    /// The keys of the stored trie nodes the accounts at a state root are
+    /// kept in, including the root's own
+    pub(crate) fn node_keys_at(&self, root: H256) -> Result<HashSet<Vec<u8>>> {
+        let mut trie = self.at_root(root)?.trie;
+        let keys = trie.iter().map(|(key, _)| key).collect::<Vec<_>>();
+        let mut node_keys = HashSet::from([root.as_bytes().to_vec()]);
+
+        // every stored node is on the path to some key, and nodes under 32
+        // bytes are kept inline in their parent rather than stored
+        for key in keys {
+            let proof = trie
+                .get_proof(&key)
+                .map_err(|e| ChainError::StateNotFound(format!("{:?}: {}", root, e)))?;
+
+            node_keys.extend(
+                proof
+                    .iter()
+                    .filter(|node| node.len() >= 32)
+                    .map(|node| hash(node).to_vec()),
+            );
+        }
+
+        Ok(node_keys)
+    }
+
+    /// The stored trie nodes the accounts at a state root are kept in, by
+    /// their key, to rebuild the state in another storage with `insert_nodes`
+    pub(crate) fn nodes_at(&self, root: H256) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.node_keys_at(root)?
+            .into_iter()
+            .map(|key| {
+                let node = self
+                    .storage
+                    .get(&key)?
+                    .ok_or_else(|| ChainError::StorageNotFound(Storage::key_string(&key)))?;
+
+                Ok((key, node))
+            })
+            .collect()
+    }
+
+    /// Store trie nodes taken with `nodes_at`
+    pub(crate) fn insert_nodes(&self, nodes: Vec<(Vec<u8>, Vec<u8>)>) -> Result<()> {
+        for (key, node) in nodes {
+            self.storage.insert(&key, node)?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn upsert(&mut self, key: &Account, data: &AccountData) -> Result<()> {
         self.trie
             .insert(key.as_ref(), &serialize(&data)?)
@@ -106,13 +175,27 @@ impl AccountStorage {
         Ok(account_data.nonce)
     }
 
-    pub(crate) fn get_account_balance_by_block(
-        &self,
-        key: &Account,
-        _block: &BlockNumber,
-    ) -> Result<U256> {
-        let balance = self.get_account(key)?.balance;
-        Ok(balance)
+    /// Contract storage lives in the account trie, keyed by `hash(address || key)`
+    fn storage_key(address: &Account, key: &str) -> H256 {
+        hash(&[address.as_bytes(), key.as_bytes()].concat()).into()
+    }
+
+    pub(crate) fn get_storage(&self, address: &Account, key: &str) -> Result<Option<String>> {
+        let storage_key = Self::storage_key(address, key);
+
+        self.trie
+            .get(storage_key.as_bytes())
+            .map_err(|_| ChainError::StorageNotFound(format!("{:?}", storage_key)))?
+            .map(|value| deserialize(&value))
+            .transpose()
+    }
+
+    pub(crate) fn set_storage(&mut self, address: &Account, key: &str, value: &str) -> Result<()> {
+        let storage_key = Self::storage_key(address, key);
+
+        self.trie
+            .insert(storage_key.as_bytes(), &serialize(&value)?)
+            .map_err(|_| ChainError::StoragePutError(format!("{:?}", storage_key)))
     }
 
     pub(crate) fn root_hash(&mut self) -> Result<H256> {
@@ -125,6 +208,35 @@ impl AccountStorage {
     }
 }
 
+/// The accounts as seen by the contract runtime.
+/// Contracts are addressed by their hex encoded address.
+pub(crate) struct ContractState(Mutex<AccountStorage>);
+
+impl ContractState {
+    pub(crate) fn new(accounts: AccountStorage) -> Self {
+        Self(Mutex::new(accounts))
+    }
+
+    fn accounts(&self) -> std::sync::MutexGuard<'_, AccountStorage> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl runtime::host::State for ContractState {
+    fn code(&self, address: &str) -> Option<Vec<u8>> {
+        let address = Account::from_str(address).ok()?;
+        let code = self.accounts().get_account(&address).ok()?.code_hash?;
+
+        Some(code.to_vec())
+    }
+
+    fn storage(&self, address: &str, key: &str) -> Option<String> {
+        let address = Account::from_str(address).ok()?;
+
+        self.accounts().get_storage(&address, key).ok().flatten()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ethereum_types::H160;
@@ -173,6 +285,20 @@ mod tests {
         let (_, _) = add_account(&mut account_storage);
     }
 
+    #[test]
+    fn it_sets_contract_storage() {
+        let mut account_storage = new_account_storage();
+        let (_, id) = add_account(&mut account_storage);
+        assert_eq!(account_storage.get_storage(&id, "key").unwrap(), None);
+
+        account_storage.set_storage(&id, "key", "value").unwrap();
+        assert_eq!(
+            account_storage.get_storage(&id, "key").unwrap(),
+            Some("value".into())
+        );
+        assert_eq!(account_storage.get_storage(&id, "other").unwrap(), None);
+    }
+
     #[test]
     fn root_hash_changes() {
         let mut account_storage = new_account_storage();
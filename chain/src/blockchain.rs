@@ -4,28 +4,105 @@
 
 ////////////////////////////////////////////////////////////////////////////////
 
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-use crate::account::AccountStorage;
+use crate::account::{AccountStorage, ContractState};
+use crate::consensus::{Consensus, InstantSeal};
 use crate::error::{ChainError, Result};
 use crate::storage::Storage;
 use crate::transaction::TransactionStorage;
 use crate::world_state::WorldState;
-use ethereum_types::{H256, U64};
+use ethereum_types::{H256, U256, U64};
+use futures::{stream, Stream, StreamExt};
+use runtime::context::{ExecutionContext, Input};
+use runtime::host::SelfDestructs;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
-use types::account::Account;
-use types::block::{Block, BlockNumber};
+use types::account::{Account, AccountData};
+use types::block::{Block, BlockId, BlockNumber, BlockTag, FeeHistory, DEFAULT_GAS_LIMIT};
 use types::bytes::Bytes;
+use types::error::TypeError;
 use types::transaction::{
-    SignedTransaction, Transaction, TransactionKind, TransactionReceipt, TransactionRequest,
+    ExecutionResult, SignedTransaction, Transaction, TransactionKind, TransactionReceipt,
+    TransactionRequest,
 };
 
+/// The chain prices gas with `gas_price` alone, so there is no base fee to burn
+pub(crate) const BASE_FEE: U256 = U256::zero();
+
+/// The chain id when none is configured, the conventional id of a local
+/// development chain
+pub(crate) const DEFAULT_CHAIN_ID: u64 = 1337;
+
+/// The most blocks a single fee history can cover
+pub(crate) const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
+/// The chain's activity since it started, for operators' dashboards.
+/// Counters are kept as blocks are produced and transactions admitted, while
+/// the mempool size and rates are filled in by `BlockChain::stats`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChainStats {
+    /// Blocks built from the mempool
+    pub(crate) blocks_produced: u64,
+    /// Transactions included in produced blocks
+    pub(crate) transactions_processed: u64,
+    /// Transactions admitted into the mempool
+    pub(crate) transactions_admitted: u64,
+    /// Transactions waiting in the mempool
+    pub(crate) mempool_size: usize,
+    /// The gas used by produced blocks
+    pub(crate) gas_used: U256,
+    pub(crate) average_gas_per_block: U256,
+    pub(crate) blocks_per_second: f64,
+    pub(crate) transactions_per_second: f64,
+}
+
+/// How far an import of many blocks has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ImportProgress {
+    /// The blocks imported so far
+    pub(crate) imported: u64,
+    /// The number of the last block imported
+    pub(crate) head: U64,
+}
+
+/// The state of the oldest retained block once the blocks before it are
+/// pruned, which is persisted in place of the state those blocks built, as
+/// the block can't be imported again without its parent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct StateSnapshot {
+    /// The stored trie nodes of the block's state, by their key
+    pub(crate) nodes: Vec<(Vec<u8>, Vec<u8>)>,
+    /// The receipts of the block's transactions
+    pub(crate) receipts: Vec<TransactionReceipt>,
+}
+
 // TODO(ddimaria): store blocks in a patricia merkle trie
 #[derive(Debug)]
 pub(crate) struct BlockChain {
     pub(crate) accounts: AccountStorage,
-    pub(crate) blocks: Vec<Block>,
+    /// The id signed into replay protected transactions meant for this chain
+    pub(crate) chain_id: u64,
+    /// The retained blocks, oldest first
+    pub(crate) blocks: VecDeque<Block>,
+    /// Seals produced blocks and validates imported ones
+    pub(crate) consensus: Arc<dyn Consensus>,
+    /// The gas limit that block gas limits are adjusted toward
+    pub(crate) gas_limit_target: U256,
+    /// The timestamp of the next block built, instead of the time it's built
+    pub(crate) next_block_timestamp: Option<U64>,
+    /// How many blocks behind the head are kept, with their receipts and the
+    /// state to revert them. Older blocks are pruned. All are kept when `None`.
+    /// Set with `set_retention`.
+    pub(crate) retention: Option<u64>,
+    /// When the chain started, to turn its stats into rates
+    pub(crate) started: Instant,
+    pub(crate) stats: ChainStats,
     pub(crate) transactions: Arc<Mutex<TransactionStorage>>,
     pub(crate) world_state: WorldState,
 }
@@ -34,296 +111,1360 @@ impl BlockChain {
     pub(crate) fn new(storage: Arc<Storage>) -> Result<Self> {
         Ok(Self {
             accounts: AccountStorage::new(storage),
-            blocks: vec![Block::genesis()?],
+            chain_id: DEFAULT_CHAIN_ID,
+            blocks: VecDeque::from([Block::genesis()?]),
+            consensus: Arc::new(InstantSeal),
+            gas_limit_target: U256::from(DEFAULT_GAS_LIMIT),
+            next_block_timestamp: None,
+            retention: None,
+            started: Instant::now(),
+            stats: ChainStats::default(),
             transactions: Arc::new(Mutex::new(TransactionStorage::new())),
             world_state: WorldState::new(),
         })
     }
 
+    /// Keep only `retention` blocks behind the head from now on.
+    /// Pruning deletes the trie nodes of ancient states from storage, so the
+    /// chain must not share its storage with another chain.
+    pub(crate) fn set_retention(&mut self, retention: u64) {
+        self.accounts.storage().track_stale_nodes();
+        self.retention = Some(retention);
+    }
+
     pub(crate) fn get_current_block(&self) -> Result<Block> {
         let block = self
             .blocks
-            .last()
+            .back()
             .ok_or_else(|| ChainError::BlockNotFound("current block".into()))?;
 
         Ok(block.to_owned())
     }
 
     pub(crate) fn get_block_by_number(&self, block_number: U64) -> Result<Block> {
-        let index = (block_number - 1).as_usize();
+        let oldest = self
+            .blocks
+            .front()
+            .map_or(U64::zero(), |block| block.number);
+
+        if block_number < oldest {
+            return Err(ChainError::BlockPruned(block_number.to_string()));
+        }
+
         let block = self
             .blocks
-            .get(index)
-            .ok_or_else(|| ChainError::BlockNotFound("current block".into()))?;
+            .get((block_number - oldest).as_usize())
+            .ok_or_else(|| ChainError::BlockNotFound(block_number.to_string()))?;
 
         Ok(block.to_owned())
     }
 
-    pub(crate) fn parse_block_number(&self, block_number: &str) -> Result<BlockNumber> {
-        if block_number == "latest" {
-            Ok(BlockNumber(self.get_current_block()?.number))
-        } else {
-            Ok(block_number
-                .try_into()
-                .map_err(|_| ChainError::InvalidBlockNumber(block_number.into()))?)
+    pub(crate) fn get_block_by_hash(&self, block_hash: H256) -> Result<Block> {
+        let block = self
+            .blocks
+            .iter()
+            .find(|block| block.hash == Some(block_hash))
+            .ok_or_else(|| ChainError::BlockNotFound(format!("{:?}", block_hash)))?;
+
+        Ok(block.to_owned())
+    }
+
+    /// Resolve a block id to a tag, looking up the number of blocks referenced by hash
+    pub(crate) fn block_tag(&self, block: BlockId) -> Result<BlockTag> {
+        match block {
+            BlockId::Number(tag) => Ok(tag),
+            BlockId::Hash(block_hash) => {
+                Ok(BlockTag::Number(self.get_block_by_hash(block_hash)?.number))
+            }
+        }
+    }
+
+    /// An account as it was once `block` was applied. The pending block is
+    /// the latest one, as its transactions haven't run yet.
+    pub(crate) fn get_account_by_block(
+        &self,
+        account: &Account,
+        block: impl Into<BlockId>,
+    ) -> Result<AccountData> {
+        let head = self.get_current_block()?.number;
+        let number = match self.block_tag(block.into())? {
+            BlockTag::Latest | BlockTag::Pending => head,
+            BlockTag::Earliest => U64::zero(),
+            BlockTag::Number(number) => number,
+        };
+
+        if number == head {
+            return self.accounts.get_account(account);
+        }
+
+        // the genesis block has no state, and an account missing from an
+        // older block's state didn't exist yet
+        let state_root = self.get_block_by_number(number)?.state_root;
+
+        if state_root.is_zero() {
+            return Ok(AccountData::new(None));
+        }
+
+        match self.accounts.at_root(state_root)?.get_account(account) {
+            Err(ChainError::StorageNotFound(_)) => Ok(AccountData::new(None)),
+            account => account,
+        }
+    }
+
+    /// The balance of an account once `block` was applied, or once the
+    /// transactions in the mempool are applied for the pending block
+    pub(crate) async fn get_balance_by_block(
+        &self,
+        account: &Account,
+        block: impl Into<BlockId>,
+    ) -> Result<U256> {
+        match self.block_tag(block.into())? {
+            BlockTag::Pending => self.get_pending_balance(account).await,
+            tag => Ok(self.get_account_by_block(account, tag)?.balance),
         }
     }
 
+    /// The balance of an account once the transactions in the mempool are applied
+    pub(crate) async fn get_pending_balance(&self, account: &Account) -> Result<U256> {
+        let balance = self.accounts.get_account(account)?.balance;
+        let pending_balance = self.transactions.lock().await.mempool.iter().fold(
+            balance,
+            |mut balance, transaction| {
+                // a transfer to itself debits and credits the account
+                if transaction.from == *account {
+                    balance = balance.saturating_sub(transaction.value);
+                }
+
+                if transaction.to == Some(*account) {
+                    balance = balance.saturating_add(transaction.value);
+                }
+
+                balance
+            },
+        );
+
+        Ok(pending_balance)
+    }
+
+    /// The transactions from an account that are waiting in the mempool,
+    /// ordered by nonce
+    pub(crate) async fn pending_for(&self, account: &Account) -> Vec<Transaction> {
+        self.transactions.lock().await.pending_for(account)
+    }
+
     pub(crate) fn new_block(
         &mut self,
         transactions: Vec<Transaction>,
+        receipts: &[TransactionReceipt],
         state_trie: H256,
+        timestamp: U64,
     ) -> Result<Block> {
         let current_block = self.get_current_block()?;
         let number = current_block.number + 1_u64;
         let parent_hash = current_block.block_hash()?;
-        let block = Block::new(number, parent_hash, transactions, state_trie)?;
-
-        self.blocks.push(block);
+        let gas_limit = current_block.next_gas_limit(self.gas_limit_target);
+        let mut block = Block::new(
+            number,
+            parent_hash,
+            transactions,
+            receipts,
+            state_trie,
+            gas_limit,
+            timestamp,
+        )?;
+
+        self.consensus.seal_block(&mut block)?;
+        self.blocks.push_back(block);
 
         self.get_block_by_number(number)
     }
 
+    /// Append a block produced by another node.
+    ///
+    /// The block must pass the consensus rules, and re-executing its
+    /// transactions must produce its state and receipts roots. Otherwise the
+    /// state is restored and the block is rejected.
+    pub(crate) async fn import_block(&mut self, block: Block) -> Result<Block> {
+        let parent = self.get_current_block()?;
+        self.consensus.validate_block(&block, &parent)?;
+
+        let pre_state_root = self.accounts.root_hash()?;
+        let receipts = match self.execute_block(&block) {
+            Ok(receipts) => receipts,
+            Err(error) => {
+                self.accounts = self.accounts.at_root(pre_state_root)?;
+                return Err(error);
+            }
+        };
+
+        let state_trie = self.accounts.root_hash()?;
+        self.world_state.update_state_trie(state_trie);
+        self.world_state
+            .record_pre_state_root(block.number, pre_state_root);
+        self.blocks.push_back(block.clone());
+
+        {
+            let storage = self.transactions.lock().await;
+
+            for (index, mut receipt) in receipts.into_iter().enumerate() {
+                receipt.block_number = Some(BlockNumber(block.number));
+                receipt.block_hash = block.hash;
+                receipt.transaction_index = Some(U64::from(index));
+                storage.receipts.insert(receipt.transaction_hash, receipt);
+            }
+        }
+
+        self.prune().await?;
+
+        tracing::info!(
+            "Imported block {} with {} transactions",
+            block.number,
+            block.transactions.len()
+        );
+
+        Ok(block)
+    }
+
+    /// Import blocks one at a time as they arrive, so a long sync only holds
+    /// the retained blocks in memory. Progress is reported after each block.
+    /// Returns the number of blocks imported. An invalid block fails the
+    /// import, leaving the blocks before it imported.
+    pub(crate) async fn import_blocks(
+        &mut self,
+        mut blocks: impl Stream<Item = Block> + Unpin,
+        mut progress: impl FnMut(ImportProgress),
+    ) -> Result<u64> {
+        let mut imported = 0;
+
+        while let Some(block) = blocks.next().await {
+            let block = self.import_block(block).await?;
+            imported += 1;
+
+            progress(ImportProgress {
+                imported,
+                head: block.number,
+            });
+        }
+
+        Ok(imported)
+    }
+
+    /// Write the chain's blocks and transactions to a file, to be read back
+    /// with `restore`. Once blocks are pruned, the state of the oldest
+    /// retained block is written too.
+    pub(crate) async fn persist(&self, path: impl AsRef<Path>) -> Result<()> {
+        let blocks = self.blocks.iter().cloned().collect();
+        let storage = self.transactions.lock().await;
+        let state = match self.blocks.front() {
+            Some(oldest) if !oldest.number.is_zero() => Some(StateSnapshot {
+                nodes: self.accounts.nodes_at(oldest.state_root)?,
+                receipts: oldest
+                    .transactions
+                    .iter()
+                    .map(|transaction| {
+                        storage.get_transaction_receipt(&transaction.transaction_hash()?)
+                    })
+                    .collect::<Result<_>>()?,
+            }),
+            _ => None,
+        };
+
+        storage.persist_with(path, blocks, state)
+    }
+
+    /// Restore a chain persisted with `persist` onto this one.
+    ///
+    /// The blocks past this chain's head are imported, which rebuilds the
+    /// state, nonces and receipts their transactions left. When the oldest
+    /// of them doesn't follow the head, as the blocks before it were pruned,
+    /// it becomes the chain's only block with its persisted state instead.
+    /// The pending and dropped transactions are restored with this chain's
+    /// mempool config.
+    pub(crate) async fn restore(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let config = self.transactions.lock().await.config.clone();
+        let (transactions, blocks, state) = TransactionStorage::load(path, config)?;
+        let head = self.get_current_block()?.number;
+        let mut blocks = blocks
+            .into_iter()
+            .filter(|block| block.number > head)
+            .peekable();
+        self.transactions = Arc::new(Mutex::new(transactions));
+
+        if let Some(state) = state {
+            if let Some(oldest) = blocks.next_if(|block| block.number > head + 1_u64) {
+                self.restore_state(oldest, state).await?;
+            }
+        }
+
+        self.import_blocks(stream::iter(blocks), |progress| {
+            tracing::info!(
+                "Restored {} blocks up to block {}",
+                progress.imported,
+                progress.head
+            )
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace the chain's blocks with `block`, storing its persisted state
+    /// for the blocks after it to be imported onto
+    async fn restore_state(&mut self, block: Block, state: StateSnapshot) -> Result<()> {
+        self.accounts.insert_nodes(state.nodes)?;
+        self.accounts = self.accounts.at_root(block.state_root)?;
+        self.world_state.update_state_trie(block.state_root);
+
+        {
+            let storage = self.transactions.lock().await;
+
+            for receipt in state.receipts {
+                storage.receipts.insert(receipt.transaction_hash, receipt);
+            }
+        }
+
+        tracing::info!("Restored the state of block {}", block.number);
+        self.blocks = VecDeque::from([block]);
+
+        Ok(())
+    }
+
+    /// Drop the blocks more than `retention` behind the head, along with
+    /// their receipts, the state roots needed to revert them and the
+    /// transactions dropped before them.
+    /// Every `retention` blocks, the state of pruned blocks is deleted too.
+    async fn prune(&mut self) -> Result<()> {
+        let Some(retention) = self.retention else {
+            return Ok(());
+        };
+        let head = self.get_current_block()?.number;
+        let mut storage = self.transactions.lock().await;
+
+        while let Some(block) = self.blocks.pop_front() {
+            if block.number + retention >= head {
+                self.blocks.push_front(block);
+                break;
+            }
+
+            self.world_state.remove_pre_state_root(block.number);
+
+            // commits only replace the nodes below a root, so its own node
+            // is left to the next `prune_state`
+            self.accounts
+                .storage()
+                .mark_stale_nodes([block.state_root.as_bytes().to_vec()]);
+
+            for transaction in block.transactions.iter() {
+                storage.receipts.remove(&transaction.transaction_hash()?);
+            }
+        }
+
+        let oldest = self.blocks.front().map_or(head, |block| block.number);
+        storage.prune_dropped(head, oldest);
+        drop(storage);
+
+        if head.as_u64() % retention.max(1) == 0 {
+            self.prune_state()?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete the trie nodes that commits replaced and that neither the
+    /// current state nor the state of a retained block reaches
+    fn prune_state(&mut self) -> Result<()> {
+        let mut roots = self
+            .blocks
+            .iter()
+            .map(|block| block.state_root)
+            .collect::<HashSet<_>>();
+        roots.insert(self.accounts.root_hash()?);
+
+        // taken once the current state is committed, as committing replaces nodes
+        let stale_nodes = self.accounts.storage().take_stale_nodes();
+
+        if stale_nodes.is_empty() {
+            return Ok(());
+        }
+
+        let mut live_nodes = HashSet::new();
+
+        for root in roots.into_iter().filter(|root| !root.is_zero()) {
+            live_nodes.extend(self.accounts.node_keys_at(root)?);
+        }
+
+        let (live, dead): (Vec<_>, Vec<_>) = stale_nodes
+            .into_iter()
+            .partition(|key| live_nodes.contains(key));
+        self.accounts.storage().delete_nodes(&dead)?;
+
+        // a node still reached may be left behind by a later prune
+        self.accounts.storage().mark_stale_nodes(live);
+
+        tracing::debug!("Pruned {} trie nodes", dead.len());
+
+        Ok(())
+    }
+
+    /// Execute a block's transactions, checking the resulting roots match the block's
+    fn execute_block(&mut self, block: &Block) -> Result<Vec<TransactionReceipt>> {
+        let context = block_context(block);
+        let receipts = block
+            .transactions
+            .iter()
+            .map(|transaction| {
+                let (_, receipt) =
+                    self.process_transaction(&mut transaction.to_owned(), &context)?;
+                Ok(receipt)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if self.accounts.root_hash()? != block.state_root {
+            return Err(ChainError::InvalidBlock(format!(
+                "state root of block {} does not match",
+                block.number
+            )));
+        }
+
+        if TransactionReceipt::root_hash(&receipts)? != block.receipts_root {
+            return Err(ChainError::InvalidBlock(format!(
+                "receipts root of block {} does not match",
+                block.number
+            )));
+        }
+
+        Ok(receipts)
+    }
+
+    /// Remove the latest block, restoring the state from just before it was applied.
+    ///
+    /// Restoring the state also rolls back the nonce of each sender, so the
+    /// block's transactions are returned to the front of the mempool with their
+    /// original nonces, in their original order, and their receipts are discarded.
+    pub(crate) async fn revert_head(&mut self) -> Result<Block> {
+        let block = self.get_current_block()?;
+
+        if block.number.is_zero() {
+            return Err(ChainError::CannotRevertGenesisBlock);
+        }
+
+        // the parent has to be retained to become the head again
+        if self.blocks.len() < 2 {
+            return Err(ChainError::BlockPruned((block.number - 1_u64).to_string()));
+        }
+
+        let pre_state_root = self
+            .world_state
+            .pre_state_root(block.number)
+            .ok_or_else(|| ChainError::StateNotFound(format!("block {}", block.number)))?;
+
+        self.accounts = self.accounts.at_root(pre_state_root)?;
+        self.world_state.update_state_trie(pre_state_root);
+        self.world_state.remove_pre_state_root(block.number);
+        self.blocks.pop_back();
+
+        let mut storage = self.transactions.lock().await;
+
+        for transaction in block.transactions.iter().rev() {
+            storage.receipts.remove(&transaction.transaction_hash()?);
+            storage.mempool.push_front(transaction.to_owned());
+        }
+
+        tracing::info!(
+            "Reverted block {} with {} transactions",
+            block.number,
+            block.transactions.len()
+        );
+
+        Ok(block)
+    }
+
+    /// The nonce the next transaction from an account is expected to use.
+    /// Accounts store the nonce of their last transaction.
+    pub(crate) fn next_nonce(&self, account: &Account) -> Result<U256> {
+        Ok(self.accounts.get_account(account)?.nonce + 1_u64)
+    }
+
     pub(crate) async fn send_transaction(
         &mut self,
         transaction_request: TransactionRequest,
     ) -> Result<H256> {
         let mut transaction: Transaction = transaction_request.try_into()?;
-        let account = self.accounts.get_account(&transaction.from)?;
-        let nonce = transaction.nonce.unwrap_or_else(|| account.nonce + 1_u64);
+        let nonce = match transaction.nonce {
+            Some(nonce) => nonce,
+            None => self.next_nonce(&transaction.from)?,
+        };
 
         transaction.nonce = Some(nonce);
+        self.validate_transaction(&transaction)?;
 
         // regenerate the transaction hash with the nonce in place
         let transaction_hash = transaction.hash()?;
 
         // add to the transaction mempool
-        self.transactions.lock().await.send_transaction(transaction);
+        self.transactions
+            .lock()
+            .await
+            .send_transaction(transaction)?;
+        self.stats.transactions_admitted += 1;
 
         Ok(transaction_hash)
     }
 
-    pub(crate) async fn send_raw_transaction(&mut self, transaction: Bytes) -> Result<H256> {
-        let signed_transaction: SignedTransaction = bincode::deserialize(&transaction)?;
+    /// The chain's activity since it started
+    pub(crate) async fn stats(&self) -> ChainStats {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let rate = |count: u64| match elapsed {
+            elapsed if elapsed > 0.0 => count as f64 / elapsed,
+            _ => 0.0,
+        };
+        let average_gas_per_block = match self.stats.blocks_produced {
+            0 => U256::zero(),
+            blocks => self.stats.gas_used / blocks,
+        };
+
+        ChainStats {
+            mempool_size: self.transactions.lock().await.mempool.len(),
+            average_gas_per_block,
+            blocks_per_second: rate(self.stats.blocks_produced),
+            transactions_per_second: rate(self.stats.transactions_processed),
+            ..self.stats.clone()
+        }
+    }
+
+    /// Check a transaction could run before it waits in the mempool, so one
+    /// that never can is rejected when it is sent rather than when a block
+    /// is built
+    fn validate_transaction(&self, transaction: &Transaction) -> Result<()> {
+        let account = self.accounts.get_account(&transaction.from)?;
+        let nonce = transaction.nonce.unwrap_or_default();
+
+        // reject nonces that have already been used
+        if nonce <= account.nonce {
+            return Err(ChainError::NonceTooLow(
+                nonce.to_string(),
+                transaction.from.to_string(),
+            ));
+        }
+
+        if transaction.max_cost().is_none() {
+            return Err(ChainError::InvalidTransaction(format!(
+                "value {} plus {} gas at {} overflows",
+                transaction.value,
+                transaction.gas,
+                transaction.fee_cap()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Send a signed transaction encoded as an EIP-2718 envelope
+    pub(crate) async fn send_raw_transaction(&mut self, envelope: Bytes) -> Result<H256> {
+        let signed_transaction = SignedTransaction::from_envelope(&envelope)
+            .map_err(|e| ChainError::InvalidTransaction(e.to_string()))?;
         let transaction: Transaction = signed_transaction.clone().try_into()?;
         let transaction_hash = transaction.transaction_hash()?;
 
-        Transaction::verify(signed_transaction, transaction.from).map_err(|e| {
-            ChainError::TransactionNotVerified(format!("{}: {}", transaction_hash, e))
+        // transactions signed without a chain id are replayable anywhere, but
+        // ones signed for another chain are not meant for this one
+        if let Some(chain_id) = transaction
+            .chain_id
+            .filter(|id| id.as_u64() != self.chain_id)
+        {
+            return Err(ChainError::InvalidTransaction(format!(
+                "{}: signed for chain {}",
+                transaction_hash, chain_id
+            )));
+        }
+
+        signed_transaction.verify().map_err(|error| match error {
+            TypeError::InvalidSignature(reason) | TypeError::SenderMismatch(reason) => {
+                ChainError::InvalidSignature(format!("{}: {}", transaction_hash, reason))
+            }
+            error => ChainError::TransactionNotVerified(format!("{}: {}", transaction_hash, error)),
         })?;
 
         self.send_transaction(transaction.into()).await
     }
 
-    pub(crate) async fn process_transactions(&mut self) -> Result<()> {
-        // Bulk drain the current queue to fit into the new block
+    /// Build a block from the mempool, returning it if there was anything to include.
+    ///
+    /// Transactions are taken best paying first, in nonce order for each
+    /// sender, with ties going to the lowest sender and then the lowest hash,
+    /// so the same mempool always produces the same block. They are taken
+    /// until the block's gas limit is reached. The rest stay in the
+    /// mempool in their original order. Transactions that are no longer valid
+    /// are dropped with a failed receipt, except those waiting on an earlier
+    /// nonce, which stay pending until it arrives.
+    pub(crate) async fn produce_block(&mut self) -> Result<Option<Block>> {
         // This is not safe as we lose transactions if a panic occurs
         // or if the program is halted
-        let transactions = self
-            .transactions
-            .lock()
-            .await
-            .mempool
-            .drain(0..)
-            .collect::<VecDeque<_>>();
+        let transactions = {
+            let mut storage = self.transactions.lock().await;
+            let pending = storage.mempool.len();
+            let transactions = storage.take_best(pending);
+
+            for transaction in transactions.iter() {
+                if let Ok(transaction_hash) = transaction.transaction_hash() {
+                    storage.processed.insert(transaction_hash);
+                }
+            }
 
-        if !transactions.is_empty() {
-            let mut receipts: Vec<TransactionReceipt> = vec![];
-            let mut processed: Vec<Transaction> = vec![];
+            transactions
+        };
 
-            tracing::info!("Processing {} transactions", transactions.len());
+        if transactions.is_empty() {
+            return Ok(None);
+        }
 
-            for mut transaction in transactions.into_iter() {
-                match self.process_transaction(&mut transaction) {
-                    Ok((transaction, transaction_receipt)) => {
-                        receipts.push(transaction_receipt);
-                        processed.push(transaction.to_owned());
-                    }
-                    Err(error) => {
-                        match error {
-                            // The nonce is too high, add back to the mempool
-                            ChainError::NonceTooHigh(_, _) => {
-                                tracing::warn!(
-                                    "Could not process transaction {:?}: {}",
-                                    transaction,
-                                    error
-                                );
-                                self.transactions
-                                    .lock()
-                                    .await
-                                    .mempool
-                                    .push_back(transaction);
-                            }
-                            _ => tracing::error!(
-                                "Could not process transaction {:?}: {}",
-                                transaction,
-                                error
-                            ),
+        // capture the state before any transactions are applied so that
+        // the block can be replayed later
+        let pre_state_root = self.accounts.root_hash()?;
+        let context = self.next_block_context()?;
+        let mut receipts: Vec<TransactionReceipt> = vec![];
+        let mut processed: Vec<Transaction> = vec![];
+        let gas_limit = U256::from(context.gas_limit);
+        let mut gas_used = U256::zero();
+        let mut transactions = transactions.into_iter();
+
+        tracing::info!("Processing {} transactions", transactions.len());
+
+        while let Some(mut transaction) = transactions.next() {
+            let root = self.accounts.root_hash()?;
+
+            let result = self
+                .process_transaction(&mut transaction, &context)
+                .map(|(_, transaction_receipt)| transaction_receipt);
+
+            let error = match result {
+                Ok(transaction_receipt) if gas_used + transaction_receipt.gas_used <= gas_limit => {
+                    gas_used += transaction_receipt.gas_used;
+                    receipts.push(transaction_receipt);
+                    processed.push(transaction);
+                    continue;
+                }
+                Ok(transaction_receipt) => {
+                    self.accounts = self.accounts.at_root(root)?;
+
+                    // the block is full, so the rest wait for the next one
+                    if !processed.is_empty() {
+                        let mut storage = self.transactions.lock().await;
+
+                        for transaction in transactions.rev() {
+                            storage.mempool.push_front(transaction);
                         }
+
+                        storage.mempool.push_front(transaction);
+                        break;
                     }
+
+                    ChainError::InvalidTransaction(format!(
+                        "uses {} gas, more than the block gas limit of {}",
+                        transaction_receipt.gas_used, gas_limit
+                    ))
                 }
-            }
+                Err(error) => {
+                    // execution may have bumped the nonce or created the
+                    // recipient before failing, so undo it
+                    self.accounts = self.accounts.at_root(root)?;
+                    error
+                }
+            };
 
-            // update world state
-            let state_trie = self.accounts.root_hash()?;
-            self.world_state.update_state_trie(state_trie);
+            match error {
+                // The nonce is too high, add back to the mempool
+                ChainError::NonceTooHigh(_, _) => {
+                    tracing::warn!("Could not process transaction {:?}: {}", transaction, error);
+                    self.transactions
+                        .lock()
+                        .await
+                        .mempool
+                        .push_back(transaction);
+                }
+                // The transaction is no longer valid, so drop it and
+                // carry on with the rest of the block
+                _ => {
+                    tracing::error!("Could not process transaction {:?}: {}", transaction, error);
+
+                    self.transactions
+                        .lock()
+                        .await
+                        .drop_transaction(&transaction, error.to_string());
+                }
+            }
+        }
 
-            tracing::info!("World State: state_trie {:?}", state_trie);
+        // update world state
+        let state_trie = self.accounts.root_hash()?;
+        self.world_state.update_state_trie(state_trie);
 
-            let num_processed = processed.len();
-            let block = self.new_block(processed, state_trie)?;
+        tracing::info!("World State: state_trie {:?}", state_trie);
 
-            tracing::info!(
-                "Created block {} with {} transactions",
-                block.number,
-                num_processed
-            );
+        let num_processed = processed.len();
+        let block = self.new_block(processed, &receipts, state_trie, context.timestamp.into())?;
+        self.next_block_timestamp = None;
+        self.stats.blocks_produced += 1;
+        self.stats.transactions_processed += num_processed as u64;
+        self.stats.gas_used += gas_used;
+        self.world_state
+            .record_pre_state_root(block.number, pre_state_root);
 
-            // now add the block number and hash to the receipts
-            for mut receipt in receipts.into_iter() {
-                receipt.block_number = Some(BlockNumber(block.number));
-                receipt.block_hash = block.hash;
+        tracing::info!(
+            "Created block {} with {} transactions",
+            block.number,
+            num_processed
+        );
 
-                self.transactions
-                    .clone()
-                    .lock()
-                    .await
-                    .receipts
-                    .insert(receipt.transaction_hash, receipt);
-            }
+        let storage = self.transactions.lock().await;
 
-            let storage = self.transactions.lock().await;
+        // now add the block number, hash and position to the receipts
+        for (index, mut receipt) in receipts.into_iter().enumerate() {
+            receipt.block_number = Some(BlockNumber(block.number));
+            receipt.block_hash = block.hash;
+            receipt.transaction_index = Some(U64::from(index));
 
-            tracing::info!(
-                "Transaction storage: mempool {:?}, receipts {:?}",
-                storage.mempool.len(),
-                storage.receipts.len()
-            );
+            storage.receipts.insert(receipt.transaction_hash, receipt);
         }
 
-        Ok(())
+        storage.processed.clear();
+
+        tracing::info!(
+            "Transaction storage: mempool {:?}, receipts {:?}",
+            storage.mempool.len(),
+            storage.receipts.len()
+        );
+
+        drop(storage);
+        self.prune().await?;
+
+        Ok(Some(block))
     }
 
-    // TODO(ddimaria): handle TransactionKind::ContractExecution
     pub(crate) fn process_transaction<'a>(
         &mut self,
         transaction: &'a mut Transaction,
+        context: &ExecutionContext,
     ) -> Result<(&'a mut Transaction, TransactionReceipt)> {
-        let mut contract_address: Option<Account> = None;
         let transaction_hash = transaction.transaction_hash()?;
 
-        // ignore transactions without a nonce
-        if let Some(nonce) = transaction.nonce {
-            tracing::info!("Processing Transaction {:?}", transaction_hash);
-
-            // create the `to` account if it doesn't exist
-            if let Some(to) = transaction.to {
-                self.accounts.add_empty_account(&to)?;
-            }
+        tracing::info!("Processing Transaction {:?}", transaction_hash);
 
-            // TODO(ddimaria): remove this copy
-            let kind = transaction.to_owned().kind()?;
+        // an earlier transaction in the block may have spent the funds this one
+        // needs, which makes it invalid rather than failed
+        let balance = self.accounts.get_account(&transaction.from)?.balance;
 
-            match kind {
-                TransactionKind::Regular(from, to, value) => {
-                    self.accounts.transfer(&from, &to, value)
-                }
-                TransactionKind::ContractDeployment(from, data) => {
-                    contract_address = self.accounts.add_contract_account(&from, data).ok();
-                    Ok(())
-                }
-                TransactionKind::ContractExecution(_from, to, data) => {
-                    let code = self
-                        .accounts
-                        .get_account(&to)?
-                        .code_hash
-                        .ok_or_else(|| ChainError::NotAContractAccount(to.to_string()))?;
-                    let (function, params): (&str, Vec<&str>) = bincode::deserialize(&data)?;
-
-                    // call the function in the contract
-                    runtime::contract::call_function(&code, function, &params)
-                        .map_err(|e| ChainError::RuntimeError(to.to_string(), e.to_string()))
-                }
-            }?;
+        if balance < transaction.value {
+            return Err(ChainError::InsufficientFunds(
+                transaction.from.to_string(),
+                balance.to_string(),
+                transaction.value.to_string(),
+            ));
+        }
 
-            // update the nonce
-            self.accounts.update_nonce(&transaction.from, nonce)?;
+        let execution_result = self.execute_transaction(transaction, context)?;
+        let transaction_receipt = TransactionReceipt {
+            block_hash: None,
+            block_number: None,
+            contract_address: execution_result.contract_address,
+            from: transaction.from,
+            to: transaction.to,
+            transaction_hash,
+            transaction_index: None,
+            gas_used: execution_result.gas_used,
+            status: execution_result.status,
+        };
+
+        Ok((transaction, transaction_receipt))
+    }
 
-            let transaction_receipt = TransactionReceipt {
-                block_hash: None,
-                block_number: None,
-                contract_address,
-                transaction_hash,
+    /// Apply the self-destructs of a transaction once it has run, following
+    /// EIP-6780: each contract's balance goes to its beneficiary. Only
+    /// contracts created in the same transaction would be removed, and
+    /// contracts can't create contracts yet, so every contract keeps its code
+    /// and storage.
+    pub(crate) fn apply_self_destructs(&mut self, self_destructs: &SelfDestructs) -> Result<()> {
+        for (address, beneficiary) in self_destructs {
+            let parse = |address: &String| {
+                Account::from_str(address)
+                    .map_err(|e| ChainError::RuntimeError(address.clone(), e.to_string()))
             };
+            let (address, beneficiary) = (parse(address)?, parse(beneficiary)?);
+            let balance = self.accounts.get_account(&address)?.balance;
 
-            return Ok((transaction, transaction_receipt));
+            if beneficiary != address {
+                self.accounts.add_empty_account(&beneficiary)?;
+                self.accounts.transfer(&address, &beneficiary, balance)?;
+            }
         }
 
-        Err(ChainError::MissingTransactionNonce(
-            transaction_hash.to_string(),
-        ))
+        Ok(())
     }
 
-    pub(crate) async fn get_transaction_receipt(
-        &mut self,
-        transaction_hash: H256,
-    ) -> Result<TransactionReceipt> {
-        let transaction_receipt = self
-            .transactions
-            .lock()
-            .await
-            .get_transaction_receipt(&transaction_hash)?;
-
-        Ok(transaction_receipt)
+    /// The context of transactions in the next block built, which is the
+    /// time now unless the next block's timestamp has been set. Blocks built
+    /// within the same second still move a second past their parent.
+    pub(crate) fn next_block_context(&self) -> Result<ExecutionContext> {
+        let current_block = self.get_current_block()?;
+        let timestamp = match self.next_block_timestamp {
+            Some(timestamp) => timestamp,
+            None => U64::from(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_err(|e| ChainError::InternalError(e.to_string()))?
+                    .as_secs(),
+            )
+            .max(current_block.timestamp + 1_u64),
+        };
+        let block = Block {
+            number: current_block.number + 1_u64,
+            gas_limit: current_block.next_gas_limit(self.gas_limit_target),
+            timestamp,
+            ..current_block
+        };
+
+        Ok(block_context(&block))
     }
-}
 
-#[cfg(test)]
-pub(crate) mod tests {
-    use ethereum_types::U256;
-    use types::account::AccountData;
-    use utils::crypto::keypair;
+    /// Set the timestamp of the next block built, like hardhat's
+    /// `evm_setNextBlockTimestamp`, which has to be after the current block's
+    pub(crate) fn set_next_block_timestamp(&mut self, timestamp: U64) -> Result<()> {
+        let current_block = self.get_current_block()?;
 
-    use super::*;
-    use crate::{
-        helpers::tests::{setup, ACCOUNT_1, STORAGE},
-        transaction,
-    };
+        if timestamp <= current_block.timestamp {
+            return Err(ChainError::InvalidBlock(format!(
+                "timestamp {} isn't after the timestamp {} of block {}",
+                timestamp, current_block.timestamp, current_block.number
+            )));
+        }
 
-    pub(crate) fn new_blockchain() -> BlockChain {
-        BlockChain::new((*STORAGE).clone()).unwrap()
+        self.next_block_timestamp = Some(timestamp);
+
+        Ok(())
     }
 
-    pub(crate) async fn new_transaction(
-        to: Account,
-        blockchain: Arc<Mutex<BlockChain>>,
-    ) -> Transaction {
-        let nonce = blockchain
-            .lock()
-            .await
-            .accounts
-            .get_account(&ACCOUNT_1)
-            .unwrap_or(AccountData::new(None))
+    /// Execute a transaction against the current account state, in the block
+    /// described by the context.
+    ///
+    /// The sender's nonce is updated before execution, so a failed contract
+    /// call still consumes the nonce and is reported via the result's status.
+    pub(crate) fn execute_transaction(
+        &mut self,
+        transaction: &Transaction,
+        context: &ExecutionContext,
+    ) -> Result<ExecutionResult> {
+        let transaction_hash = transaction.transaction_hash()?;
+
+        // ignore transactions without a nonce
+        let nonce = transaction
             .nonce
-            + 1;
+            .ok_or_else(|| ChainError::MissingTransactionNonce(transaction_hash.to_string()))?;
 
-        let transaction =
-            Transaction::new(*ACCOUNT_1, Some(to), U256::from(10), Some(nonce), None).unwrap();
+        // update the nonce
+        self.accounts.update_nonce(&transaction.from, nonce)?;
 
-        transaction
-    }
+        // create the `to` account if it doesn't exist
+        if let Some(to) = transaction.to {
+            self.accounts.add_empty_account(&to)?;
+        }
 
-    pub(crate) async fn process_transactions(blockchain: Arc<Mutex<BlockChain>>) {
-        blockchain
-            .lock()
-            .await
-            .process_transactions()
-            .await
-            .unwrap();
-    }
+        let mut contract_address: Option<Account> = None;
+        let mut error: Option<String> = None;
+        let mut gas_used = transaction.intrinsic_gas();
+
+        // TODO(ddimaria): remove this copy
+        match transaction.to_owned().kind()? {
+            TransactionKind::Regular(from, to, value) => {
+                let balance = self.accounts.get_account(&from)?.balance;
+
+                if balance < value {
+                    error = Some(
+                        ChainError::InsufficientFunds(
+                            from.to_string(),
+                            balance.to_string(),
+                            value.to_string(),
+                        )
+                        .to_string(),
+                    );
+                } else {
+                    self.accounts.transfer(&from, &to, value)?;
+                }
+            }
+            TransactionKind::ContractDeployment(from, data) => {
+                contract_address = self.accounts.add_contract_account(&from, data).ok();
+            }
+            TransactionKind::ContractExecution(from, to, data) => {
+                self.accounts
+                    .get_account(&to)?
+                    .code_hash
+                    .ok_or_else(|| ChainError::NotAContractAccount(to.to_string()))?;
+                let (function, params): (&str, Vec<&str>) = bincode::deserialize(&data)?;
+
+                // a call can use the transaction's gas limit, short of what
+                // is left of the block's, once the intrinsic gas is paid
+                let gas_limit = transaction.gas.min(U256::from(context.gas_limit));
+                let context = ExecutionContext {
+                    caller: format!("{:?}", from),
+                    callee: format!("{:?}", to),
+                    value: transaction.value.to_string(),
+                    input: Input::new(function, &params),
+                    gas: gas_limit.saturating_sub(gas_used).low_u64(),
+                    ..context.clone()
+                };
+                let state = ContractState::new(self.accounts.at_root(self.accounts.root_hash()?)?);
+
+                // call the function in the contract, which may call other contracts
+                let execution = runtime::contract::execute(Arc::new(state), context);
+                gas_used += U256::from(execution.gas_used);
+
+                match execution.changes {
+                    Ok(changes) => {
+                        self.apply_self_destructs(&execution.self_destructs)?;
+
+                        for ((address, key), value) in changes {
+                            let address = Account::from_str(&address).map_err(|e| {
+                                ChainError::RuntimeError(address.clone(), e.to_string())
+                            })?;
+
+                            self.accounts.set_storage(&address, &key, &value)?;
+                        }
+                    }
+                    Err(e) => {
+                        error = Some(
+                            ChainError::RuntimeError(to.to_string(), e.to_string()).to_string(),
+                        );
+                    }
+                }
+            }
+        };
 
-    pub(crate) async fn assert_receipt(blockchain: Arc<Mutex<BlockChain>>, transaction_hash: H256) {
-        process_transactions(blockchain.clone()).await;
+        let status = if error.is_none() {
+            U64::one()
+        } else {
+            U64::zero()
+        };
+
+        Ok(ExecutionResult {
+            status,
+            gas_used,
+            contract_address,
+            logs: vec![],
+            error,
+        })
+    }
+
+    /// Re-execute a mined transaction against the state just before its block.
+    ///
+    /// Transactions preceding it in the same block are replayed first so the
+    /// result matches what was produced when the block was created.
+    /// The current state is left untouched.
+    pub(crate) fn replay_transaction(&mut self, transaction_hash: H256) -> Result<ExecutionResult> {
+        let (block, index) = self.find_transaction(transaction_hash)?;
+        let pre_state_root = self
+            .world_state
+            .pre_state_root(block.number)
+            .ok_or_else(|| ChainError::StateNotFound(format!("block {}", block.number)))?;
+        let pre_state = self.accounts.at_root(pre_state_root)?;
+        let current_state = std::mem::replace(&mut self.accounts, pre_state);
+        let context = block_context(&block);
+
+        let execution_result = block.transactions[..=index]
+            .iter()
+            .map(|transaction| self.execute_transaction(transaction, &context))
+            .collect::<Result<Vec<_>>>()
+            .map(|mut results| results.pop());
+
+        self.accounts = current_state;
+
+        execution_result?
+            .ok_or_else(|| ChainError::TransactionNotFound(transaction_hash.to_string()))
+    }
+
+    /// Execute transactions in order against a fork of the state at `block`.
+    ///
+    /// Each transaction sees the effects of the ones before it, but nothing is
+    /// committed. The pending state is the latest state with the mempool applied.
+    /// Transactions that cannot be executed are reported as failures.
+    pub(crate) async fn simulate_bundle(
+        &mut self,
+        transaction_requests: Vec<TransactionRequest>,
+        block: BlockTag,
+    ) -> Result<Vec<ExecutionResult>> {
+        self.simulate_at(block, |blockchain, context| {
+            transaction_requests
+                .into_iter()
+                .map(|transaction_request| {
+                    let transaction =
+                        blockchain.simulated_transaction(transaction_request, context)?;
+                    let execution_result = blockchain
+                        .execute_transaction(&transaction, context)
+                        .unwrap_or_else(|error| {
+                            ExecutionResult::failure(transaction.intrinsic_gas(), error.to_string())
+                        });
+
+                    Ok(execution_result)
+                })
+                .collect()
+        })
+        .await
+    }
+
+    /// Run `simulate` against a fork of the state at `block`, restoring the
+    /// current state afterwards
+    async fn simulate_at<T>(
+        &mut self,
+        block: BlockTag,
+        simulate: impl FnOnce(&mut Self, &ExecutionContext) -> Result<T>,
+    ) -> Result<T> {
+        let (root, context) = match block.number() {
+            Some(number) => {
+                let block = self.get_block_by_number(number)?;
+                (block.state_root, block_context(&block))
+            }
+            None => (self.accounts.root_hash()?, self.next_block_context()?),
+        };
+        // the genesis block has no state, so it's simulated against an empty one
+        let fork = match root.is_zero() {
+            true => AccountStorage::new(Arc::clone(self.accounts.storage())),
+            false => self.accounts.at_root(root)?,
+        };
+        let current_state = std::mem::replace(&mut self.accounts, fork);
+
+        if block == BlockTag::Pending {
+            let mempool = self.transactions.lock().await.mempool.clone();
+
+            for transaction in mempool.iter() {
+                // pending transactions that fail are skipped, as they would be in a block
+                if let Err(error) = self.execute_transaction(transaction, &context) {
+                    tracing::warn!("Could not simulate pending transaction: {}", error);
+                }
+            }
+        }
+
+        let simulated = simulate(self, &context);
+
+        self.accounts = current_state;
+
+        simulated
+    }
+
+    /// Execute a transaction against the state at a block without committing it.
+    /// Like `eth_call`, the nonce is ignored.
+    pub(crate) async fn call(
+        &mut self,
+        transaction_request: TransactionRequest,
+        block: impl Into<BlockId>,
+    ) -> Result<Bytes> {
+        let block = self.block_tag(block.into())?;
+        self.simulate_call(transaction_request, block).await?;
+
+        // the runtime doesn't return data from calls yet
+        Ok(Bytes::new())
+    }
+
+    /// The gas a transaction uses when executed against the state at a block.
+    /// Like `eth_call`, the nonce is ignored, and transactions that would
+    /// revert can't be estimated.
+    pub(crate) async fn estimate_gas(
+        &mut self,
+        transaction_request: TransactionRequest,
+        block: BlockTag,
+    ) -> Result<U256> {
+        let execution_result = self.simulate_call(transaction_request, block).await?;
+
+        Ok(execution_result.gas_used)
+    }
+
+    /// Execute a single transaction without committing it, failing if it
+    /// reverts. A transaction that can't run at all, such as one from an
+    /// unknown sender or one sending more than the sender has, fails with
+    /// why rather than as a revert.
+    async fn simulate_call(
+        &mut self,
+        mut transaction_request: TransactionRequest,
+        block: BlockTag,
+    ) -> Result<ExecutionResult> {
+        transaction_request.nonce = None;
+
+        let execution_result = self
+            .simulate_at(block, |blockchain, context| {
+                let transaction = blockchain.simulated_transaction(transaction_request, context)?;
+                let balance = blockchain.accounts.get_account(&transaction.from)?.balance;
+
+                if balance < transaction.value {
+                    return Err(ChainError::InsufficientFunds(
+                        transaction.from.to_string(),
+                        balance.to_string(),
+                        transaction.value.to_string(),
+                    ));
+                }
+
+                blockchain.execute_transaction(&transaction, context)
+            })
+            .await?;
+
+        if !execution_result.is_success() {
+            return Err(ChainError::ExecutionReverted(
+                execution_result.error.unwrap_or_default(),
+            ));
+        }
+
+        Ok(execution_result)
+    }
+
+    /// The transaction a request runs as when simulated, with the block's gas
+    /// limit when it has none and the sender's next nonce when it has none
+    fn simulated_transaction(
+        &self,
+        transaction_request: TransactionRequest,
+        context: &ExecutionContext,
+    ) -> Result<Transaction> {
+        let gas = transaction_request.gas;
+        let mut transaction: Transaction = transaction_request.try_into()?;
+
+        // like nodes, a call without a gas limit can use the block's
+        if gas.is_zero() {
+            transaction.gas = U256::from(context.gas_limit);
+            transaction.hash()?;
+        }
+
+        if transaction.nonce.is_none() {
+            let nonce = self
+                .accounts
+                .get_account(&transaction.from)
+                .map_or(U256::zero(), |account| account.nonce);
+            transaction.nonce = Some(nonce + 1_u64);
+            transaction.hash()?;
+        }
+
+        Ok(transaction)
+    }
+
+    /// Locate a mined transaction, returning its block and index within the block
+    pub(crate) fn find_transaction(&self, transaction_hash: H256) -> Result<(Block, usize)> {
+        self.blocks
+            .iter()
+            .find_map(|block| {
+                block
+                    .transactions
+                    .iter()
+                    .position(|transaction| transaction.hash == Some(transaction_hash))
+                    .map(|index| (block.to_owned(), index))
+            })
+            .ok_or_else(|| ChainError::TransactionNotFound(transaction_hash.to_string()))
+    }
+
+    pub(crate) async fn get_transaction_receipt(
+        &mut self,
+        transaction_hash: H256,
+    ) -> Result<TransactionReceipt> {
+        let transaction_receipt = self
+            .transactions
+            .lock()
+            .await
+            .get_transaction_receipt(&transaction_hash)?;
+
+        Ok(transaction_receipt)
+    }
+
+    /// Receipts for every transaction in a block, in block order.
+    ///
+    /// Transactions in the pending block have not been executed, so it has no receipts.
+    pub(crate) async fn get_block_receipts(
+        &self,
+        block: BlockTag,
+    ) -> Result<Vec<TransactionReceipt>> {
+        let block = match block {
+            BlockTag::Pending => return Ok(vec![]),
+            BlockTag::Latest => self.get_current_block()?,
+            BlockTag::Earliest => self.get_block_by_number(U64::zero())?,
+            BlockTag::Number(number) => self.get_block_by_number(number)?,
+        };
+        let storage = self.transactions.lock().await;
+
+        block
+            .transactions
+            .iter()
+            .map(|transaction| storage.get_transaction_receipt(&transaction.transaction_hash()?))
+            .collect()
+    }
+
+    /// The fees paid in up to `block_count` blocks ending at `newest_block`.
+    ///
+    /// Rewards are the effective tips at each percentile of the gas used in a
+    /// block, weighting each transaction by the gas it used (as geth does).
+    pub(crate) async fn fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockTag,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> Result<FeeHistory> {
+        if let Some(percentiles) = &reward_percentiles {
+            validate_reward_percentiles(percentiles)?;
+        }
+
+        // the pending block has no receipts yet, so it ends at the latest block
+        let newest = match newest_block {
+            BlockTag::Latest | BlockTag::Pending => self.get_current_block()?.number,
+            BlockTag::Earliest => U64::zero(),
+            BlockTag::Number(number) => self.get_block_by_number(number)?.number,
+        };
+        // like geth, a history reaching past the pruned blocks covers the
+        // blocks that remain
+        let first = self
+            .blocks
+            .front()
+            .map_or(U64::zero(), |block| block.number);
+        let block_count = block_count
+            .min(MAX_FEE_HISTORY_BLOCKS)
+            .min((newest.as_u64() + 1).saturating_sub(first.as_u64()));
+        let oldest = newest + 1_u64 - block_count;
+        let mut gas_used_ratio = vec![];
+        let mut reward = vec![];
+
+        for number in oldest.as_u64()..=newest.as_u64() {
+            let block = self.get_block_by_number(U64::from(number))?;
+            let receipts = self
+                .get_block_receipts(BlockTag::Number(block.number))
+                .await?;
+            let gas_used = receipts.iter().fold(U256::zero(), |total, receipt| {
+                total.saturating_add(receipt.gas_used)
+            });
+
+            gas_used_ratio.push(match block.gas_limit.is_zero() {
+                true => 0.0,
+                false => gas_as_f64(gas_used) / gas_as_f64(block.gas_limit),
+            });
+
+            if let Some(percentiles) = &reward_percentiles {
+                let tips = block
+                    .transactions
+                    .iter()
+                    .zip(receipts.iter())
+                    .map(|(transaction, receipt)| {
+                        (transaction.effective_tip(BASE_FEE), receipt.gas_used)
+                    })
+                    .collect();
+
+                reward.push(rewards(tips, gas_used, percentiles));
+            }
+        }
+
+        Ok(FeeHistory {
+            oldest_block: oldest,
+            base_fee_per_gas: vec![BASE_FEE; block_count as usize + 1],
+            gas_used_ratio,
+            reward: reward_percentiles.map(|_| reward),
+        })
+    }
+}
+
+/// Percentiles must be between 0 and 100, in increasing order
+fn validate_reward_percentiles(percentiles: &[f64]) -> Result<()> {
+    let in_range = percentiles
+        .iter()
+        .all(|percentile| (0.0..=100.0).contains(percentile));
+    let increasing = percentiles.windows(2).all(|pair| pair[0] <= pair[1]);
+
+    if !in_range || !increasing {
+        return Err(ChainError::InvalidRewardPercentiles(format!(
+            "{:?}",
+            percentiles
+        )));
+    }
+
+    Ok(())
+}
+
+/// The tip at each percentile of a block's gas used, given each transaction's
+/// `(effective tip, gas used)`. A block without transactions rewards nothing.
+fn rewards(mut tips: Vec<(U256, U256)>, gas_used: U256, percentiles: &[f64]) -> Vec<U256> {
+    if tips.is_empty() {
+        return vec![U256::zero(); percentiles.len()];
+    }
+
+    tips.sort_by_key(|(tip, _)| *tip);
+
+    let mut index = 0;
+    let mut cumulative_gas = gas_as_f64(tips[0].1);
+
+    percentiles
+        .iter()
+        .map(|percentile| {
+            let threshold = gas_as_f64(gas_used) * percentile / 100.0;
+
+            while cumulative_gas < threshold && index < tips.len() - 1 {
+                index += 1;
+                cumulative_gas += gas_as_f64(tips[index].1);
+            }
+
+            tips[index].0
+        })
+        .collect()
+}
+
+/// Gas as a float for ratios, saturating at `u64::MAX` rather than panicking
+/// on an amount of gas no block could hold
+fn gas_as_f64(gas: U256) -> f64 {
+    gas.min(U256::from(u64::MAX)).as_u64() as f64
+}
+
+/// The context of transactions in a block, before the call itself is known.
+/// Blocks don't record a beneficiary and fees aren't paid out, so the
+/// coinbase is the zero address.
+fn block_context(block: &Block) -> ExecutionContext {
+    ExecutionContext {
+        block: block.number.as_u64(),
+        coinbase: format!("{:?}", Account::zero()),
+        timestamp: block.timestamp.as_u64(),
+        base_fee: BASE_FEE.low_u64(),
+        gas_limit: block.gas_limit.low_u64(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use types::account::AccountData;
+    use utils::crypto::{dev_account, keypair, public_key_address};
+    use utils::SecretKey;
+
+    use super::*;
+    use crate::{
+        consensus::ProofOfAuthority,
+        helpers::tests::{setup, ACCOUNT_1, STORAGE},
+        transaction,
+    };
+
+    pub(crate) fn new_blockchain() -> BlockChain {
+        BlockChain::new((*STORAGE).clone()).unwrap()
+    }
+
+    pub(crate) async fn new_transaction(
+        to: Account,
+        blockchain: Arc<Mutex<BlockChain>>,
+    ) -> Transaction {
+        let nonce = blockchain
+            .lock()
+            .await
+            .accounts
+            .get_account(&ACCOUNT_1)
+            .unwrap_or(AccountData::new(None))
+            .nonce
+            + 1;
+
+        let transaction =
+            Transaction::new(*ACCOUNT_1, Some(to), U256::from(10), Some(nonce), None).unwrap();
+
+        transaction
+    }
+
+    pub(crate) async fn produce_block(blockchain: Arc<Mutex<BlockChain>>) {
+        blockchain.lock().await.produce_block().await.unwrap();
+    }
+
+    pub(crate) async fn assert_receipt(blockchain: Arc<Mutex<BlockChain>>, transaction_hash: H256) {
+        produce_block(blockchain.clone()).await;
 
         let receipt = blockchain
             .lock()
@@ -355,16 +1496,354 @@ pub(crate) mod tests {
         let (blockchain, _, _) = setup().await;
         let block_number = blockchain.lock().await.get_current_block().unwrap().number;
         let transaction = new_transaction(Account::random(), blockchain.clone()).await;
-        let response = blockchain
-            .lock()
-            .await
-            .new_block(vec![transaction], H256::zero());
+        let response =
+            blockchain
+                .lock()
+                .await
+                .new_block(vec![transaction], &[], H256::zero(), U64::zero());
         assert!(response.is_ok());
 
         let new_block_number = blockchain.lock().await.get_current_block().unwrap().number;
         assert_eq!(new_block_number, block_number + 1);
     }
 
+    #[tokio::test]
+    async fn imports_a_block_sealed_by_an_authority() {
+        let mut blockchain = new_blockchain();
+        let (authority_key, authority) = dev_account(0);
+        let (outsider_key, outsider) = dev_account(1);
+        blockchain.consensus = Arc::new(ProofOfAuthority::new(vec![authority], None));
+
+        let block = |key: SecretKey, signer: Account, blockchain: &mut BlockChain| {
+            let parent = blockchain.get_current_block().unwrap();
+            let mut block = Block::new(
+                parent.number + 1_u64,
+                parent.block_hash().unwrap(),
+                vec![],
+                &[],
+                blockchain.accounts.root_hash().unwrap(),
+                parent.gas_limit,
+                parent.timestamp + 1_u64,
+            )
+            .unwrap();
+            ProofOfAuthority::new(vec![signer], Some(key))
+                .seal_block(&mut block)
+                .unwrap();
+
+            block
+        };
+
+        let unauthorized = block(outsider_key, outsider, &mut blockchain);
+        assert!(matches!(
+            blockchain.import_block(unauthorized).await,
+            Err(ChainError::InvalidBlock(_))
+        ));
+
+        let authorized = block(authority_key, authority, &mut blockchain);
+        let imported = blockchain.import_block(authorized.clone()).await.unwrap();
+        assert_eq!(imported, authorized);
+        assert_eq!(blockchain.get_current_block().unwrap(), authorized);
+    }
+
+    #[tokio::test]
+    async fn imports_a_long_chain_keeping_only_the_retained_blocks() {
+        let retention = 8;
+        let (sender, recipient) = (Account::random(), Account::random());
+        let mut sender_data = AccountData::new(None);
+        sender_data.balance = U256::from(1_000);
+        let mut producer = new_blockchain();
+        // pruning deletes trie nodes, so the importer has storage of its own
+        let mut importer =
+            BlockChain::new(Arc::new(Storage::new(Some("retention")).unwrap())).unwrap();
+        importer.set_retention(retention);
+
+        for blockchain in [&mut producer, &mut importer] {
+            blockchain
+                .accounts
+                .add_account(&sender, &sender_data)
+                .unwrap();
+        }
+
+        // a synthetic chain with a transfer in every block
+        let mut blocks = vec![];
+
+        for _ in 0..200 {
+            let transfer = TransactionRequest {
+                from: Some(sender),
+                to: Some(recipient),
+                value: Some(U256::one()),
+                ..Default::default()
+            };
+            producer.send_transaction(transfer).await.unwrap();
+            blocks.push(producer.produce_block().await.unwrap().unwrap());
+        }
+
+        // a transaction dropped before the sync, with its failed receipt
+        let dropped = Transaction::new(
+            sender,
+            Some(recipient),
+            U256::one(),
+            Some(U256::from(1_000)),
+            None,
+        )
+        .unwrap();
+        let dropped_hash = dropped.transaction_hash().unwrap();
+        importer
+            .transactions
+            .lock()
+            .await
+            .drop_transaction(&dropped, "nonce too high".into());
+        let pruned_state_root = blocks[99].state_root;
+
+        // the trie nodes stored after each block, to bound the state's size
+        let storage = importer.accounts.storage().clone();
+        let mut progress = vec![];
+        let mut stored_nodes = vec![];
+        let imported = importer
+            .import_blocks(futures::stream::iter(blocks), |update| {
+                progress.push(update);
+                stored_nodes.push(storage._get_all_keys::<Vec<u8>>().unwrap().len());
+            })
+            .await
+            .unwrap();
+        let head = U64::from(200);
+
+        assert_eq!(imported, 200);
+        assert_eq!(progress.len(), 200);
+        assert_eq!(progress.last(), Some(&ImportProgress { imported, head }));
+        // the stored state stops growing once pruning has caught up, rather
+        // than growing with the chain
+        let (earlier, later) = stored_nodes.split_at(100);
+        let settled = earlier[4 * retention as usize..].iter().max().unwrap();
+        assert!(later.iter().max().unwrap() <= settled);
+        // only the retained blocks and their receipts are held in memory
+        assert_eq!(importer.blocks.len() as u64, retention + 1);
+        assert_eq!(
+            importer.transactions.lock().await.receipts.len() as u64,
+            retention + 1
+        );
+
+        // ancient blocks are gone, while the latest state is intact
+        let oldest = head - retention;
+        assert!(importer.get_block_by_number(oldest).is_ok());
+        assert!(matches!(
+            importer.get_block_by_number(oldest - 1_u64),
+            Err(ChainError::BlockPruned(_))
+        ));
+        assert!(importer
+            .world_state
+            .pre_state_root(oldest - 1_u64)
+            .is_none());
+        assert!(importer
+            .transactions
+            .lock()
+            .await
+            .get_dropped_reason(&dropped_hash)
+            .is_none());
+
+        // a fee history reaching past them covers only the retained blocks
+        let fee_history = importer
+            .fee_history(MAX_FEE_HISTORY_BLOCKS, BlockTag::Latest, Some(vec![50.0]))
+            .await
+            .unwrap();
+        assert_eq!(fee_history.oldest_block, oldest);
+        assert_eq!(fee_history.gas_used_ratio.len() as u64, retention + 1);
+
+        // as is the state of ancient blocks, while that of retained ones remains
+        assert!(importer.accounts.at_root(pruned_state_root).is_err());
+        let oldest_state_root = importer.get_block_by_number(oldest).unwrap().state_root;
+        assert_eq!(
+            importer
+                .accounts
+                .at_root(oldest_state_root)
+                .unwrap()
+                .get_account(&recipient)
+                .unwrap()
+                .balance,
+            U256::from(oldest.as_u64())
+        );
+        assert_eq!(
+            importer.get_current_block().unwrap(),
+            producer.get_current_block().unwrap()
+        );
+        assert_eq!(
+            importer
+                .get_balance_by_block(&recipient, BlockTag::Latest)
+                .await
+                .unwrap(),
+            U256::from(200)
+        );
+    }
+
+    #[tokio::test]
+    async fn restores_a_persisted_chain_with_its_state_and_receipts() {
+        let (sender, recipient) = (Account::random(), Account::random());
+        let mut sender_data = AccountData::new(None);
+        sender_data.balance = U256::from(1_000);
+        let mut persisted = new_blockchain();
+        let mut restored = new_blockchain();
+
+        for blockchain in [&mut persisted, &mut restored] {
+            blockchain
+                .accounts
+                .add_account(&sender, &sender_data)
+                .unwrap();
+        }
+
+        let transfer = |nonce: u64| TransactionRequest {
+            from: Some(sender),
+            to: Some(recipient),
+            value: Some(U256::one()),
+            nonce: Some(U256::from(nonce)),
+            ..Default::default()
+        };
+        let mined = persisted.send_transaction(transfer(1)).await.unwrap();
+        persisted.produce_block().await.unwrap();
+        let pending = persisted.send_transaction(transfer(2)).await.unwrap();
+
+        let path = std::env::temp_dir().join(format!("chain-{:?}.json", H256::random()));
+        persisted.persist(&path).await.unwrap();
+        restored.restore(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            restored.get_current_block().unwrap(),
+            persisted.get_current_block().unwrap()
+        );
+        assert_eq!(
+            restored.accounts.get_account(&sender).unwrap().nonce,
+            U256::one()
+        );
+
+        let transactions = restored.transactions.lock().await;
+        assert_eq!(
+            transactions.get_transaction_receipt(&mined).unwrap(),
+            persisted
+                .transactions
+                .lock()
+                .await
+                .get_transaction_receipt(&mined)
+                .unwrap()
+        );
+        assert!(transactions.mempool.contains(&pending));
+    }
+
+    #[tokio::test]
+    async fn restores_a_persisted_chain_whose_old_blocks_were_pruned() {
+        let retention = 4;
+        let (sender, recipient) = (Account::random(), Account::random());
+        let mut sender_data = AccountData::new(None);
+        sender_data.balance = U256::from(1_000);
+        // pruning deletes trie nodes, so the persisted chain has storage of
+        // its own, which the restored chain can't read the state from
+        let mut persisted =
+            BlockChain::new(Arc::new(Storage::new(Some("restore-retention")).unwrap())).unwrap();
+        persisted.set_retention(retention);
+        persisted
+            .accounts
+            .add_account(&sender, &sender_data)
+            .unwrap();
+        let mut restored = new_blockchain();
+
+        let mut mined = vec![];
+
+        for _ in 0..(3 * retention) {
+            let transfer = TransactionRequest {
+                from: Some(sender),
+                to: Some(recipient),
+                value: Some(U256::one()),
+                ..Default::default()
+            };
+            mined.push(persisted.send_transaction(transfer).await.unwrap());
+            persisted.produce_block().await.unwrap();
+        }
+
+        let oldest = persisted.blocks.front().unwrap().number;
+        assert!(oldest > U64::one());
+
+        let path = std::env::temp_dir().join(format!("chain-{:?}.json", H256::random()));
+        persisted.persist(&path).await.unwrap();
+        restored.restore(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.blocks, persisted.blocks);
+        assert_eq!(
+            restored.accounts.root_hash().unwrap(),
+            persisted.accounts.root_hash().unwrap()
+        );
+        assert_eq!(
+            restored
+                .get_balance_by_block(&recipient, BlockTag::Latest)
+                .await
+                .unwrap(),
+            U256::from(3 * retention)
+        );
+        assert_eq!(
+            restored
+                .get_account_by_block(&recipient, BlockTag::Number(oldest))
+                .unwrap()
+                .balance,
+            U256::from(oldest.as_u64())
+        );
+
+        // the receipts of every retained block are back, including the oldest's
+        let transactions = restored.transactions.lock().await;
+
+        for transaction_hash in &mined[oldest.as_usize() - 1..] {
+            assert_eq!(
+                transactions
+                    .get_transaction_receipt(transaction_hash)
+                    .unwrap(),
+                persisted
+                    .transactions
+                    .lock()
+                    .await
+                    .get_transaction_receipt(transaction_hash)
+                    .unwrap()
+            );
+        }
+        drop(transactions);
+
+        // and the restored chain carries on from the persisted head
+        let transfer = TransactionRequest {
+            from: Some(sender),
+            to: Some(recipient),
+            value: Some(U256::one()),
+            ..Default::default()
+        };
+        restored.send_transaction(transfer).await.unwrap();
+        assert!(restored.produce_block().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn adjusts_the_gas_limit_toward_the_target() {
+        let mut blockchain = new_blockchain();
+        let start = blockchain.get_current_block().unwrap().gas_limit;
+        let target = start + 100_000;
+        blockchain.gas_limit_target = target;
+
+        let mut parent = start;
+
+        // each block moves by less than 1/1024th of its parent's limit
+        while parent < target {
+            let block = blockchain
+                .new_block(vec![], &[], H256::zero(), U64::zero())
+                .unwrap();
+            assert!(block.gas_limit > parent);
+            assert!(block.gas_limit - parent < parent / 1024);
+            parent = block.gas_limit;
+        }
+
+        // converges on the target without overshooting it
+        assert_eq!(parent, target);
+        assert_eq!(blockchain.get_current_block().unwrap().number, U64::from(4));
+
+        let block = blockchain
+            .new_block(vec![], &[], H256::zero(), U64::zero())
+            .unwrap();
+        assert_eq!(block.gas_limit, target);
+    }
+
     #[tokio::test]
     async fn sends_a_transaction() {
         let (blockchain, _, _) = setup().await;
@@ -387,14 +1866,26 @@ pub(crate) mod tests {
     async fn send_a_raw_transaction() {
         let (blockchain, _, _) = setup().await;
         let to = Account::random();
-        let (secret_key, _) = keypair();
-        let transaction = new_transaction(to, blockchain.clone()).await;
+        let (secret_key, public_key) = keypair();
+        let sender = public_key_address(&public_key);
+        let mut sender_data = AccountData::new(None);
+        sender_data.balance = U256::from(100);
+        blockchain
+            .lock()
+            .await
+            .accounts
+            .add_account(&sender, &sender_data)
+            .unwrap();
+
+        // the sender is recovered from the signature
+        let transaction =
+            Transaction::new(sender, Some(to), U256::from(10), Some(U256::one()), None).unwrap();
         let signed_transaction = transaction.sign(secret_key).unwrap();
-        let encoded = bincode::serialize(&signed_transaction).unwrap();
+        let envelope = signed_transaction.to_envelope().unwrap();
         let response = blockchain
             .lock()
             .await
-            .send_raw_transaction(encoded.into())
+            .send_raw_transaction(envelope.into())
             .await
             .unwrap();
 
@@ -403,4 +1894,899 @@ pub(crate) mod tests {
         let balance = get_balance(blockchain, &to).await;
         assert_eq!(balance, U256::from(10));
     }
+
+    #[tokio::test]
+    async fn rejects_a_malformed_raw_transaction() {
+        let mut blockchain = new_blockchain();
+        let response = blockchain
+            .send_raw_transaction(vec![0x02, 0xc0].into())
+            .await;
+
+        assert!(matches!(response, Err(ChainError::InvalidTransaction(_))));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_raw_transaction_for_another_chain() {
+        let mut blockchain = new_blockchain();
+        let (secret_key, public_key) = keypair();
+        let sender = public_key_address(&public_key);
+        let mut transaction = Transaction::new(
+            sender,
+            Some(Account::random()),
+            U256::from(10),
+            Some(U256::one()),
+            None,
+        )
+        .unwrap();
+        transaction.chain_id = Some(U64::from(1));
+        let envelope: Bytes = transaction
+            .sign(secret_key)
+            .unwrap()
+            .to_envelope()
+            .unwrap()
+            .into();
+        let response = blockchain.send_raw_transaction(envelope.clone()).await;
+
+        assert!(matches!(response, Err(ChainError::InvalidTransaction(_))));
+
+        // it's meant for a chain configured with that id
+        blockchain.chain_id = 1;
+        let response = blockchain.send_raw_transaction(envelope).await;
+        assert!(!matches!(response, Err(ChainError::InvalidTransaction(_))));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_raw_transaction_with_a_malleated_signature() {
+        let mut blockchain = new_blockchain();
+        let (secret_key, public_key) = keypair();
+        let mut transaction = Transaction::new(
+            public_key_address(&public_key),
+            Some(Account::random()),
+            U256::from(10),
+            Some(U256::one()),
+            None,
+        )
+        .unwrap();
+        transaction.chain_id = Some(U64::from(blockchain.chain_id));
+        let mut signed_transaction = transaction.sign(secret_key).unwrap();
+
+        // (r, n - s) with the other recovery id is also a valid signature, but
+        // only the low s form is accepted
+        let order =
+            U256::from_str("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+                .unwrap();
+        let mut s = [0; 32];
+        (order - U256::from_big_endian(signed_transaction.s.as_bytes())).to_big_endian(&mut s);
+        signed_transaction.s = H256::from(s);
+        signed_transaction.v ^= 1;
+        let envelope = signed_transaction.to_envelope().unwrap();
+        let response = blockchain.send_raw_transaction(envelope.into()).await;
+
+        assert!(matches!(response, Err(ChainError::InvalidSignature(_))));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_transaction_whose_cost_overflows() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let transaction_request = TransactionRequest {
+            from: Some(id_1),
+            to: Some(id_2),
+            value: Some(U256::MAX),
+            ..Default::default()
+        };
+        let response = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction_request)
+            .await;
+
+        assert!(matches!(response, Err(ChainError::InvalidTransaction(_))));
+    }
+
+    #[tokio::test]
+    async fn counts_produced_blocks_and_transactions() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let nonce = blockchain.next_nonce(&id_1).unwrap();
+        let transfers = (0..4u64).map(|index| TransactionRequest {
+            from: Some(id_1),
+            to: Some(id_2),
+            value: Some(U256::one()),
+            nonce: Some(nonce + index),
+            ..Default::default()
+        });
+
+        // two blocks, of two transfers and one, with one left pending
+        for (index, transfer) in transfers.enumerate() {
+            blockchain.send_transaction(transfer).await.unwrap();
+
+            if index == 1 || index == 2 {
+                blockchain.produce_block().await.unwrap().unwrap();
+            }
+        }
+
+        let stats = blockchain.stats().await;
+
+        assert_eq!(stats.blocks_produced, 2);
+        assert_eq!(stats.transactions_processed, 3);
+        assert_eq!(stats.transactions_admitted, 4);
+        assert_eq!(stats.mempool_size, 1);
+        assert!(stats.gas_used > U256::zero());
+        assert_eq!(stats.average_gas_per_block, stats.gas_used / 2);
+        assert!(stats.blocks_per_second > 0.0);
+    }
+
+    #[tokio::test]
+    async fn gets_a_pending_balance() {
+        let (blockchain, _, _) = setup().await;
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+
+        let latest = blockchain
+            .lock()
+            .await
+            .get_balance_by_block(&ACCOUNT_1, BlockTag::Latest)
+            .await
+            .unwrap();
+        let pending = blockchain
+            .lock()
+            .await
+            .get_balance_by_block(&ACCOUNT_1, BlockTag::Pending)
+            .await
+            .unwrap();
+
+        assert_eq!(pending, latest - U256::from(10));
+    }
+
+    #[tokio::test]
+    async fn a_pending_transfer_to_itself_leaves_the_pending_balance() {
+        let (blockchain, _, _) = setup().await;
+        let transaction = new_transaction(*ACCOUNT_1, blockchain.clone()).await;
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+
+        let latest = blockchain
+            .lock()
+            .await
+            .get_balance_by_block(&ACCOUNT_1, BlockTag::Latest)
+            .await
+            .unwrap();
+        let pending = blockchain
+            .lock()
+            .await
+            .get_balance_by_block(&ACCOUNT_1, BlockTag::Pending)
+            .await
+            .unwrap();
+
+        assert_eq!(pending, latest);
+    }
+
+    #[tokio::test]
+    async fn simulates_a_bundle() {
+        let (blockchain, _, _) = setup().await;
+        let (funded, recipient) = (Account::random(), Account::random());
+        let fund = TransactionRequest {
+            from: Some(*ACCOUNT_1),
+            to: Some(funded),
+            value: Some(U256::from(100)),
+            ..Default::default()
+        };
+        let transfer = TransactionRequest {
+            from: Some(funded),
+            to: Some(recipient),
+            value: Some(U256::from(50)),
+            ..Default::default()
+        };
+        let balance = get_balance(blockchain.clone(), &ACCOUNT_1).await;
+
+        // the transfer alone fails, as the account has no funds
+        let results = blockchain
+            .lock()
+            .await
+            .simulate_bundle(vec![transfer.clone()], BlockTag::Latest)
+            .await
+            .unwrap();
+        assert!(!results[0].is_success());
+
+        // funding the account first lets the transfer succeed
+        let results = blockchain
+            .lock()
+            .await
+            .simulate_bundle(vec![fund, transfer], BlockTag::Latest)
+            .await
+            .unwrap();
+        assert!(results.iter().all(ExecutionResult::is_success));
+
+        // nothing was committed
+        assert_eq!(get_balance(blockchain.clone(), &ACCOUNT_1).await, balance);
+        assert!(blockchain
+            .lock()
+            .await
+            .accounts
+            .get_account(&funded)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn simulates_at_the_earliest_block() {
+        let (blockchain, _, _) = setup().await;
+        let transfer = TransactionRequest {
+            from: Some(*ACCOUNT_1),
+            to: Some(Account::random()),
+            value: Some(U256::from(50)),
+            ..Default::default()
+        };
+
+        // no account existed at genesis, so the sender is unknown there
+        let results = blockchain
+            .lock()
+            .await
+            .simulate_bundle(vec![transfer.clone()], BlockTag::Earliest)
+            .await
+            .unwrap();
+        assert!(!results[0].is_success());
+
+        let response = blockchain
+            .lock()
+            .await
+            .call(transfer, BlockTag::Earliest)
+            .await;
+        assert!(matches!(response, Err(ChainError::StorageNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn applies_self_destructs_at_the_end_of_a_transaction() {
+        let mut blockchain = new_blockchain();
+        let (contract, other, beneficiary) =
+            (Account::random(), Account::random(), Account::random());
+        let mut contract_data = AccountData::new(Some(Bytes::from(vec![0, 1])));
+        contract_data.balance = U256::from(100);
+
+        for address in [contract, other] {
+            blockchain
+                .accounts
+                .add_account(&address, &contract_data)
+                .unwrap();
+        }
+
+        let self_destructs = SelfDestructs::from([
+            (format!("{:?}", contract), format!("{:?}", beneficiary)),
+            (format!("{:?}", other), format!("{:?}", beneficiary)),
+        ]);
+        blockchain.apply_self_destructs(&self_destructs).unwrap();
+
+        // existing contracts keep their code, but their balance goes to the beneficiary
+        for address in [contract, other] {
+            let contract_data = blockchain.accounts.get_account(&address).unwrap();
+            assert_eq!(contract_data.balance, U256::zero());
+            assert!(contract_data.code_hash.is_some());
+        }
+        assert_eq!(
+            blockchain
+                .accounts
+                .get_account(&beneficiary)
+                .unwrap()
+                .balance,
+            U256::from(200)
+        );
+    }
+
+    #[tokio::test]
+    async fn runs_contracts_at_the_next_block_timestamp() {
+        let (blockchain, id_1, _) = setup().await;
+        let contract = Account::random();
+        let code = include_bytes!("./../../target/wasm32-unknown-unknown/release/storage_wit.wasm");
+        let data = bincode::serialize(&("store-timestamp", Vec::<&str>::new())).unwrap();
+        let mut blockchain = blockchain.lock().await;
+        blockchain
+            .accounts
+            .add_account(
+                &contract,
+                &AccountData::new(Some(Bytes::from(code.to_vec()))),
+            )
+            .unwrap();
+        let nonce = blockchain.accounts.get_account(&id_1).unwrap().nonce + 1;
+        let mut transaction = Transaction::new(
+            id_1,
+            Some(contract),
+            U256::zero(),
+            Some(nonce),
+            Some(Bytes::from(data)),
+        )
+        .unwrap();
+        transaction.gas = U256::from(1_000_000);
+
+        blockchain
+            .set_next_block_timestamp(U64::from(1_700_000_000))
+            .unwrap();
+        blockchain
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        blockchain.produce_block().await.unwrap();
+
+        let block = blockchain.get_current_block().unwrap();
+        assert_eq!(block.timestamp, U64::from(1_700_000_000));
+        assert_eq!(
+            blockchain
+                .accounts
+                .get_storage(&contract, "timestamp")
+                .unwrap(),
+            Some("1700000000".to_string())
+        );
+        assert_eq!(blockchain.next_block_timestamp, None);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_next_block_timestamp_not_after_the_current_block() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        blockchain
+            .set_next_block_timestamp(U64::from(1_700_000_000))
+            .unwrap();
+        blockchain.produce_block().await.unwrap();
+
+        for timestamp in [1_699_999_999, 1_700_000_000] {
+            assert!(matches!(
+                blockchain.set_next_block_timestamp(U64::from(timestamp)),
+                Err(ChainError::InvalidBlock(_))
+            ));
+        }
+        assert_eq!(blockchain.next_block_timestamp, None);
+
+        blockchain
+            .set_next_block_timestamp(U64::from(1_700_000_001))
+            .unwrap();
+        assert_eq!(
+            blockchain.next_block_timestamp,
+            Some(U64::from(1_700_000_001))
+        );
+    }
+
+    #[tokio::test]
+    async fn calls_a_transaction() {
+        let (blockchain, _, _) = setup().await;
+        let transfer = |from| TransactionRequest {
+            from: Some(from),
+            to: Some(Account::random()),
+            value: Some(U256::from(50)),
+            ..Default::default()
+        };
+
+        // a sender that doesn't exist or can't afford the value isn't a revert
+        let response = blockchain
+            .lock()
+            .await
+            .call(transfer(Account::random()), BlockTag::Latest)
+            .await;
+        assert!(matches!(response, Err(ChainError::StorageNotFound(_))));
+
+        let response = blockchain
+            .lock()
+            .await
+            .call(
+                TransactionRequest {
+                    value: Some(U256::from(1_000_000)),
+                    ..transfer(*ACCOUNT_1)
+                },
+                BlockTag::Latest,
+            )
+            .await;
+        assert!(matches!(response, Err(ChainError::InsufficientFunds(..))));
+
+        let response = blockchain
+            .lock()
+            .await
+            .call(transfer(*ACCOUNT_1), BlockTag::Latest)
+            .await;
+        assert!(response.is_ok());
+    }
+
+    #[tokio::test]
+    async fn calls_a_reverting_contract() {
+        let (blockchain, _, _) = setup().await;
+        let contract = Account::random();
+        let code = include_bytes!("./../../target/wasm32-unknown-unknown/release/storage_wit.wasm");
+        let mut blockchain = blockchain.lock().await;
+        blockchain
+            .accounts
+            .add_account(
+                &contract,
+                &AccountData::new(Some(Bytes::from(code.to_vec()))),
+            )
+            .unwrap();
+        let data = bincode::serialize(&("not-a-function", Vec::<&str>::new())).unwrap();
+        let request = TransactionRequest {
+            from: Some(*ACCOUNT_1),
+            to: Some(contract),
+            data: Some(Bytes::from(data)),
+            ..Default::default()
+        };
+
+        let response = blockchain.call(request, BlockTag::Latest).await;
+        assert!(matches!(response, Err(ChainError::ExecutionReverted(_))));
+    }
+
+    #[tokio::test]
+    async fn estimates_the_gas_of_a_transaction() {
+        let (blockchain, _, _) = setup().await;
+        let transfer = |from| TransactionRequest {
+            from: Some(from),
+            to: Some(Account::random()),
+            value: Some(U256::from(50)),
+            ..Default::default()
+        };
+
+        let response = blockchain
+            .lock()
+            .await
+            .estimate_gas(transfer(Account::random()), BlockTag::Latest)
+            .await;
+        assert!(matches!(response, Err(ChainError::StorageNotFound(_))));
+
+        let gas = blockchain
+            .lock()
+            .await
+            .estimate_gas(transfer(*ACCOUNT_1), BlockTag::Pending)
+            .await
+            .unwrap();
+        assert_eq!(gas, U256::from(21_000));
+    }
+
+    #[tokio::test]
+    async fn gets_block_receipts() {
+        let (blockchain, _, _) = setup().await;
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+
+        assert_receipt(blockchain.clone(), transaction_hash).await;
+
+        let receipt = blockchain
+            .lock()
+            .await
+            .get_transaction_receipt(transaction_hash)
+            .await
+            .unwrap();
+        let receipts = blockchain
+            .lock()
+            .await
+            .get_block_receipts(BlockTag::Latest)
+            .await
+            .unwrap();
+
+        assert_eq!(receipts, vec![receipt]);
+    }
+
+    #[tokio::test]
+    async fn gets_a_balance_by_block_hash() {
+        let (blockchain, _, _) = setup().await;
+        let block_hash = blockchain
+            .lock()
+            .await
+            .get_current_block()
+            .unwrap()
+            .block_hash()
+            .unwrap();
+        let by_hash = blockchain
+            .lock()
+            .await
+            .get_balance_by_block(&ACCOUNT_1, block_hash)
+            .await
+            .unwrap();
+        let by_tag = blockchain
+            .lock()
+            .await
+            .get_balance_by_block(&ACCOUNT_1, BlockTag::Latest)
+            .await
+            .unwrap();
+
+        assert_eq!(by_hash, by_tag);
+
+        let response = blockchain
+            .lock()
+            .await
+            .get_balance_by_block(&ACCOUNT_1, H256::zero())
+            .await;
+        assert!(matches!(response, Err(ChainError::BlockNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn reverts_the_head_block() {
+        let (blockchain, _, _) = setup().await;
+        let balance = get_balance(blockchain.clone(), &ACCOUNT_1).await;
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+
+        assert_receipt(blockchain.clone(), transaction_hash).await;
+        assert_eq!(
+            get_balance(blockchain.clone(), &ACCOUNT_1).await,
+            balance - U256::from(10)
+        );
+
+        let block = blockchain.lock().await.revert_head().await.unwrap();
+        assert_eq!(block.transactions[0].hash, Some(transaction_hash));
+        assert_eq!(get_balance(blockchain.clone(), &ACCOUNT_1).await, balance);
+
+        // the transaction is back in the mempool
+        let mempool = blockchain
+            .lock()
+            .await
+            .transactions
+            .lock()
+            .await
+            .mempool
+            .clone();
+        assert_eq!(mempool.len(), 1);
+        assert_eq!(mempool.iter().next().unwrap().hash, Some(transaction_hash));
+    }
+
+    #[tokio::test]
+    async fn restores_the_sender_nonce_when_reverting_a_block() {
+        let (blockchain, _, _) = setup().await;
+        let next_nonce = blockchain.lock().await.next_nonce(&ACCOUNT_1).unwrap();
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+
+        assert_receipt(blockchain.clone(), transaction_hash).await;
+        assert_eq!(
+            blockchain.lock().await.next_nonce(&ACCOUNT_1).unwrap(),
+            next_nonce + 1
+        );
+
+        blockchain.lock().await.revert_head().await.unwrap();
+        assert_eq!(
+            blockchain.lock().await.next_nonce(&ACCOUNT_1).unwrap(),
+            next_nonce
+        );
+
+        let readmitted = blockchain.lock().await.pending_for(&ACCOUNT_1).await;
+        assert_eq!(readmitted.len(), 1);
+        assert_eq!(readmitted[0].nonce, Some(next_nonce));
+
+        // the readmitted transaction is mined again with its original nonce
+        assert_receipt(blockchain.clone(), transaction_hash).await;
+        assert_eq!(
+            blockchain.lock().await.next_nonce(&ACCOUNT_1).unwrap(),
+            next_nonce + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn mines_the_transaction_that_replaced_another() {
+        let (blockchain, _, _) = setup().await;
+        let mut transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let sent = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.clone().into())
+            .await
+            .unwrap();
+
+        // speed up the transaction with a higher fee
+        transaction.max_fee_per_gas = Some(U256::from(100));
+        let replacement = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        assert_ne!(sent, replacement);
+        assert_eq!(
+            blockchain.lock().await.pending_for(&ACCOUNT_1).await.len(),
+            1
+        );
+
+        assert_receipt(blockchain.clone(), replacement).await;
+        assert!(blockchain
+            .lock()
+            .await
+            .get_transaction_receipt(sent)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn leaves_transactions_past_the_block_gas_limit_for_the_next_block() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let sender = Account::random();
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(100);
+        blockchain
+            .accounts
+            .add_account(&sender, &account_data)
+            .unwrap();
+
+        // room for two transfers in each block
+        let gas_limit = U256::from(42_000);
+        blockchain.blocks.back_mut().unwrap().gas_limit = gas_limit;
+        blockchain.gas_limit_target = gas_limit;
+
+        for nonce in 1..=3 {
+            let transfer = Transaction::new(
+                sender,
+                Some(Account::random()),
+                U256::one(),
+                Some(U256::from(nonce)),
+                None,
+            )
+            .unwrap();
+            blockchain.send_transaction(transfer.into()).await.unwrap();
+        }
+
+        let nonces = |block: Block| -> Vec<U256> {
+            block
+                .transactions
+                .iter()
+                .filter_map(|transaction| transaction.nonce)
+                .collect()
+        };
+        let block = blockchain.produce_block().await.unwrap().unwrap();
+        assert_eq!(nonces(block), vec![U256::from(1), U256::from(2)]);
+
+        let block = blockchain.produce_block().await.unwrap().unwrap();
+        assert_eq!(nonces(block), vec![U256::from(3)]);
+        assert!(blockchain.produce_block().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn rolls_back_the_state_of_a_dropped_transaction() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let sender = Account::random();
+        let recipient = Account::random();
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(100);
+        blockchain
+            .accounts
+            .add_account(&sender, &account_data)
+            .unwrap();
+
+        // calling an account that isn't a contract fails after the nonce is
+        // updated and the recipient is created
+        let call = Transaction::new(
+            sender,
+            Some(recipient),
+            U256::zero(),
+            Some(U256::one()),
+            Some(bincode::serialize(&("transfer", vec!["1"])).unwrap().into()),
+        )
+        .unwrap();
+        blockchain.send_transaction(call.into()).await.unwrap();
+
+        let block = blockchain.produce_block().await.unwrap().unwrap();
+        assert!(block.transactions.is_empty());
+        assert_eq!(
+            blockchain.accounts.get_account(&sender).unwrap().nonce,
+            U256::zero()
+        );
+        assert!(blockchain.accounts.get_account(&recipient).is_err());
+    }
+
+    #[tokio::test]
+    async fn drops_a_transaction_invalidated_earlier_in_the_block() {
+        let (blockchain, _, _) = setup().await;
+        let sender = Account::random();
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(100);
+        blockchain
+            .lock()
+            .await
+            .accounts
+            .add_account(&sender, &account_data)
+            .unwrap();
+
+        // each transfer is affordable on its own, but not both
+        let transfer = |nonce: u64| {
+            Transaction::new(
+                sender,
+                Some(Account::random()),
+                U256::from(60),
+                Some(U256::from(nonce)),
+                None,
+            )
+            .unwrap()
+        };
+        let first = blockchain
+            .lock()
+            .await
+            .send_transaction(transfer(1).into())
+            .await
+            .unwrap();
+        let second = blockchain
+            .lock()
+            .await
+            .send_transaction(transfer(2).into())
+            .await
+            .unwrap();
+
+        assert_receipt(blockchain.clone(), first).await;
+        let blockchain = blockchain.lock().await;
+        assert_eq!(
+            blockchain.get_current_block().unwrap().transactions.len(),
+            1
+        );
+
+        // the dropped transaction has a failed receipt outside of any block
+        let transactions = blockchain.transactions.lock().await;
+        let receipt = transactions.get_transaction_receipt(&second).unwrap();
+        assert_eq!(receipt.status, U64::zero());
+        assert_eq!(receipt.block_number, None);
+        assert!(transactions
+            .get_dropped_reason(&second)
+            .unwrap()
+            .starts_with("Insufficient funds"));
+    }
+
+    #[tokio::test]
+    async fn lists_the_pending_transactions_of_an_account() {
+        let (blockchain, _, _) = setup().await;
+        let first = new_transaction(Account::random(), blockchain.clone()).await;
+        let mut second = first.clone();
+        second.nonce = first.nonce.map(|nonce| nonce + 1);
+
+        // sent out of order
+        for transaction in [second, first] {
+            blockchain
+                .lock()
+                .await
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+        }
+
+        let blockchain = blockchain.lock().await;
+        let pending = blockchain.pending_for(&ACCOUNT_1).await;
+        let nonces = pending
+            .iter()
+            .map(|transaction| transaction.nonce.unwrap())
+            .collect::<Vec<_>>();
+        let nonce = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce;
+
+        assert_eq!(nonces, vec![nonce + 1, nonce + 2]);
+        assert!(blockchain.pending_for(&Account::random()).await.is_empty());
+    }
+
+    #[test]
+    fn rewards_the_tip_at_each_percentile_of_gas_used() {
+        let gas = U256::from(21_000);
+        let tips = vec![
+            (U256::from(30), gas),
+            (U256::from(10), gas),
+            (U256::from(20), gas * 2),
+        ];
+        let rewards = rewards(tips, gas * 4, &[0.0, 25.0, 50.0, 75.0, 100.0]);
+        let expected = [10, 10, 20, 20, 30].map(U256::from);
+
+        assert_eq!(rewards, expected);
+        assert_eq!(
+            super::rewards(vec![], U256::zero(), &[25.0, 75.0]),
+            vec![U256::zero(); 2]
+        );
+
+        // gas past a u64 saturates rather than panicking, so the first tip
+        // already covers all of it
+        let huge = vec![(U256::from(10), U256::MAX), (U256::from(20), U256::MAX)];
+        assert_eq!(
+            super::rewards(huge, U256::MAX, &[100.0]),
+            vec![U256::from(10)]
+        );
+    }
+
+    #[tokio::test]
+    async fn gets_the_fee_history_of_recent_blocks() {
+        let (blockchain, _, _) = setup().await;
+
+        for gas_prices in [vec![10], vec![30, 20]] {
+            let mut transaction = new_transaction(Account::random(), blockchain.clone()).await;
+
+            for gas_price in gas_prices {
+                transaction.gas_price = U256::from(gas_price);
+                blockchain
+                    .lock()
+                    .await
+                    .send_transaction(transaction.clone().into())
+                    .await
+                    .unwrap();
+                transaction.nonce = transaction.nonce.map(|nonce| nonce + 1);
+            }
+
+            produce_block(blockchain.clone()).await;
+        }
+
+        let blockchain = blockchain.lock().await;
+        let newest = blockchain.get_current_block().unwrap().number;
+        let fee_history = blockchain
+            .fee_history(2, BlockTag::Latest, Some(vec![25.0, 75.0]))
+            .await
+            .unwrap();
+
+        assert_eq!(fee_history.oldest_block, newest - 1_u64);
+        assert_eq!(fee_history.base_fee_per_gas, vec![BASE_FEE; 3]);
+        assert_eq!(fee_history.gas_used_ratio.len(), 2);
+        assert!(fee_history
+            .gas_used_ratio
+            .iter()
+            .all(|ratio| (0.0..=1.0).contains(ratio)));
+        assert_eq!(
+            fee_history.reward.unwrap(),
+            vec![
+                vec![U256::from(10), U256::from(10)],
+                vec![U256::from(20), U256::from(30)]
+            ]
+        );
+
+        assert!(matches!(
+            blockchain
+                .fee_history(2, BlockTag::Latest, Some(vec![75.0, 25.0]))
+                .await,
+            Err(ChainError::InvalidRewardPercentiles(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cannot_revert_the_genesis_block() {
+        let mut blockchain = new_blockchain();
+        let response = blockchain.revert_head().await;
+
+        assert_eq!(response.unwrap_err(), ChainError::CannotRevertGenesisBlock);
+    }
+
+    #[tokio::test]
+    async fn replays_a_transaction() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+
+        assert_receipt(blockchain.clone(), transaction_hash).await;
+
+        let receipt = blockchain
+            .lock()
+            .await
+            .get_transaction_receipt(transaction_hash)
+            .await
+            .unwrap();
+        let execution_result = blockchain
+            .lock()
+            .await
+            .replay_transaction(transaction_hash)
+            .unwrap();
+
+        assert_eq!(execution_result.gas_used, receipt.gas_used);
+        assert_eq!(execution_result.status, receipt.status);
+
+        // replaying does not change the current state
+        let balance = get_balance(blockchain, &to).await;
+        assert_eq!(balance, U256::from(10));
+    }
 }
@@ -0,0 +1,292 @@
+//! # Consensus
+//!
+//! The rules a block must follow to join the chain.
+//! Blocks the chain produces are sealed by the consensus engine, and blocks
+//! it imports are validated by it, so the same chain can run as an
+//! instant-seal dev node or as one of a set of proof of authority nodes.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::fmt::Debug;
+
+use ethereum_types::H256;
+use types::account::Account;
+use types::block::{Block, GAS_LIMIT_BOUND_DIVISOR, MIN_GAS_LIMIT};
+use types::bytes::Bytes;
+use types::transaction::Transaction;
+use utils::crypto::{private_key_address, recover, sign_hash, Signature};
+use utils::SecretKey;
+
+use crate::error::{ChainError, Result};
+
+pub(crate) trait Consensus: Debug + Send + Sync {
+    /// Check that a block may follow its parent
+    fn validate_block(&self, block: &Block, parent: &Block) -> Result<()>;
+
+    /// Prepare a newly produced block for the chain (e.g. sign it)
+    fn seal_block(&self, block: &mut Block) -> Result<()>;
+}
+
+/// Blocks extend their parent's number, hash and timestamp, move the gas limit
+/// by less than 1/1024th of the parent's, commit to their transactions and
+/// carry the hash of their contents
+fn validate_header(block: &Block, parent: &Block) -> Result<()> {
+    if block.number != parent.number + 1_u64 {
+        return Err(ChainError::InvalidBlock(format!(
+            "block {} can't follow block {}",
+            block.number, parent.number
+        )));
+    }
+
+    let parent_hash = parent.block_hash()?;
+
+    if block.parent_hash != parent_hash {
+        return Err(ChainError::InvalidBlock(format!(
+            "block {} has parent hash {:?}, expected {:?}",
+            block.number, block.parent_hash, parent_hash
+        )));
+    }
+
+    if block.timestamp <= parent.timestamp {
+        return Err(ChainError::InvalidBlock(format!(
+            "block {} has timestamp {}, which isn't after its parent's {}",
+            block.number, block.timestamp, parent.timestamp
+        )));
+    }
+
+    let max_delta = parent.gas_limit / GAS_LIMIT_BOUND_DIVISOR;
+    let delta = block.gas_limit.abs_diff(parent.gas_limit);
+
+    if delta >= max_delta || block.gas_limit < MIN_GAS_LIMIT.into() {
+        return Err(ChainError::InvalidBlock(format!(
+            "block {} has gas limit {}, which is out of bounds of its parent's {}",
+            block.number, block.gas_limit, parent.gas_limit
+        )));
+    }
+
+    if Transaction::root_hash(&block.transactions)? != block.transactions_root {
+        return Err(ChainError::InvalidBlock(format!(
+            "transactions root of block {} does not match",
+            block.number
+        )));
+    }
+
+    if block.hash != Some(block.compute_hash()?) {
+        return Err(ChainError::InvalidBlock(format!(
+            "hash of block {} does not match its contents",
+            block.number
+        )));
+    }
+
+    Ok(())
+}
+
+/// Seal blocks as soon as they're produced, accepting any block with a valid
+/// header (for development)
+#[derive(Debug, Default)]
+pub(crate) struct InstantSeal;
+
+impl Consensus for InstantSeal {
+    fn validate_block(&self, block: &Block, parent: &Block) -> Result<()> {
+        validate_header(block, parent)
+    }
+
+    fn seal_block(&self, _block: &mut Block) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Proof of authority: every block is signed by one of a fixed set of
+/// authorities. The seal is the signature of the block hash (`r || s || v`).
+#[derive(Debug)]
+pub(crate) struct ProofOfAuthority {
+    authorities: Vec<Account>,
+    /// The key this node seals blocks with, if it's an authority
+    key: Option<SecretKey>,
+}
+
+impl ProofOfAuthority {
+    pub(crate) fn new(authorities: Vec<Account>, key: Option<SecretKey>) -> Self {
+        Self { authorities, key }
+    }
+
+    /// The account that sealed a block, recovered from the hash of its
+    /// contents rather than the hash it claims
+    pub(crate) fn sealer(block: &Block) -> Result<Account> {
+        let seal = block
+            .seal
+            .as_ref()
+            .filter(|seal| seal.len() == 65)
+            .ok_or_else(|| {
+                ChainError::InvalidBlock(format!("block {} is unsealed", block.number))
+            })?;
+        let signature = Signature {
+            v: seal[64] as u64,
+            r: H256::from_slice(&seal[..32]),
+            s: H256::from_slice(&seal[32..64]),
+        };
+
+        recover(block.compute_hash()?, &signature)
+            .map_err(|e| ChainError::InvalidBlock(format!("block {}: {}", block.number, e)))
+    }
+}
+
+impl Consensus for ProofOfAuthority {
+    fn validate_block(&self, block: &Block, parent: &Block) -> Result<()> {
+        validate_header(block, parent)?;
+
+        let sealer = Self::sealer(block)?;
+
+        if !self.authorities.contains(&sealer) {
+            return Err(ChainError::InvalidBlock(format!(
+                "block {} was sealed by {:?}, which is not an authority",
+                block.number, sealer
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn seal_block(&self, block: &mut Block) -> Result<()> {
+        let key = self
+            .key
+            .ok_or_else(|| ChainError::InvalidBlock("this node can't seal blocks".into()))?;
+
+        if !self.authorities.contains(&private_key_address(&key)) {
+            return Err(ChainError::InvalidBlock(
+                "this node is not an authority".into(),
+            ));
+        }
+
+        let signature: Vec<u8> = sign_hash(block.block_hash()?, &key)
+            .try_into()
+            .map_err(|e: utils::error::UtilsError| ChainError::InvalidBlock(e.to_string()))?;
+        block.seal = Some(Bytes::from(signature));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::{U256, U64};
+    use types::block::DEFAULT_GAS_LIMIT;
+    use utils::crypto::dev_account;
+
+    fn child_of(parent: &Block) -> Block {
+        Block::new(
+            parent.number + 1_u64,
+            parent.block_hash().unwrap(),
+            vec![],
+            &[],
+            H256::zero(),
+            U256::from(DEFAULT_GAS_LIMIT),
+            parent.timestamp + 1_u64,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn instant_seal_accepts_a_child_block() {
+        let genesis = Block::genesis().unwrap();
+        let mut block = child_of(&genesis);
+
+        InstantSeal.seal_block(&mut block).unwrap();
+        assert!(InstantSeal.validate_block(&block, &genesis).is_ok());
+
+        // not a child of the genesis block
+        let grandchild = child_of(&block);
+        assert!(InstantSeal.validate_block(&grandchild, &genesis).is_err());
+
+        block.number = U64::from(5);
+        assert!(InstantSeal.validate_block(&block, &genesis).is_err());
+    }
+
+    #[test]
+    fn rejects_a_block_out_of_gas_limit_bounds_or_with_a_wrong_transactions_root() {
+        let genesis = Block::genesis().unwrap();
+        let max_delta = genesis.gas_limit / GAS_LIMIT_BOUND_DIVISOR;
+
+        for gas_limit in [genesis.gas_limit + max_delta, genesis.gas_limit - max_delta] {
+            let mut block = child_of(&genesis);
+            block.gas_limit = gas_limit;
+            assert!(matches!(
+                InstantSeal.validate_block(&block, &genesis),
+                Err(ChainError::InvalidBlock(_))
+            ));
+        }
+
+        let mut block = child_of(&genesis);
+        block.transactions_root = H256::random();
+        assert!(matches!(
+            InstantSeal.validate_block(&block, &genesis),
+            Err(ChainError::InvalidBlock(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_a_block_not_after_its_parent() {
+        let genesis = Block::genesis().unwrap();
+        let block = Block::new(
+            U64::one(),
+            genesis.block_hash().unwrap(),
+            vec![],
+            &[],
+            H256::zero(),
+            genesis.gas_limit,
+            genesis.timestamp,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            InstantSeal.validate_block(&block, &genesis),
+            Err(ChainError::InvalidBlock(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_a_seal_moved_onto_a_changed_block() {
+        let genesis = Block::genesis().unwrap();
+        let (authority_key, authority) = dev_account(0);
+        let consensus = ProofOfAuthority::new(vec![authority], Some(authority_key));
+        let mut sealed = child_of(&genesis);
+        consensus.seal_block(&mut sealed).unwrap();
+
+        let mut forged = child_of(&genesis);
+        forged.state_root = H256::random();
+        forged.hash = sealed.hash;
+        forged.seal = sealed.seal.clone();
+
+        assert_ne!(ProofOfAuthority::sealer(&forged).ok(), Some(authority));
+        assert!(matches!(
+            consensus.validate_block(&forged, &genesis),
+            Err(ChainError::InvalidBlock(_))
+        ));
+    }
+
+    #[test]
+    fn proof_of_authority_rejects_an_unauthorized_sealer() {
+        let genesis = Block::genesis().unwrap();
+        let (authority_key, authority) = dev_account(0);
+        let (outsider_key, outsider) = dev_account(1);
+        let consensus = ProofOfAuthority::new(vec![authority], Some(authority_key));
+
+        let mut block = child_of(&genesis);
+        consensus.seal_block(&mut block).unwrap();
+        assert_eq!(ProofOfAuthority::sealer(&block).unwrap(), authority);
+        assert!(consensus.validate_block(&block, &genesis).is_ok());
+
+        let outsider_consensus = ProofOfAuthority::new(vec![outsider], Some(outsider_key));
+        let mut block = child_of(&genesis);
+        outsider_consensus.seal_block(&mut block).unwrap();
+        assert!(matches!(
+            consensus.validate_block(&block, &genesis),
+            Err(ChainError::InvalidBlock(_))
+        ));
+
+        // unsealed
+        let block = child_of(&genesis);
+        assert!(consensus.validate_block(&block, &genesis).is_err());
+    }
+}
@@ -7,6 +7,7 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 use jsonrpsee::core::Error as JsonRpseeError;
+use jsonrpsee::types::error::{CallError, ErrorObject};
 use serde::{Deserialize, Serialize};
 use std::{net::AddrParseError, sync::PoisonError};
 use thiserror::Error;
@@ -14,7 +15,11 @@ use tracing_subscriber::{
     filter::{FromEnvError, ParseError as TracingParseError},
     util::TryInitError as TracingTryInitError,
 };
-use types::error::TypeError;
+use types::error::{
+    TypeError, EXECUTION_REVERTED_CODE, GAS_PRICE_TOO_LOW_CODE, INSUFFICIENT_FUNDS_CODE,
+    INVALID_TRANSACTION_CODE, NONCE_TOO_HIGH_CODE, NONCE_TOO_LOW_CODE,
+    REPLACEMENT_UNDERPRICED_CODE, SERVER_ERROR_CODE, TRANSACTION_TOO_LARGE_CODE,
+};
 
 #[derive(Error, Debug, Serialize, Deserialize, PartialEq)]
 pub enum ChainError {
@@ -27,24 +32,60 @@ pub enum ChainError {
     #[error("Block {0} not found")]
     BlockNotFound(String),
 
+    #[error("Block {0} has been pruned")]
+    BlockPruned(String),
+
     #[error("Could not create root hash for : {0}")]
     CannotCreateRootHash(String),
 
+    #[error("Cannot revert the genesis block")]
+    CannotRevertGenesisBlock,
+
     #[error("Error encoding/decoding: {0}")]
     EncodingDecodingError(String),
 
     #[error("Could not deserialize: {0}")]
     DeserializeError(String),
 
+    #[error("Transaction {0} has already been sent")]
+    DuplicateTransaction(String),
+
+    #[error("Execution reverted: {0}")]
+    ExecutionReverted(String),
+
+    #[error("Insufficient funds for account {0}: balance {1}, required {2}")]
+    InsufficientFunds(String, String, String),
+
     #[error("Interal Error: {0}")]
     InternalError(String),
 
+    #[error("Invalid block: {0}")]
+    InvalidBlock(String),
+
     #[error("Invalid block number {0}")]
     InvalidBlockNumber(String),
 
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Invalid reward percentiles {0}")]
+    InvalidRewardPercentiles(String),
+
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("I/O error: {0}")]
+    IoError(String),
+
     #[error("JsonRpsee Error: {0}")]
     JsonRpseeError(String),
 
+    #[error("Mempool is full with {0} transactions, all paying at least as much")]
+    MempoolFull(String),
+
     #[error("Parent hash is missing: {0}")]
     MissingHash(String),
 
@@ -60,12 +101,18 @@ pub enum ChainError {
     #[error("Account {0} is not a contract account")]
     NotAContractAccount(String),
 
+    #[error("Replacement transaction underpriced: fee cap {0} is below the required {1}")]
+    ReplacementUnderpriced(String, String),
+
     #[error("Error executing contract at address {0}: {1}")]
     RuntimeError(String, String),
 
     #[error("Could not serialize: {0}")]
     SerializeError(String),
 
+    #[error("State {0} not found")]
+    StateNotFound(String),
+
     #[error("Could not open the database: {0}")]
     StorageCannotOpenDb(String),
 
@@ -78,6 +125,9 @@ pub enum ChainError {
     #[error("Could put {0} in storage")]
     StoragePutError(String),
 
+    #[error("Could not remove {0} from storage")]
+    StorageRemoveError(String),
+
     #[error("Error parsing EnvFilter from an environment variable {0}")]
     TracingFromEnvError(String),
 
@@ -93,13 +143,41 @@ pub enum ChainError {
     #[error("Transaction {0} cannot be verified")]
     TransactionNotVerified(String),
 
+    #[error("Transaction {0} is pending")]
+    TransactionPending(String),
+
+    #[error("Transaction {0} is {1} bytes, exceeding the maximum of {2} bytes")]
+    TransactionTooLarge(String, String, String),
+
     #[error("Type Error {0}")]
     TypeError(String),
+
+    #[error("Transaction underpriced: gas price {0} is below the minimum of {1}")]
+    Underpriced(String, String),
 }
 
 /// Utility result type to be used throughout
 pub type Result<T> = std::result::Result<T, ChainError>;
 
+impl ChainError {
+    /// The JSON-RPC error code sent to clients
+    pub(crate) fn code(&self) -> i32 {
+        match self {
+            ChainError::ExecutionReverted(..) => EXECUTION_REVERTED_CODE,
+            ChainError::InsufficientFunds(..) => INSUFFICIENT_FUNDS_CODE,
+            ChainError::InvalidSignature(..)
+            | ChainError::InvalidTransaction(..)
+            | ChainError::TransactionNotVerified(..) => INVALID_TRANSACTION_CODE,
+            ChainError::NonceTooHigh(..) => NONCE_TOO_HIGH_CODE,
+            ChainError::NonceTooLow(..) => NONCE_TOO_LOW_CODE,
+            ChainError::ReplacementUnderpriced(..) => REPLACEMENT_UNDERPRICED_CODE,
+            ChainError::TransactionTooLarge(..) => TRANSACTION_TOO_LARGE_CODE,
+            ChainError::Underpriced(..) => GAS_PRICE_TOO_LOW_CODE,
+            _ => SERVER_ERROR_CODE,
+        }
+    }
+}
+
 impl From<AddrParseError> for ChainError {
     fn from(error: AddrParseError) -> Self {
         ChainError::AddrParseError(error.to_string())
@@ -124,15 +202,23 @@ impl From<TracingTryInitError> for ChainError {
     }
 }
 
+impl From<std::io::Error> for ChainError {
+    fn from(error: std::io::Error) -> Self {
+        ChainError::IoError(error.to_string())
+    }
+}
+
 impl From<JsonRpseeError> for ChainError {
     fn from(error: JsonRpseeError) -> Self {
         ChainError::JsonRpseeError(error.to_string())
     }
 }
 
+/// Surface chain errors as JSON-RPC error objects with a code clients can match on
 impl From<ChainError> for JsonRpseeError {
     fn from(error: ChainError) -> Self {
-        JsonRpseeError::Custom(error.to_string())
+        let error_object = ErrorObject::owned(error.code(), error.to_string(), None::<()>);
+        JsonRpseeError::Call(CallError::Custom(error_object))
     }
 }
 
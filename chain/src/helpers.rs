@@ -40,6 +40,7 @@ pub mod tests {
     use tokio::sync::Mutex;
     use types::account::{Account, AccountData};
     use types::transaction::Transaction;
+    use utils::crypto::dev_account;
 
     use crate::{blockchain::BlockChain, server::serve, storage::Storage};
 
@@ -51,8 +52,8 @@ pub mod tests {
             Arc::new(Storage::new(DATABASE_NAME).unwrap());
         pub(crate) static ref ACCOUNT_1: Account =
             H160::from_str("0x4a0d457e884ebd9b9773d172ed687417caac4f14").unwrap();
-        pub(crate) static ref ACCOUNT_2: Account = Account::random();
-        pub(crate) static ref ACCOUNT_3: Account = Account::random();
+        pub(crate) static ref ACCOUNT_2: Account = dev_account(1).1;
+        pub(crate) static ref ACCOUNT_3: Account = dev_account(2).1;
     }
 
     pub(crate) async fn server(blockchain: Option<Arc<Mutex<BlockChain>>>) -> ServerHandle {
@@ -89,7 +90,7 @@ pub mod tests {
         // )
         // .unwrap();
 
-        // blockchain.new_block(vec![transaction], H256::zero());
+        // blockchain.new_block(vec![transaction], &[], H256::zero());
 
         (Arc::new(Mutex::new(blockchain)), *ACCOUNT_1, *ACCOUNT_2)
     }
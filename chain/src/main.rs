@@ -7,10 +7,12 @@
 
 mod account;
 mod blockchain;
+mod consensus;
 mod error;
 mod helpers;
 mod keys;
 mod logger;
+mod mempool;
 mod method;
 mod server;
 mod storage;
@@ -20,10 +22,25 @@ mod world_state;
 // use std::sync::Arc;
 
 // use blockchain::BlockChain;
-use error::Result;
+use error::{ChainError, Result};
 use server::serve;
+use std::{path::Path, time::Duration};
 // use storage::Storage;
-// use tokio::sync::Mutex;
+use tokio::time;
+
+/// Where the chain and its transactions are kept between restarts
+const TRANSACTIONS_FILE: &str = "transactions.json";
+
+/// How often the chain and its transactions are written to disk
+const PERSIST_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The environment variable holding how many blocks behind the head to keep,
+/// pruning older ones. All blocks are kept when it's unset.
+const RETENTION_VAR: &str = "CHAIN_RETENTION";
+
+/// The environment variable holding the chain id that transactions are signed
+/// for, 1337 when it's unset.
+const CHAIN_ID_VAR: &str = "CHAIN_ID";
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -33,8 +50,48 @@ async fn main() -> Result<()> {
     // let _server = serve("127.0.0.1:8545", Arc::new(Mutex::new(blockchain))).await?;
 
     let (blockchain, _, _) = crate::helpers::tests::setup().await;
+
+    if let Ok(retention) = std::env::var(RETENTION_VAR) {
+        let retention = retention.parse().map_err(|_| {
+            ChainError::InvalidConfig(format!(
+                "{} should be a number of blocks, not {:?}",
+                RETENTION_VAR, retention
+            ))
+        })?;
+        blockchain.lock().await.set_retention(retention);
+    }
+
+    if let Ok(chain_id) = std::env::var(CHAIN_ID_VAR) {
+        blockchain.lock().await.chain_id = chain_id.parse().map_err(|_| {
+            ChainError::InvalidConfig(format!(
+                "{} should be a number, not {:?}",
+                CHAIN_ID_VAR, chain_id
+            ))
+        })?;
+    }
+
+    // pick up the chain and its transactions from before a restart
+    if Path::new(TRANSACTIONS_FILE).exists() {
+        blockchain.lock().await.restore(TRANSACTIONS_FILE).await?;
+    }
+
+    let persisted = blockchain.clone();
     let _server = serve("127.0.0.1:8545", blockchain).await?;
 
+    tokio::spawn(async move {
+        let mut interval = time::interval(PERSIST_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let written = persisted.lock().await.persist(TRANSACTIONS_FILE).await;
+
+            if let Err(error) = written {
+                tracing::error!("Could not persist transactions: {}", error);
+            }
+        }
+    });
+
     // create a future that never resolves
     futures::future::pending().await
 }
@@ -0,0 +1,404 @@
+//! # Mempool
+//!
+//! Transactions waiting to be mined.
+//! Transactions are kept in arrival order and indexed by hash, by sender
+//! and nonce, and by price, so a duplicate, a replacement or the
+//! transaction to evict is found without scanning the pool. Blocks are built from
+//! the best paying transactions first, but a sender's transactions are always
+//! taken in nonce order, and never past a gap in their nonces.
+//!
+//! Block order doesn't depend on arrival: transactions paying the same are
+//! ordered by sender and then by hash, so producing a block from the same
+//! pool always orders it the same way.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, VecDeque};
+
+use ethereum_types::{H256, U256};
+use types::account::Account;
+use types::transaction::Transaction;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Mempool {
+    /// Transactions keyed by arrival, where transactions returned to the
+    /// front of the pool arrive before everything else
+    transactions: BTreeMap<i64, Transaction>,
+    /// The arrival of each pending transaction with a nonce, by sender and nonce
+    by_sender: HashMap<Account, BTreeMap<U256, i64>>,
+    /// The arrival of each pending transaction with a hash
+    by_hash: HashMap<H256, i64>,
+    /// The transactions that can be evicted, cheapest first: each sender's
+    /// highest nonce, and those without a nonce
+    evictable: BTreeSet<(U256, Reverse<i64>)>,
+    first: i64,
+    last: i64,
+}
+
+impl Mempool {
+    pub(crate) fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// The pending transactions in arrival order
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Transaction> {
+        self.transactions.values()
+    }
+
+    /// The pending transaction from an account with a nonce
+    pub(crate) fn get(&self, from: &Account, nonce: U256) -> Option<&Transaction> {
+        self.by_sender
+            .get(from)
+            .and_then(|nonces| nonces.get(&nonce))
+            .and_then(|arrival| self.transactions.get(arrival))
+    }
+
+    /// Whether a transaction is pending
+    pub(crate) fn contains(&self, hash: &H256) -> bool {
+        self.by_hash.contains_key(hash)
+    }
+
+    /// The cheapest transaction that can be evicted, the latest arrival among
+    /// those paying the same.
+    ///
+    /// Only a sender's highest nonce can be evicted, as evicting any other
+    /// would leave the ones after it waiting on a gap.
+    pub(crate) fn cheapest(&self) -> Option<&Transaction> {
+        self.evictable
+            .first()
+            .map(|(_, Reverse(arrival))| &self.transactions[arrival])
+    }
+
+    /// Remove and return the cheapest transaction that can be evicted
+    pub(crate) fn evict_cheapest(&mut self) -> Option<Transaction> {
+        let (_, Reverse(arrival)) = self.evictable.first().copied()?;
+
+        Some(self.remove(arrival))
+    }
+
+    pub(crate) fn push_back(&mut self, transaction: Transaction) {
+        self.last += 1;
+        self.insert(self.last, transaction);
+    }
+
+    pub(crate) fn push_front(&mut self, transaction: Transaction) {
+        self.first -= 1;
+        self.insert(self.first, transaction);
+    }
+
+    /// Add a transaction, replacing any pending one with the same sender and
+    /// nonce in its place in the pool
+    fn insert(&mut self, arrival: i64, transaction: Transaction) {
+        let from = transaction.from;
+        let arrival = match transaction.nonce {
+            Some(nonce) => {
+                self.untrack_last(&from);
+                *self
+                    .by_sender
+                    .entry(from)
+                    .or_default()
+                    .entry(nonce)
+                    .or_insert(arrival)
+            }
+            None => arrival,
+        };
+
+        let hash = transaction.hash;
+        let evictable = (transaction.fee_cap(), Reverse(arrival));
+        let has_nonce = transaction.nonce.is_some();
+        let replaced = self.transactions.insert(arrival, transaction);
+
+        if let Some(replaced) = replaced.and_then(|replaced| replaced.hash) {
+            self.by_hash.remove(&replaced);
+        }
+
+        if let Some(hash) = hash {
+            self.by_hash.insert(hash, arrival);
+        }
+
+        if has_nonce {
+            self.track_last(&from);
+        } else {
+            self.evictable.insert(evictable);
+        }
+    }
+
+    /// The eviction key of a sender's highest nonce
+    fn last_of(&self, from: &Account) -> Option<(U256, Reverse<i64>)> {
+        let (_, arrival) = self.by_sender.get(from)?.last_key_value()?;
+
+        Some((self.transactions[arrival].fee_cap(), Reverse(*arrival)))
+    }
+
+    fn untrack_last(&mut self, from: &Account) {
+        if let Some(last) = self.last_of(from) {
+            self.evictable.remove(&last);
+        }
+    }
+
+    fn track_last(&mut self, from: &Account) {
+        if let Some(last) = self.last_of(from) {
+            self.evictable.insert(last);
+        }
+    }
+
+    /// Remove and return up to `n` transactions, highest fee cap first, then
+    /// by lowest sender and lowest hash.
+    ///
+    /// Each sender's transactions are taken in nonce order, starting from their
+    /// lowest pending nonce, so a sender's cheap transaction holds back their
+    /// later ones. Transactions after a gap in a sender's nonces stay pending
+    /// until the gap is filled.
+    pub(crate) fn take_best(&mut self, n: usize) -> Vec<Transaction> {
+        let mut senders: HashMap<Account, BTreeMap<U256, i64>> = HashMap::new();
+        let mut queues: Vec<VecDeque<i64>> = vec![];
+
+        for (arrival, transaction) in self.transactions.iter() {
+            match transaction.nonce {
+                Some(nonce) => {
+                    senders
+                        .entry(transaction.from)
+                        .or_default()
+                        .insert(nonce, *arrival);
+                }
+                // transactions without a nonce don't wait on any other
+                None => queues.push(VecDeque::from([*arrival])),
+            }
+        }
+
+        for nonces in senders.into_values() {
+            let mut expected = None;
+            let executable = nonces
+                .into_iter()
+                .take_while(|(nonce, _)| {
+                    let next = expected.is_none_or(|expected| *nonce == expected);
+                    expected = Some(*nonce + 1);
+                    next
+                })
+                .map(|(_, arrival)| arrival)
+                .collect();
+
+            queues.push(executable);
+        }
+
+        // the best next transaction of each queue
+        let mut candidates = BinaryHeap::new();
+
+        for (queue, arrivals) in queues.iter().enumerate() {
+            if let Some(arrival) = arrivals.front() {
+                candidates.push((self.priority(*arrival), *arrival, queue));
+            }
+        }
+
+        let mut best = vec![];
+
+        while best.len() < n {
+            let Some((_, arrival, queue)) = candidates.pop() else {
+                break;
+            };
+
+            queues[queue].pop_front();
+
+            if let Some(next) = queues[queue].front() {
+                candidates.push((self.priority(*next), *next, queue));
+            }
+
+            best.push(self.remove(arrival));
+        }
+
+        best
+    }
+
+    /// How soon a transaction goes into a block, from its contents alone
+    fn priority(&self, arrival: i64) -> (U256, Reverse<Account>, Reverse<Option<H256>>) {
+        let transaction = &self.transactions[&arrival];
+
+        (
+            transaction.fee_cap(),
+            Reverse(transaction.from),
+            Reverse(transaction.hash),
+        )
+    }
+
+    fn remove(&mut self, arrival: i64) -> Transaction {
+        let transaction = &self.transactions[&arrival];
+        let (from, nonce, fee_cap) = (transaction.from, transaction.nonce, transaction.fee_cap());
+
+        match nonce {
+            Some(_) => self.untrack_last(&from),
+            None => {
+                self.evictable.remove(&(fee_cap, Reverse(arrival)));
+            }
+        }
+
+        let transaction = self
+            .transactions
+            .remove(&arrival)
+            .expect("transaction arrivals are taken from the pool");
+
+        if let Some(nonce) = nonce {
+            if let Some(nonces) = self.by_sender.get_mut(&from) {
+                nonces.remove(&nonce);
+
+                if nonces.is_empty() {
+                    self.by_sender.remove(&from);
+                }
+            }
+
+            self.track_last(&from);
+        }
+
+        if let Some(hash) = transaction.hash {
+            self.by_hash.remove(&hash);
+        }
+
+        transaction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(from: Account, nonce: u64, gas_price: u64) -> Transaction {
+        let mut transaction = Transaction::new(
+            from,
+            Some(Account::random()),
+            U256::zero(),
+            Some(U256::from(nonce)),
+            None,
+        )
+        .unwrap();
+        transaction.gas_price = U256::from(gas_price);
+        transaction
+    }
+
+    fn nonces(transactions: &[Transaction]) -> Vec<(Account, U256)> {
+        transactions
+            .iter()
+            .map(|transaction| (transaction.from, transaction.nonce.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn takes_the_best_paying_transactions_first() {
+        let (alice, bob) = (Account::random(), Account::random());
+        let mut mempool = Mempool::default();
+        mempool.push_back(transaction(alice, 1, 10));
+        mempool.push_back(transaction(bob, 1, 30));
+        mempool.push_back(transaction(alice, 2, 50));
+        mempool.push_back(transaction(bob, 2, 20));
+
+        // alice's second transaction pays the most, but waits on her first
+        let best = mempool.take_best(3);
+        assert_eq!(
+            nonces(&best),
+            vec![
+                (bob, U256::from(1)),
+                (bob, U256::from(2)),
+                (alice, U256::from(1)),
+            ]
+        );
+        assert_eq!(nonces(&mempool.take_best(3)), vec![(alice, U256::from(2))]);
+        assert_eq!(mempool.len(), 0);
+    }
+
+    #[test]
+    fn leaves_transactions_after_a_nonce_gap() {
+        let account = Account::random();
+        let mut mempool = Mempool::default();
+        mempool.push_back(transaction(account, 4, 100));
+        mempool.push_back(transaction(account, 1, 10));
+        mempool.push_back(transaction(account, 2, 10));
+
+        assert_eq!(
+            nonces(&mempool.take_best(10)),
+            vec![(account, U256::from(1)), (account, U256::from(2))]
+        );
+        assert_eq!(mempool.len(), 1);
+
+        // once the gap is filled, the rest can be taken
+        mempool.push_back(transaction(account, 3, 10));
+        assert_eq!(
+            nonces(&mempool.take_best(10)),
+            vec![(account, U256::from(3)), (account, U256::from(4))]
+        );
+    }
+
+    #[test]
+    fn orders_the_same_transactions_the_same_way_whatever_their_arrival() {
+        let senders = [Account::random(), Account::random(), Account::random()];
+        let transactions = senders
+            .iter()
+            .flat_map(|sender| {
+                [
+                    transaction(*sender, 1, 10),
+                    transaction(*sender, 2, 10),
+                    transaction(*sender, 3, 20),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let take_all = |arrivals: &[usize]| {
+            let mut mempool = Mempool::default();
+            arrivals
+                .iter()
+                .for_each(|arrival| mempool.push_back(transactions[*arrival].clone()));
+            nonces(&mempool.take_best(transactions.len()))
+        };
+
+        let best = take_all(&[0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(take_all(&[8, 3, 5, 0, 7, 2, 4, 6, 1]), best);
+        assert_eq!(take_all(&[2, 1, 0, 5, 4, 3, 8, 7, 6]), best);
+
+        // with equal fees, the lowest sender goes first
+        let mut lowest = senders;
+        lowest.sort();
+        assert_eq!(best[0], (lowest[0], U256::one()));
+    }
+
+    #[test]
+    fn evicts_only_the_last_transaction_of_a_sender() {
+        let (alice, bob) = (Account::random(), Account::random());
+        let mut mempool = Mempool::default();
+        mempool.push_back(transaction(alice, 1, 5));
+        mempool.push_back(transaction(alice, 2, 50));
+        mempool.push_back(transaction(bob, 1, 10));
+
+        // alice's first transaction is the cheapest, but her second waits on it
+        assert_eq!(
+            nonces(&[mempool.evict_cheapest().unwrap()]),
+            vec![(bob, U256::one())]
+        );
+        assert_eq!(
+            nonces(&[mempool.evict_cheapest().unwrap()]),
+            vec![(alice, U256::from(2))]
+        );
+
+        // once a replacement is the last, it is evicted at its new price
+        mempool.push_back(transaction(alice, 2, 1));
+        mempool.push_back(transaction(alice, 1, 3));
+        assert_eq!(mempool.cheapest().unwrap().gas_price, U256::from(1));
+        assert_eq!(
+            nonces(&[mempool.evict_cheapest().unwrap()]),
+            vec![(alice, U256::from(2))]
+        );
+        assert_eq!(mempool.cheapest().unwrap().gas_price, U256::from(3));
+    }
+
+    #[test]
+    fn replaces_a_transaction_in_place() {
+        let account = Account::random();
+        let mut mempool = Mempool::default();
+        mempool.push_back(transaction(account, 1, 10));
+        mempool.push_back(transaction(Account::random(), 1, 10));
+        mempool.push_back(transaction(account, 1, 20));
+
+        assert_eq!(mempool.len(), 2);
+        assert_eq!(mempool.iter().next().unwrap().gas_price, U256::from(20));
+        assert_eq!(
+            mempool.get(&account, U256::one()).unwrap().gas_price,
+            U256::from(20)
+        );
+    }
+}
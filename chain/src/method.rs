@@ -4,37 +4,50 @@
 
 ////////////////////////////////////////////////////////////////////////////////
 
-use ethereum_types::H256;
-use jsonrpsee::core::Error;
+use ethereum_types::{H256, U64};
 use jsonrpsee::core::Error as JsonRpseeError;
 use jsonrpsee::RpcModule;
 use types::{
-    account::Account, block::BlockNumber, bytes::Bytes, helpers::to_hex,
+    account::Account,
+    block::{BlockHeader, BlockId, BlockNumber, BlockTag},
+    helpers::{strip_0x, to_hex},
     transaction::TransactionRequest,
 };
 
-use crate::{error::Result, server::Context};
+use crate::{
+    error::{ChainError, Result},
+    server::Context,
+};
 
 pub(crate) fn eth_block_number(module: &mut RpcModule<Context>) -> Result<()> {
     module.register_async_method("eth_blockNumber", |_, blockchain| async move {
-        let block_number = blockchain
-            .lock()
-            .await
-            .get_current_block()
-            .map_err(|e| JsonRpseeError::Custom(e.to_string()))?
-            .number;
+        let block_number = blockchain.lock().await.get_current_block()?.number;
         Ok(block_number)
     })?;
 
     Ok(())
 }
 
+pub(crate) fn eth_chain_id(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_chainId", |_, blockchain| async move {
+        let chain_id = blockchain.lock().await.chain_id;
+        Ok(U64::from(chain_id))
+    })?;
+
+    Ok(())
+}
+
 pub(crate) fn eth_get_block_by_number(module: &mut RpcModule<Context>) -> Result<()> {
     module.register_async_method("eth_getBlockByNumber", |params, blockchain| async move {
-        let block_number = params.one::<BlockNumber>()?;
+        let mut seq = params.sequence();
+        let block_number = seq.next::<BlockNumber>()?;
+        let full_transactions = seq.optional_next::<bool>()?.unwrap_or(true);
         let block = blockchain.lock().await.get_block_by_number(*block_number)?;
 
-        Ok(block)
+        match full_transactions {
+            true => Ok(serde_json::to_value(block)?),
+            false => Ok(serde_json::to_value(BlockHeader::from(&block))?),
+        }
     })?;
 
     Ok(())
@@ -43,19 +56,11 @@ pub(crate) fn eth_get_block_by_number(module: &mut RpcModule<Context>) -> Result
 pub(crate) fn eth_get_balance(module: &mut RpcModule<Context>) -> Result<()> {
     module.register_async_method("eth_getBalance", move |params, blockchain| async move {
         let key = params.one::<Account>()?;
-        let block = blockchain
-            .lock()
-            .await
-            .get_current_block()
-            .map_err(|e| JsonRpseeError::Custom(e.to_string()))?
-            .number;
-
         let balance = blockchain
             .lock()
             .await
-            .accounts
-            .get_account_balance_by_block(&key, &BlockNumber(block))
-            .map_err(|e| Error::Custom(e.to_string()))?;
+            .get_balance_by_block(&key, BlockTag::Latest)
+            .await?;
 
         Ok(to_hex(balance))
     })?;
@@ -65,14 +70,23 @@ pub(crate) fn eth_get_balance(module: &mut RpcModule<Context>) -> Result<()> {
 
 pub(crate) fn eth_get_transaction_count(module: &mut RpcModule<Context>) -> Result<()> {
     module.register_async_method("eth_getTransactionCount", |params, blockchain| async move {
-        let account = params.one::<Account>()?;
-        let count = blockchain
-            .lock()
-            .await
-            .accounts
-            .get_account(&account)
-            .map_err(|e| Error::Custom(e.to_string()))?
-            .nonce;
+        // the block is optional, defaulting to the latest
+        let mut seq = params.sequence();
+        let account = seq.next::<Account>()?;
+        let block = seq.optional_next::<BlockId>()?.unwrap_or_default();
+        let blockchain = blockchain.lock().await;
+        let mut count = blockchain.get_account_by_block(&account, block)?.nonce;
+
+        // pending transactions count until the first gap in their nonces
+        if block == BlockId::Number(BlockTag::Pending) {
+            for transaction in blockchain.pending_for(&account).await {
+                if transaction.nonce != Some(count + 1) {
+                    break;
+                }
+
+                count += 1;
+            }
+        }
 
         Ok(to_hex(count))
     })?;
@@ -86,19 +100,12 @@ pub(crate) fn eth_get_balance_by_block(module: &mut RpcModule<Context>) -> Resul
         move |params, blockchain| async move {
             let mut seq = params.sequence();
             let account = seq.next::<Account>()?;
-            let block = seq.next::<String>()?.clone();
-            let block_number = blockchain
-                .lock()
-                .await
-                .parse_block_number(&block)
-                .map_err(|e| JsonRpseeError::Custom(e.to_string()))?;
-
+            let block = seq.next::<BlockId>()?;
             let balance = blockchain
                 .lock()
                 .await
-                .accounts
-                .get_account_balance_by_block(&account, &block_number)
-                .map_err(|e| Error::Custom(e.to_string()))?;
+                .get_balance_by_block(&account, block)
+                .await?;
 
             Ok(to_hex(balance))
         },
@@ -129,13 +136,14 @@ pub(crate) fn eth_send_raw_transaction(module: &mut RpcModule<Context>) -> Resul
     module.register_async_method(
         "eth_sendRawTransaction",
         move |params, blockchain| async move {
-            let raw_transaction = params.one::<Bytes>()?;
+            let raw_transaction = params.one::<String>()?;
+            let envelope = hex::decode(strip_0x(&raw_transaction))
+                .map_err(|e| ChainError::InvalidTransaction(e.to_string()))?;
             let transaction_hash = blockchain
                 .lock()
                 .await
-                .send_raw_transaction(raw_transaction)
-                .await
-                .map_err(|e| Error::Custom(e.to_string()))?;
+                .send_raw_transaction(envelope.into())
+                .await?;
 
             Ok(transaction_hash)
         },
@@ -149,12 +157,18 @@ pub(crate) fn eth_get_transaction_receipt(module: &mut RpcModule<Context>) -> Re
         "eth_getTransactionReceipt",
         move |params, blockchain| async move {
             let transaction_hash = params.one::<H256>()?;
-            let transaction_receipt = blockchain
+            let transaction_receipt = match blockchain
                 .lock()
                 .await
                 .get_transaction_receipt(transaction_hash)
                 .await
-                .map_err(|e| Error::Custom(e.to_string()))?;
+            {
+                Ok(transaction_receipt) => Some(transaction_receipt),
+                // clients poll until there is a receipt, so a pending or
+                // unknown transaction is null rather than an error
+                Err(ChainError::TransactionPending(_) | ChainError::TransactionNotFound(_)) => None,
+                Err(error) => return Err(JsonRpseeError::from(error)),
+            };
 
             Ok(transaction_receipt)
         },
@@ -163,29 +177,95 @@ pub(crate) fn eth_get_transaction_receipt(module: &mut RpcModule<Context>) -> Re
     Ok(())
 }
 
-pub(crate) fn eth_get_code(module: &mut RpcModule<Context>) -> Result<()> {
-    module.register_async_method("eth_getCode", move |params, blockchain| async move {
+pub(crate) fn eth_get_block_receipts(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "eth_getBlockReceipts",
+        move |params, blockchain| async move {
+            let block = params.one::<BlockTag>()?;
+            let receipts = blockchain.lock().await.get_block_receipts(block).await?;
+
+            Ok(receipts)
+        },
+    )?;
+
+    Ok(())
+}
+
+pub(crate) fn eth_simulate_bundle(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_simulateBundle", move |params, blockchain| async move {
         let mut seq = params.sequence();
-        let address = seq.next::<Account>()?;
+        let transaction_requests = seq.next::<Vec<TransactionRequest>>()?;
+        let block = seq.next::<BlockTag>()?;
+        let execution_results = blockchain
+            .lock()
+            .await
+            .simulate_bundle(transaction_requests, block)
+            .await?;
 
-        // TODO(ddimaria): lookup code by block number
-        // let _block = seq.next::<BlockNumber>()?;
-        let block = seq.next::<String>()?.clone();
-        let block_number = blockchain
+        Ok(execution_results)
+    })?;
+
+    Ok(())
+}
+
+pub(crate) fn eth_call(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_call", move |params, blockchain| async move {
+        let mut seq = params.sequence();
+        let transaction_request = seq.next::<TransactionRequest>()?;
+        let block = seq.optional_next::<BlockId>()?.unwrap_or_default();
+        let output = blockchain
             .lock()
             .await
-            .parse_block_number(&block)
-            .map_err(|e| JsonRpseeError::Custom(e.to_string()))?;
+            .call(transaction_request, block)
+            .await?;
+
+        Ok(format!("0x{}", hex::encode(output)))
+    })?;
+
+    Ok(())
+}
+
+pub(crate) fn eth_estimate_gas(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_estimateGas", move |params, blockchain| async move {
+        let mut seq = params.sequence();
+        let transaction_request = seq.next::<TransactionRequest>()?;
+        let block = seq.optional_next::<BlockTag>()?.unwrap_or(BlockTag::Latest);
+        let gas = blockchain
+            .lock()
+            .await
+            .estimate_gas(transaction_request, block)
+            .await?;
+
+        Ok(gas)
+    })?;
+
+    Ok(())
+}
+
+pub(crate) fn eth_gas_price(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_gasPrice", move |_, blockchain| async move {
+        let transactions = blockchain.lock().await.transactions.clone();
+        let gas_price = transactions.lock().await.config.min_gas_price;
+
+        Ok(gas_price)
+    })?;
+
+    Ok(())
+}
+
+pub(crate) fn eth_get_code(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_getCode", move |params, blockchain| async move {
+        let mut seq = params.sequence();
+        let address = seq.next::<Account>()?;
+        let block = seq.next::<BlockId>()?;
 
         let code_hash = blockchain
             .lock()
             .await
-            .accounts
-            .get_account(&address)
-            .map_err(|e| Error::Custom(e.to_string()))?
+            .get_account_by_block(&address, block)?
             .code_hash
             .ok_or_else(|| {
-                JsonRpseeError::Custom(format!("missing code hash for block {:?}", block_number))
+                JsonRpseeError::Custom(format!("missing code hash for block {:?}", block))
             })?;
 
         Ok(code_hash)
@@ -194,10 +274,61 @@ pub(crate) fn eth_get_code(module: &mut RpcModule<Context>) -> Result<()> {
     Ok(())
 }
 
+pub(crate) fn eth_fee_history(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_feeHistory", move |params, blockchain| async move {
+        let mut seq = params.sequence();
+        let block_count = seq.next::<U64>()?;
+        let newest_block = seq.next::<BlockTag>()?;
+        let reward_percentiles = seq.optional_next::<Vec<f64>>()?;
+        let fee_history = blockchain
+            .lock()
+            .await
+            .fee_history(block_count.as_u64(), newest_block, reward_percentiles)
+            .await?;
+
+        Ok(fee_history)
+    })?;
+
+    Ok(())
+}
+
+pub(crate) fn evm_set_next_block_timestamp(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "evm_setNextBlockTimestamp",
+        move |params, blockchain| async move {
+            let timestamp = params.one::<U64>()?;
+            blockchain
+                .lock()
+                .await
+                .set_next_block_timestamp(timestamp)?;
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+pub(crate) fn chain_stats(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("chain_stats", |_, blockchain| async move {
+        Ok(blockchain.lock().await.stats().await)
+    })?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::blockchain::tests::{new_transaction, produce_block};
     use crate::helpers::tests::setup;
+    use ethereum_types::U256;
+    use jsonrpsee::rpc_params;
+    use jsonrpsee::types::error::CallError;
+    use types::account::AccountData;
+    use types::block::FeeHistory;
+    use types::transaction::TransactionReceipt;
+    use web3::error::Web3Error;
 
     #[tokio::test]
     async fn gets_an_account_balance() {
@@ -215,4 +346,408 @@ pub mod tests {
 
         assert_eq!(response, to_hex(balance));
     }
+
+    #[tokio::test]
+    async fn surfaces_a_nonce_too_low_as_a_client_error() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let mut module = RpcModule::new(blockchain);
+        eth_send_transaction(&mut module).unwrap();
+
+        // the account's nonce is 0, so 0 has already been used
+        let transaction_request = TransactionRequest {
+            from: Some(id_1),
+            to: Some(id_2),
+            value: Some(U256::from(1)),
+            nonce: Some(U256::zero()),
+            ..Default::default()
+        };
+        let response = module
+            .call::<_, H256>("eth_sendTransaction", [transaction_request])
+            .await;
+
+        match response {
+            Err(JsonRpseeError::Call(CallError::Custom(error))) => {
+                assert!(matches!(Web3Error::from(error), Web3Error::NonceTooLow(_)))
+            }
+            _ => panic!("expected a nonce too low error, got {:?}", response),
+        }
+    }
+
+    #[tokio::test]
+    async fn surfaces_underpriced_and_replacement_underpriced_as_distinct_errors() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let transactions = blockchain.lock().await.transactions.clone();
+        transactions.lock().await.config.min_gas_price = U256::from(100);
+        let mut module = RpcModule::new(blockchain);
+        eth_send_transaction(&mut module).unwrap();
+
+        let transaction_request = |max_fee_per_gas: u64| TransactionRequest {
+            from: Some(id_1),
+            to: Some(id_2),
+            value: Some(U256::from(1)),
+            nonce: Some(U256::one()),
+            max_fee_per_gas: Some(U256::from(max_fee_per_gas)),
+            ..Default::default()
+        };
+        let send = |max_fee_per_gas: u64| {
+            module.call::<_, H256>(
+                "eth_sendTransaction",
+                [transaction_request(max_fee_per_gas)],
+            )
+        };
+        let rejection = |response| match response {
+            Err(JsonRpseeError::Call(CallError::Custom(error))) => Web3Error::from(error),
+            _ => panic!("expected a rejection, got {:?}", response),
+        };
+
+        assert!(matches!(
+            rejection(send(99).await),
+            Web3Error::GasPriceTooLow(_)
+        ));
+
+        // replacing a pending transaction takes a 10% bump
+        send(200).await.unwrap();
+        assert!(matches!(
+            rejection(send(210).await),
+            Web3Error::ReplacementUnderpriced(_)
+        ));
+        send(220).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn gets_a_receipt_with_its_sender_recipient_and_contract() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let nonce = blockchain.lock().await.next_nonce(&id_1).unwrap();
+        let transfer = TransactionRequest {
+            from: Some(id_1),
+            to: Some(id_2),
+            value: Some(U256::from(1)),
+            nonce: Some(nonce),
+            ..Default::default()
+        };
+        let deploy = TransactionRequest {
+            from: Some(id_1),
+            data: Some(vec![0, 97, 115, 109].into()),
+            nonce: Some(nonce + 1),
+            ..Default::default()
+        };
+        let mut transaction_hashes = vec![];
+
+        for transaction_request in [transfer, deploy] {
+            let transaction_hash = blockchain
+                .lock()
+                .await
+                .send_transaction(transaction_request)
+                .await
+                .unwrap();
+            transaction_hashes.push(transaction_hash);
+        }
+
+        produce_block(blockchain.clone()).await;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_transaction_receipt(&mut module).unwrap();
+        let mut receipts = vec![];
+
+        for transaction_hash in transaction_hashes {
+            let receipt: TransactionReceipt = module
+                .call("eth_getTransactionReceipt", [transaction_hash])
+                .await
+                .unwrap();
+            receipts.push(receipt);
+        }
+
+        assert_eq!((receipts[0].from, receipts[0].to), (id_1, Some(id_2)));
+        assert_eq!(receipts[0].contract_address, None);
+        assert_eq!((receipts[1].from, receipts[1].to), (id_1, None));
+        assert!(receipts[1].contract_address.is_some());
+        assert_eq!(
+            (receipts[0].transaction_index, receipts[1].transaction_index),
+            (Some(U64::zero()), Some(U64::one()))
+        );
+        assert_eq!(receipts[0].block_hash, receipts[1].block_hash);
+    }
+
+    #[tokio::test]
+    async fn gets_a_null_receipt_for_pending_and_unknown_transactions() {
+        let (blockchain, _, _) = setup().await;
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        let mut module = RpcModule::new(blockchain);
+        eth_get_transaction_receipt(&mut module).unwrap();
+
+        for hash in [transaction_hash, H256::random()] {
+            let receipt: Option<TransactionReceipt> = module
+                .call("eth_getTransactionReceipt", [hash])
+                .await
+                .unwrap();
+            assert_eq!(receipt, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn gets_a_block_with_only_transaction_hashes() {
+        let (blockchain, _, _) = setup().await;
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        produce_block(blockchain.clone()).await;
+        let block_number = blockchain.lock().await.get_current_block().unwrap().number;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_block_by_number(&mut module).unwrap();
+
+        let header: serde_json::Value = module
+            .call("eth_getBlockByNumber", rpc_params![block_number, false])
+            .await
+            .unwrap();
+        let block: serde_json::Value = module
+            .call("eth_getBlockByNumber", rpc_params![block_number, true])
+            .await
+            .unwrap();
+
+        let header: BlockHeader = serde_json::from_value(header).unwrap();
+        assert_eq!(header.transactions, vec![transaction_hash]);
+        assert!(serde_json::to_string(&header).unwrap().len() < block.to_string().len());
+    }
+
+    #[tokio::test]
+    async fn gets_a_transaction_count_with_or_without_a_block() {
+        let (blockchain, id_1, _) = setup().await;
+        let block_hash = blockchain
+            .lock()
+            .await
+            .get_current_block()
+            .unwrap()
+            .block_hash()
+            .unwrap();
+        let mut module = RpcModule::new(blockchain);
+        eth_get_transaction_count(&mut module).unwrap();
+
+        let count: String = module
+            .call("eth_getTransactionCount", [id_1])
+            .await
+            .unwrap();
+        let count_at_block: String = module
+            .call("eth_getTransactionCount", rpc_params![id_1, "latest"])
+            .await
+            .unwrap();
+        assert_eq!(count, count_at_block);
+
+        let count_at_hash: String = module
+            .call(
+                "eth_getTransactionCount",
+                rpc_params![id_1, BlockId::Hash(block_hash)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(count, count_at_hash);
+    }
+
+    #[tokio::test]
+    async fn counts_pending_transactions_only_for_the_pending_block() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let transaction = new_transaction(id_2, blockchain.clone()).await;
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        let mut module = RpcModule::new(blockchain);
+        eth_get_transaction_count(&mut module).unwrap();
+
+        let count = |block: BlockTag| {
+            module.call::<_, U256>("eth_getTransactionCount", rpc_params![id_1, block])
+        };
+        assert_eq!(
+            count(BlockTag::Pending).await.unwrap(),
+            count(BlockTag::Latest).await.unwrap() + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn counts_transactions_as_of_an_earlier_block() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let mut counts = vec![];
+
+        for _ in 0..2 {
+            let transaction = new_transaction(id_2, blockchain.clone()).await;
+            let mut blockchain = blockchain.lock().await;
+            blockchain
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            blockchain.produce_block().await.unwrap();
+            counts.push(blockchain.accounts.get_account(&id_1).unwrap().nonce);
+        }
+
+        let head = blockchain.lock().await.get_current_block().unwrap().number;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_transaction_count(&mut module).unwrap();
+
+        let count = |block: BlockTag| {
+            module.call::<_, U256>("eth_getTransactionCount", rpc_params![id_1, block])
+        };
+        assert_eq!(
+            count(BlockTag::Number(head - 1_u64)).await.unwrap(),
+            counts[0]
+        );
+        assert_eq!(count(BlockTag::Number(head)).await.unwrap(), counts[1]);
+        assert_eq!(count(BlockTag::Earliest).await.unwrap(), U256::zero());
+    }
+
+    #[tokio::test]
+    async fn gets_a_balance_as_of_an_earlier_block() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let mut balances = vec![];
+
+        for _ in 0..2 {
+            let transaction = new_transaction(id_2, blockchain.clone()).await;
+            let mut blockchain = blockchain.lock().await;
+            blockchain
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            blockchain.produce_block().await.unwrap();
+            balances.push(blockchain.accounts.get_account(&id_1).unwrap().balance);
+        }
+
+        let head = blockchain.lock().await.get_current_block().unwrap().number;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_balance_by_block(&mut module).unwrap();
+
+        let balance = |block: BlockTag| {
+            module.call::<_, U256>("eth_getBalanceByBlock", rpc_params![id_1, block])
+        };
+        assert_ne!(balances[0], balances[1]);
+        assert_eq!(
+            balance(BlockTag::Number(head - 1_u64)).await.unwrap(),
+            balances[0]
+        );
+        assert_eq!(balance(BlockTag::Latest).await.unwrap(), balances[1]);
+        assert_eq!(balance(BlockTag::Earliest).await.unwrap(), U256::zero());
+    }
+
+    #[tokio::test]
+    async fn gets_the_code_as_of_an_earlier_block() {
+        let (blockchain, _, id_2) = setup().await;
+        let contract = Account::random();
+        let code = vec![0_u8, 97, 115, 109];
+        let before = {
+            let transaction = new_transaction(id_2, blockchain.clone()).await;
+            let mut blockchain = blockchain.lock().await;
+            blockchain
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            blockchain.produce_block().await.unwrap().unwrap()
+        };
+
+        // the contract is deployed in the next block
+        let transaction = new_transaction(id_2, blockchain.clone()).await;
+        {
+            let mut blockchain = blockchain.lock().await;
+            blockchain
+                .accounts
+                .add_account(&contract, &AccountData::new(Some(code.clone().into())))
+                .unwrap();
+            blockchain
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            blockchain.produce_block().await.unwrap();
+        }
+
+        let mut module = RpcModule::new(blockchain);
+        eth_get_code(&mut module).unwrap();
+
+        let get_code =
+            |block: BlockId| module.call::<_, Vec<u8>>("eth_getCode", rpc_params![contract, block]);
+        assert_eq!(get_code(BlockId::default()).await.unwrap(), code);
+        assert!(get_code(BlockId::Hash(before.hash.unwrap())).await.is_err());
+        assert!(get_code(BlockId::Number(BlockTag::Number(before.number)))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn calls_at_a_block_by_hash_or_at_the_latest_by_default() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let transaction = new_transaction(id_2, blockchain.clone()).await;
+        let block = {
+            let mut blockchain = blockchain.lock().await;
+            blockchain
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            blockchain.produce_block().await.unwrap().unwrap()
+        };
+        let transfer = TransactionRequest {
+            from: Some(id_1),
+            to: Some(id_2),
+            value: Some(U256::one()),
+            ..Default::default()
+        };
+        let mut module = RpcModule::new(blockchain);
+        eth_call(&mut module).unwrap();
+
+        let by_hash: String = module
+            .call(
+                "eth_call",
+                rpc_params![transfer.clone(), BlockId::Hash(block.hash.unwrap())],
+            )
+            .await
+            .unwrap();
+        let latest: String = module
+            .call("eth_call", rpc_params![transfer])
+            .await
+            .unwrap();
+
+        assert_eq!((by_hash.as_str(), latest.as_str()), ("0x", "0x"));
+        assert!(module
+            .call::<_, String>(
+                "eth_call",
+                rpc_params![TransactionRequest::default(), BlockId::Hash(H256::random())]
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn gets_the_fee_history() {
+        let (blockchain, _, _) = setup().await;
+        let mut module = RpcModule::new(blockchain);
+        eth_fee_history(&mut module).unwrap();
+
+        let fee_history: FeeHistory = module
+            .call("eth_feeHistory", rpc_params!["0x2", "latest", [25.0, 75.0]])
+            .await
+            .unwrap();
+
+        // only the genesis block exists
+        assert_eq!(fee_history.oldest_block, U64::zero());
+        assert_eq!(fee_history.base_fee_per_gas.len(), 2);
+        assert_eq!(fee_history.gas_used_ratio, vec![0.0]);
+        assert_eq!(fee_history.reward, Some(vec![vec![U256::zero(); 2]]));
+    }
+
+    #[tokio::test]
+    async fn gets_the_gas_price() {
+        let (blockchain, _, _) = setup().await;
+        let transactions = blockchain.lock().await.transactions.clone();
+        transactions.lock().await.config.min_gas_price = U256::from(100);
+        let mut module = RpcModule::new(blockchain);
+        eth_gas_price(&mut module).unwrap();
+
+        let gas_price: U256 = module.call("eth_gasPrice", rpc_params![]).await.unwrap();
+        assert_eq!(gas_price, U256::from(100));
+    }
 }
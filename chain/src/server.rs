@@ -43,14 +43,23 @@ pub(crate) async fn serve(addr: &str, blockchain: Context) -> Result<ServerHandl
 
     // register methods
     eth_block_number(&mut module)?;
+    eth_chain_id(&mut module)?;
     eth_get_block_by_number(&mut module)?;
     eth_get_balance(&mut module)?;
     eth_get_balance_by_block(&mut module)?;
     eth_send_transaction(&mut module)?;
     eth_send_raw_transaction(&mut module)?;
     eth_get_transaction_receipt(&mut module)?;
+    eth_get_block_receipts(&mut module)?;
+    eth_simulate_bundle(&mut module)?;
+    eth_call(&mut module)?;
+    eth_estimate_gas(&mut module)?;
+    eth_gas_price(&mut module)?;
     eth_get_transaction_count(&mut module)?;
     eth_get_code(&mut module)?;
+    eth_fee_history(&mut module)?;
+    evm_set_next_block_timestamp(&mut module)?;
+    chain_stats(&mut module)?;
 
     let server_handle = server.start(module)?;
 
@@ -70,10 +79,10 @@ pub(crate) async fn serve(addr: &str, blockchain: Context) -> Result<ServerHandl
             if let Err(error) = blockchain_for_transaction_processor
                 .lock()
                 .await
-                .process_transactions()
+                .produce_block()
                 .await
             {
-                tracing::error!("Error processing transactions {}", error.to_string());
+                tracing::error!("Error producing a block {}", error.to_string());
             }
         }
     });
@@ -1,4 +1,6 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
 
 use eth_trie::DB as EthDB;
 use rocksdb::{Options, DB};
@@ -11,6 +13,9 @@ const DATABASE_NAME: &str = "db";
 #[derive(Debug)]
 pub(crate) struct Storage {
     db: rocksdb::DB,
+    /// The trie nodes commits have replaced, which pruning deletes once no
+    /// retained state reaches them. Only tracked once pruning is enabled.
+    stale_nodes: Mutex<Option<HashSet<Vec<u8>>>>,
 }
 
 /// Implement a patricia merkle trie interface to work directly with RocksDB
@@ -34,8 +39,12 @@ impl EthDB for Storage {
         Ok(())
     }
 
-    // noop
-    fn remove(&self, _key: &[u8]) -> Result<()> {
+    // past states still need the node, so it's only deleted when pruned
+    fn remove(&self, key: &[u8]) -> Result<()> {
+        if let Some(stale_nodes) = self.stale_nodes().as_mut() {
+            stale_nodes.insert(key.to_vec());
+        }
+
         Ok(())
     }
 
@@ -51,7 +60,48 @@ impl Storage {
         let db = DB::open_default(Storage::path(database_name))
             .map_err(|e| ChainError::StorageCannotOpenDb(e.to_string()))?;
 
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            stale_nodes: Mutex::new(None),
+        })
+    }
+
+    /// Start tracking the trie nodes that commits replace, to prune them
+    pub(crate) fn track_stale_nodes(&self) {
+        self.stale_nodes().get_or_insert_with(HashSet::new);
+    }
+
+    /// Take the trie nodes replaced since they were last taken
+    pub(crate) fn take_stale_nodes(&self) -> HashSet<Vec<u8>> {
+        self.stale_nodes()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Track trie nodes as replaced again, e.g. those still reachable when pruned
+    pub(crate) fn mark_stale_nodes(&self, keys: impl IntoIterator<Item = Vec<u8>>) {
+        if let Some(stale_nodes) = self.stale_nodes().as_mut() {
+            stale_nodes.extend(keys);
+        }
+    }
+
+    /// Delete trie nodes from the database for good
+    pub(crate) fn delete_nodes<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a Vec<u8>>,
+    ) -> Result<()> {
+        for key in keys {
+            self.db
+                .delete(key)
+                .map_err(|_| ChainError::StorageRemoveError(Storage::key_string(key)))?;
+        }
+
+        Ok(())
+    }
+
+    fn stale_nodes(&self) -> MutexGuard<'_, Option<HashSet<Vec<u8>>>> {
+        self.stale_nodes.lock().unwrap_or_else(|e| e.into_inner())
     }
 
     pub(crate) fn _get_all_keys<K: AsRef<[u8]>>(&self) -> Result<Vec<Box<[u8]>>> {
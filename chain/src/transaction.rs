@@ -1,49 +1,410 @@
+use crate::blockchain::StateSnapshot;
 use crate::error::{ChainError, Result};
+use crate::mempool::Mempool;
 
-use dashmap::DashMap;
-use ethereum_types::H256;
+use dashmap::{DashMap, DashSet};
+use ethereum_types::{H256, U256, U64};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use types::account::Account;
+use types::block::Block;
 use types::transaction::{Transaction, TransactionReceipt};
 
+// large enough for WASM contract deployments such as erc20_wit.wasm
+pub(crate) const DEFAULT_MAX_TX_BYTES: u64 = 512 * 1024;
+
+/// How much more (in percent) a transaction must pay to replace one with the same nonce
+pub(crate) const DEFAULT_PRICE_BUMP: u64 = 10;
+
+/// The most transactions waiting in the mempool, similar to geth's `--txpool.globalslots`
+pub(crate) const DEFAULT_MAX_MEMPOOL_SIZE: usize = 5120;
+
+/// The most dropped transactions whose reason and failed receipt are kept
+pub(crate) const DEFAULT_MAX_DROPPED: usize = 4096;
+
+/// Limits applied when admitting transactions into the mempool
+#[derive(Debug, Clone)]
+pub(crate) struct MempoolConfig {
+    pub(crate) max_tx_bytes: u64,
+    /// The price floor, similar to a node operator's `--miner.gasprice`
+    pub(crate) min_gas_price: U256,
+    /// The increase in fee cap (in percent) needed to replace a pending transaction,
+    /// similar to geth's `--txpool.pricebump`
+    pub(crate) price_bump: u64,
+    /// The most transactions the mempool holds. Once full, a transaction that
+    /// pays more than the cheapest sender's last pending one evicts it, and
+    /// others are rejected.
+    pub(crate) max_mempool_size: usize,
+    /// The most dropped transactions remembered, forgetting the earliest
+    /// dropped first, so they're bounded even when blocks aren't pruned
+    pub(crate) max_dropped: usize,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            max_tx_bytes: DEFAULT_MAX_TX_BYTES,
+            min_gas_price: U256::zero(),
+            price_bump: DEFAULT_PRICE_BUMP,
+            max_mempool_size: DEFAULT_MAX_MEMPOOL_SIZE,
+            max_dropped: DEFAULT_MAX_DROPPED,
+        }
+    }
+}
+
+/// The on-disk format of `TransactionStorage`, tagged with its version.
+/// Schema changes add a new version rather than changing an existing one, so
+/// files written by older nodes can still be loaded.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "version")]
+enum PersistedTransactions {
+    /// The chain's blocks are persisted alongside the transactions, and are
+    /// imported again to rebuild the state and receipts they depend on
+    #[serde(rename = "1")]
+    V1 {
+        blocks: Vec<Block>,
+        /// The state of the oldest block, once the blocks before it are pruned
+        state: Option<StateSnapshot>,
+        /// Pending transactions in arrival order
+        mempool: Vec<Transaction>,
+        /// The failed receipt of each dropped transaction, with the reason
+        dropped: Vec<(TransactionReceipt, String)>,
+    },
+}
+
+/// Where a transaction is on its way from the mempool into a block
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TransactionStatus {
+    /// Waiting in the mempool
+    Pending,
+    /// Taken from the mempool into the block being built
+    Processed,
+    Receipted(TransactionReceipt),
+    Unknown,
+}
+
 #[derive(Debug)]
 pub(crate) struct TransactionStorage {
-    pub(crate) mempool: VecDeque<Transaction>,
+    pub(crate) mempool: Mempool,
+    /// Transactions taken from the mempool into the block being built, which
+    /// have no receipt yet
+    pub(crate) processed: DashSet<H256>,
     pub(crate) receipts: DashMap<H256, TransactionReceipt>,
+    /// Transactions left out of a block because they were no longer valid,
+    /// with the reason
+    pub(crate) dropped: DashMap<H256, String>,
+    /// The head of the chain when pruning first saw each dropped transaction,
+    /// so the transaction is pruned along with that block
+    pub(crate) dropped_at: DashMap<H256, U64>,
+    /// The dropped transactions, earliest dropped first
+    pub(crate) dropped_order: VecDeque<H256>,
+    pub(crate) config: MempoolConfig,
 }
 
 impl TransactionStorage {
     pub(crate) fn new() -> Self {
+        Self::with_config(MempoolConfig::default())
+    }
+
+    pub(crate) fn with_config(config: MempoolConfig) -> Self {
         Self {
-            mempool: VecDeque::new(),
+            mempool: Mempool::default(),
+            processed: DashSet::new(),
             receipts: DashMap::new(),
+            dropped: DashMap::new(),
+            dropped_at: DashMap::new(),
+            dropped_order: VecDeque::new(),
+            config,
         }
     }
 
     // add to the transaction mempool
-    pub(crate) fn send_transaction(&mut self, transaction: Transaction) {
+    pub(crate) fn send_transaction(&mut self, transaction: Transaction) -> Result<()> {
+        // a resubmitted transaction (e.g. a client retry) is only processed
+        // once, though one that was dropped can be sent again
+        if let Some(hash) = transaction.hash {
+            if self.mempool.contains(&hash)
+                || self.processed.contains(&hash)
+                || (self.receipts.contains_key(&hash) && !self.dropped.contains_key(&hash))
+            {
+                return Err(ChainError::DuplicateTransaction(hash.to_string()));
+            }
+        }
+
+        let size = bincode::serialized_size(&transaction)?;
+
+        if size > self.config.max_tx_bytes {
+            return Err(ChainError::TransactionTooLarge(
+                transaction
+                    .hash
+                    .map_or_else(|| "without a hash".into(), |hash| format!("{:?}", hash)),
+                size.to_string(),
+                self.config.max_tx_bytes.to_string(),
+            ));
+        }
+
+        if transaction.fee_cap() < self.config.min_gas_price {
+            return Err(ChainError::Underpriced(
+                transaction.fee_cap().to_string(),
+                self.config.min_gas_price.to_string(),
+            ));
+        }
+
+        // a transaction with the nonce of a pending one replaces it, as long as
+        // it pays enough more to be worth it
+        let pending = transaction
+            .nonce
+            .and_then(|nonce| self.mempool.get(&transaction.from, nonce));
+
+        if let Some(pending) = pending {
+            let fee_cap = pending.fee_cap();
+            let required = (fee_cap + fee_cap * self.config.price_bump / 100).max(fee_cap + 1);
+
+            if transaction.fee_cap() < required {
+                return Err(ChainError::ReplacementUnderpriced(
+                    transaction.fee_cap().to_string(),
+                    required.to_string(),
+                ));
+            }
+        } else if self.mempool.len() >= self.config.max_mempool_size {
+            // a full mempool makes room by evicting its cheapest transaction,
+            // but only for one that pays more
+            let outbid = self
+                .mempool
+                .cheapest()
+                .is_some_and(|cheapest| transaction.fee_cap() > cheapest.fee_cap());
+
+            if !outbid {
+                return Err(ChainError::MempoolFull(
+                    self.config.max_mempool_size.to_string(),
+                ));
+            }
+
+            if let Some(evicted) = self.mempool.evict_cheapest() {
+                self.drop_transaction(&evicted, "evicted from a full mempool".into());
+            }
+        }
+
+        // the receipt it was dropped with no longer applies
+        if let Some(hash) = transaction.hash {
+            if self.dropped.remove(&hash).is_some() {
+                self.receipts.remove(&hash);
+                self.dropped_at.remove(&hash);
+                self.dropped_order.retain(|dropped| *dropped != hash);
+            }
+        }
+
         self.mempool.push_back(transaction);
+
+        Ok(())
+    }
+
+    // take up to `n` transactions from the mempool, best paying first
+    pub(crate) fn take_best(&mut self, n: usize) -> Vec<Transaction> {
+        self.mempool.take_best(n)
+    }
+
+    // get the transactions in the mempool from an account, ordered by nonce
+    pub(crate) fn pending_for(&self, account: &Account) -> Vec<Transaction> {
+        let mut transactions = self
+            .mempool
+            .iter()
+            .filter(|transaction| transaction.from == *account)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        transactions.sort_by_key(|transaction| transaction.nonce);
+
+        transactions
+    }
+
+    // get the status of the transaction, checking the mempool, then the block
+    // being built, then the receipts
+    pub(crate) fn transaction_status(&self, hash: &H256) -> TransactionStatus {
+        if self.mempool.contains(hash) {
+            TransactionStatus::Pending
+        } else if self.processed.contains(hash) {
+            TransactionStatus::Processed
+        } else if let Some(receipt) = self.receipts.get(hash) {
+            TransactionStatus::Receipted(receipt.value().clone())
+        } else {
+            TransactionStatus::Unknown
+        }
     }
 
     // get the receipt of the transaction
     pub(crate) fn get_transaction_receipt(&self, hash: &H256) -> Result<TransactionReceipt> {
-        let transaction_receipt = self
-            .receipts
-            .get(hash)
-            .ok_or_else(|| ChainError::TransactionNotFound(hash.to_string()))?
-            .value()
-            .clone();
+        match self.transaction_status(hash) {
+            TransactionStatus::Receipted(transaction_receipt) => Ok(transaction_receipt),
+            TransactionStatus::Pending | TransactionStatus::Processed => {
+                Err(ChainError::TransactionPending(hash.to_string()))
+            }
+            TransactionStatus::Unknown => Err(ChainError::TransactionNotFound(hash.to_string())),
+        }
+    }
+
+    // drop a transaction that can't go into a block, recording why alongside
+    // a failed receipt so that clients waiting on it can stop (a transaction
+    // without a hash can't be waited on, so there is nothing to record)
+    pub(crate) fn drop_transaction(&mut self, transaction: &Transaction, reason: String) {
+        let Some(hash) = transaction.hash else {
+            return;
+        };
+        let receipt = TransactionReceipt {
+            block_hash: None,
+            block_number: None,
+            contract_address: None,
+            from: transaction.from,
+            to: transaction.to,
+            transaction_hash: hash,
+            transaction_index: None,
+            gas_used: U256::zero(),
+            status: U64::zero(),
+        };
+
+        self.receipts.insert(hash, receipt);
+        self.record_dropped(hash, reason);
+    }
+
+    // remember why a transaction was dropped, forgetting the earliest dropped
+    // ones (and their failed receipts) beyond `max_dropped`
+    fn record_dropped(&mut self, hash: H256, reason: String) {
+        if self.dropped.insert(hash, reason).is_some() {
+            self.dropped_order.retain(|dropped| *dropped != hash);
+        }
+
+        self.dropped_order.push_back(hash);
+
+        while self.dropped_order.len() > self.config.max_dropped {
+            let Some(forgotten) = self.dropped_order.pop_front() else {
+                break;
+            };
+
+            self.dropped.remove(&forgotten);
+            self.dropped_at.remove(&forgotten);
+            self.receipts.remove(&forgotten);
+        }
+    }
 
-        Ok(transaction_receipt)
+    /// Forget the transactions dropped before the `oldest` retained block,
+    /// with their failed receipts. Those dropped since the last prune are
+    /// dated `head`.
+    pub(crate) fn prune_dropped(&mut self, head: U64, oldest: U64) {
+        for dropped in self.dropped.iter() {
+            self.dropped_at.entry(*dropped.key()).or_insert(head);
+        }
+
+        self.dropped_at.retain(|hash, dropped_at| {
+            if *dropped_at >= oldest {
+                return true;
+            }
+
+            self.dropped.remove(hash);
+            self.receipts.remove(hash);
+            false
+        });
+
+        let dropped = &self.dropped;
+        self.dropped_order.retain(|hash| dropped.contains_key(hash));
+    }
+
+    // get the reason a transaction was dropped from a block
+    pub(crate) fn get_dropped_reason(&self, hash: &H256) -> Option<String> {
+        self.dropped.get(hash).map(|reason| reason.value().clone())
+    }
+
+    /// Write the mempool and dropped transactions to a file, to be read back
+    /// with `load`. The chain persists its blocks with them, with `persist_with`.
+    #[allow(unused)]
+    pub(crate) fn persist(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.persist_with(path, vec![], None)
+    }
+
+    /// Write the mempool and dropped transactions to a file alongside the
+    /// chain's `blocks`, which the receipts of mined transactions are rebuilt
+    /// from, and the `state` of the oldest block if it can't be rebuilt
+    pub(crate) fn persist_with(
+        &self,
+        path: impl AsRef<Path>,
+        blocks: Vec<Block>,
+        state: Option<StateSnapshot>,
+    ) -> Result<()> {
+        write_persisted(path, &self.to_persisted(blocks, state)?)
+    }
+
+    fn to_persisted(&self, blocks: Vec<Block>, state: Option<StateSnapshot>) -> Result<Vec<u8>> {
+        let persisted = PersistedTransactions::V1 {
+            blocks,
+            state,
+            mempool: self.mempool.iter().cloned().collect(),
+            dropped: self
+                .dropped
+                .iter()
+                .filter_map(|dropped| {
+                    let receipt = self.receipts.get(dropped.key())?.value().clone();
+                    Some((receipt, dropped.value().clone()))
+                })
+                .collect(),
+        };
+
+        serde_json::to_vec(&persisted).map_err(|e| ChainError::SerializeError(e.to_string()))
+    }
+
+    /// Rebuild the storage from a file written by `persist` or `persist_with` with
+    /// `config`, returning the blocks to import to rebuild the receipts of
+    /// mined transactions, along with the state of the oldest if persisted
+    pub(crate) fn load(
+        path: impl AsRef<Path>,
+        config: MempoolConfig,
+    ) -> Result<(Self, Vec<Block>, Option<StateSnapshot>)> {
+        let serialized = fs::read(path)?;
+        let persisted: PersistedTransactions = serde_json::from_slice(&serialized)
+            .map_err(|e| ChainError::DeserializeError(e.to_string()))?;
+        let mut transaction_storage = Self::with_config(config);
+
+        let PersistedTransactions::V1 {
+            blocks,
+            state,
+            mempool,
+            dropped,
+        } = persisted;
+
+        for transaction in mempool {
+            transaction_storage.mempool.push_back(transaction);
+        }
+
+        for (receipt, reason) in dropped {
+            let hash = receipt.transaction_hash;
+            transaction_storage.receipts.insert(hash, receipt);
+            transaction_storage.record_dropped(hash, reason);
+        }
+
+        Ok((transaction_storage, blocks, state))
     }
 }
 
+/// Write serialized transactions to a file.
+/// The file is replaced in one step, so a crash mid-write leaves the
+/// previous file intact.
+fn write_persisted(path: impl AsRef<Path>, persisted: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    let temporary = path.with_extension("tmp");
+
+    fs::write(&temporary, persisted)?;
+    fs::rename(&temporary, path)?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::blockchain::tests::{assert_receipt, new_transaction};
     use crate::helpers::tests::setup;
 
     use super::*;
-    use types::account::Account;
+    use types::bytes::Bytes;
 
     #[tokio::test]
     async fn sends_a_transaction() {
@@ -52,8 +413,309 @@ mod tests {
         let transaction = new_transaction(Account::random(), blockchain.clone()).await;
         assert_eq!(transaction_storage.mempool.len(), 0);
 
-        transaction_storage.send_transaction(transaction);
+        transaction_storage.send_transaction(transaction).unwrap();
+        assert_eq!(transaction_storage.mempool.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_transaction_sent_twice() {
+        let (blockchain, _, _) = setup().await;
+        let mut transaction_storage = TransactionStorage::new();
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let transaction_hash = transaction.transaction_hash().unwrap();
+        let duplicate = Err(ChainError::DuplicateTransaction(
+            transaction_hash.to_string(),
+        ));
+
+        transaction_storage
+            .send_transaction(transaction.clone())
+            .unwrap();
+        assert_eq!(
+            transaction_storage.send_transaction(transaction.clone()),
+            duplicate
+        );
+        assert_eq!(transaction_storage.mempool.len(), 1);
+
+        // still a duplicate once it is taken into a block
+        transaction_storage.take_best(1);
+        transaction_storage.processed.insert(transaction_hash);
+        assert_eq!(transaction_storage.send_transaction(transaction), duplicate);
+    }
+
+    #[tokio::test]
+    async fn accepts_a_dropped_transaction_sent_again() {
+        let (blockchain, _, _) = setup().await;
+        let mut transaction_storage = TransactionStorage::new();
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let transaction_hash = transaction.transaction_hash().unwrap();
+
+        transaction_storage
+            .send_transaction(transaction.clone())
+            .unwrap();
+        transaction_storage.take_best(1);
+        transaction_storage.drop_transaction(&transaction, "insufficient funds".into());
+
+        transaction_storage.send_transaction(transaction).unwrap();
+        assert_eq!(transaction_storage.mempool.len(), 1);
+        assert_eq!(
+            transaction_storage.get_dropped_reason(&transaction_hash),
+            None
+        );
+        assert_eq!(
+            transaction_storage.transaction_status(&transaction_hash),
+            TransactionStatus::Pending
+        );
+    }
+
+    #[tokio::test]
+    async fn gets_the_status_of_a_transaction() {
+        let (blockchain, _, _) = setup().await;
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let transaction_hash = transaction.transaction_hash().unwrap();
+        let mut transaction_storage = TransactionStorage::new();
+        let status = |storage: &TransactionStorage| storage.transaction_status(&transaction_hash);
+        assert_eq!(status(&transaction_storage), TransactionStatus::Unknown);
+
+        transaction_storage.send_transaction(transaction).unwrap();
+        assert_eq!(status(&transaction_storage), TransactionStatus::Pending);
+        assert!(matches!(
+            transaction_storage.get_transaction_receipt(&transaction_hash),
+            Err(ChainError::TransactionPending(_))
+        ));
+
+        transaction_storage.take_best(1);
+        transaction_storage.processed.insert(transaction_hash);
+        assert_eq!(status(&transaction_storage), TransactionStatus::Processed);
+
+        let receipt = TransactionReceipt {
+            block_hash: Some(H256::random()),
+            block_number: None,
+            contract_address: None,
+            from: Account::random(),
+            to: None,
+            transaction_hash,
+            transaction_index: None,
+            gas_used: U256::from(21_000),
+            status: 1.into(),
+        };
+        transaction_storage.processed.remove(&transaction_hash);
+        transaction_storage
+            .receipts
+            .insert(transaction_hash, receipt.clone());
+        assert_eq!(
+            status(&transaction_storage),
+            TransactionStatus::Receipted(receipt)
+        );
+    }
+
+    #[test]
+    fn rejects_a_transaction_over_the_size_limit() {
+        let transaction = |size: usize| {
+            let data = Some(Bytes::from(vec![1u8; size]));
+            Transaction::new(
+                Account::random(),
+                None,
+                U256::zero(),
+                Some(U256::zero()),
+                data,
+            )
+            .unwrap()
+        };
+        let max_tx_bytes = bincode::serialized_size(&transaction(1000)).unwrap();
+        let mut transaction_storage = TransactionStorage::with_config(MempoolConfig {
+            max_tx_bytes,
+            ..MempoolConfig::default()
+        });
+
+        assert!(transaction_storage
+            .send_transaction(transaction(1000))
+            .is_ok());
+        let oversized = transaction(1001);
+        let hash = oversized.hash.unwrap();
+        assert!(matches!(
+            transaction_storage.send_transaction(oversized),
+            Err(ChainError::TransactionTooLarge(reported, ..)) if reported == format!("{:?}", hash)
+        ));
+        assert_eq!(transaction_storage.mempool.len(), 1);
+    }
+
+    #[test]
+    fn evicts_the_cheapest_transaction_for_a_pricier_one_when_full() {
+        let transaction = |gas_price: u64| {
+            let mut transaction = Transaction::new(
+                Account::random(),
+                Some(Account::random()),
+                U256::zero(),
+                Some(U256::one()),
+                None,
+            )
+            .unwrap();
+            transaction.gas_price = U256::from(gas_price);
+            transaction.hash().unwrap();
+            transaction
+        };
+        let mut transaction_storage = TransactionStorage::with_config(MempoolConfig {
+            max_mempool_size: 2,
+            ..MempoolConfig::default()
+        });
+        let cheapest = transaction(10);
+        let cheapest_hash = cheapest.transaction_hash().unwrap();
+        transaction_storage.send_transaction(cheapest).unwrap();
+        transaction_storage
+            .send_transaction(transaction(20))
+            .unwrap();
+
+        // paying no more than the cheapest isn't enough to get in
+        assert_eq!(
+            transaction_storage.send_transaction(transaction(10)),
+            Err(ChainError::MempoolFull("2".into()))
+        );
+
+        transaction_storage
+            .send_transaction(transaction(30))
+            .unwrap();
+        assert_eq!(transaction_storage.mempool.len(), 2);
+        assert!(!transaction_storage.mempool.contains(&cheapest_hash));
+        assert!(transaction_storage
+            .get_dropped_reason(&cheapest_hash)
+            .is_some());
+    }
+
+    #[test]
+    fn forgets_the_earliest_dropped_transactions_beyond_the_limit() {
+        let mut transaction_storage = TransactionStorage::with_config(MempoolConfig {
+            max_dropped: 2,
+            ..MempoolConfig::default()
+        });
+        let hashes = (0..3)
+            .map(|_| {
+                let transaction = Transaction::new(
+                    Account::random(),
+                    Some(Account::random()),
+                    U256::zero(),
+                    Some(U256::one()),
+                    None,
+                )
+                .unwrap();
+                transaction_storage.drop_transaction(&transaction, "insufficient funds".into());
+                transaction.transaction_hash().unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(transaction_storage.get_dropped_reason(&hashes[0]), None);
+        assert_eq!(
+            transaction_storage.transaction_status(&hashes[0]),
+            TransactionStatus::Unknown
+        );
+        for hash in &hashes[1..] {
+            assert!(transaction_storage.get_dropped_reason(hash).is_some());
+        }
+        assert_eq!(transaction_storage.dropped.len(), 2);
+        assert_eq!(transaction_storage.receipts.len(), 2);
+    }
+
+    #[test]
+    fn rejects_a_transaction_below_the_minimum_gas_price() {
+        let transaction = |gas_price: u64| {
+            let mut transaction = Transaction::new(
+                Account::random(),
+                Some(Account::random()),
+                U256::zero(),
+                Some(U256::zero()),
+                None,
+            )
+            .unwrap();
+            transaction.gas_price = U256::from(gas_price);
+            transaction
+        };
+        let mut transaction_storage = TransactionStorage::with_config(MempoolConfig {
+            min_gas_price: U256::from(100),
+            ..MempoolConfig::default()
+        });
+
+        assert!(matches!(
+            transaction_storage.send_transaction(transaction(99)),
+            Err(ChainError::Underpriced(..))
+        ));
+        assert!(transaction_storage
+            .send_transaction(transaction(100))
+            .is_ok());
+
+        // dynamic fee transactions are compared using their max fee
+        let mut dynamic_fee = transaction(0);
+        dynamic_fee.max_fee_per_gas = Some(U256::from(99));
+        assert!(transaction_storage
+            .send_transaction(dynamic_fee.clone())
+            .is_err());
+
+        dynamic_fee.max_fee_per_gas = Some(U256::from(100));
+        assert!(transaction_storage.send_transaction(dynamic_fee).is_ok());
+        assert_eq!(transaction_storage.mempool.len(), 2);
+    }
+
+    #[test]
+    fn rejects_a_transaction_that_does_not_outbid_a_pending_one() {
+        let mut transaction = Transaction::new(
+            Account::random(),
+            Some(Account::random()),
+            U256::zero(),
+            Some(U256::one()),
+            None,
+        )
+        .unwrap();
+        let mut transaction_storage = TransactionStorage::new();
+        transaction.gas_price = U256::from(100);
+        transaction_storage
+            .send_transaction(transaction.clone())
+            .unwrap();
+
+        assert_eq!(
+            transaction_storage.send_transaction(transaction),
+            Err(ChainError::ReplacementUnderpriced(
+                "100".into(),
+                "110".into()
+            ))
+        );
+        assert_eq!(transaction_storage.mempool.len(), 1);
+    }
+
+    #[test]
+    fn replaces_a_transaction_that_pays_enough_more() {
+        let mut transaction = Transaction::new(
+            Account::random(),
+            Some(Account::random()),
+            U256::zero(),
+            Some(U256::one()),
+            None,
+        )
+        .unwrap();
+        let mut transaction_storage = TransactionStorage::new();
+        transaction.gas_price = U256::from(100);
+        transaction_storage
+            .send_transaction(transaction.clone())
+            .unwrap();
+
+        // the default bump is 10%
+        transaction.gas_price = U256::from(109);
+        transaction.hash().unwrap();
+        assert_eq!(
+            transaction_storage.send_transaction(transaction.clone()),
+            Err(ChainError::ReplacementUnderpriced(
+                "109".into(),
+                "110".into()
+            ))
+        );
+
+        transaction.gas_price = U256::from(110);
+        transaction.hash().unwrap();
+        transaction_storage
+            .send_transaction(transaction.clone())
+            .unwrap();
         assert_eq!(transaction_storage.mempool.len(), 1);
+        assert_eq!(
+            transaction_storage.mempool.iter().next().unwrap().gas_price,
+            U256::from(110)
+        );
     }
 
     #[tokio::test]
@@ -69,8 +731,73 @@ mod tests {
             .transactions
             .lock()
             .await
-            .send_transaction(transaction);
+            .send_transaction(transaction)
+            .unwrap();
 
         assert_receipt(blockchain, transaction_hash).await;
     }
+
+    #[tokio::test]
+    async fn persists_and_loads_transactions() {
+        let (blockchain, _, _) = setup().await;
+        let config = MempoolConfig {
+            max_mempool_size: 7,
+            ..MempoolConfig::default()
+        };
+        let mut transaction_storage = TransactionStorage::with_config(config.clone());
+        let pending = new_transaction(Account::random(), blockchain.clone()).await;
+        transaction_storage
+            .send_transaction(pending.clone())
+            .unwrap();
+
+        let dropped = new_transaction(Account::random(), blockchain.clone()).await;
+        let dropped_hash = dropped.transaction_hash().unwrap();
+        transaction_storage.drop_transaction(&dropped, "Insufficient funds".into());
+
+        // the receipts of mined transactions come back as their blocks are imported
+        let mined = TransactionReceipt {
+            block_hash: Some(H256::random()),
+            block_number: None,
+            contract_address: None,
+            from: Account::random(),
+            to: None,
+            transaction_hash: H256::random(),
+            transaction_index: None,
+            gas_used: U256::from(21_000),
+            status: 1.into(),
+        };
+        transaction_storage
+            .receipts
+            .insert(mined.transaction_hash, mined.clone());
+        let blocks = vec![blockchain.lock().await.get_current_block().unwrap()];
+
+        let path = std::env::temp_dir().join(format!("transactions-{:?}.json", H256::random()));
+        transaction_storage
+            .persist_with(&path, blocks.clone(), None)
+            .unwrap();
+        let (loaded, loaded_blocks, state) = TransactionStorage::load(&path, config).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded_blocks, blocks);
+        assert!(state.is_none());
+        assert_eq!(loaded.config.max_mempool_size, 7);
+        assert_eq!(
+            loaded.mempool.iter().cloned().collect::<Vec<_>>(),
+            vec![pending]
+        );
+        assert_eq!(
+            loaded
+                .get_transaction_receipt(&dropped_hash)
+                .unwrap()
+                .status,
+            U64::zero()
+        );
+        assert_eq!(
+            loaded.get_dropped_reason(&dropped_hash),
+            Some("Insufficient funds".into())
+        );
+        assert!(loaded
+            .get_transaction_receipt(&mined.transaction_hash)
+            .is_err());
+    }
 }
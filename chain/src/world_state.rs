@@ -1,18 +1,35 @@
-use ethereum_types::H256;
+use std::collections::HashMap;
+
+use ethereum_types::{H256, U64};
 
 #[derive(Debug)]
 pub(crate) struct WorldState {
     state_trie: H256,
+    // the state root of the account trie just before each block was applied
+    pre_state_roots: HashMap<U64, H256>,
 }
 
 impl WorldState {
     pub(crate) fn new() -> Self {
         WorldState {
             state_trie: H256::zero(),
+            pre_state_roots: HashMap::new(),
         }
     }
 
     pub(crate) fn update_state_trie(&mut self, hash: H256) {
         self.state_trie = hash;
     }
+
+    pub(crate) fn record_pre_state_root(&mut self, block_number: U64, hash: H256) {
+        self.pre_state_roots.insert(block_number, hash);
+    }
+
+    pub(crate) fn pre_state_root(&self, block_number: U64) -> Option<H256> {
+        self.pre_state_roots.get(&block_number).copied()
+    }
+
+    pub(crate) fn remove_pre_state_root(&mut self, block_number: U64) -> Option<H256> {
+        self.pre_state_roots.remove(&block_number)
+    }
 }
@@ -0,0 +1,31 @@
+// the bindings pass each string argument of `forward` as a pointer and length
+#![allow(clippy::too_many_arguments)]
+
+wit_bindgen::generate!("caller");
+
+pub struct Caller;
+
+export_contract!(Caller);
+
+impl Contract for Caller {
+    /// Call `function(key, value)` on the contract at `address`, recording
+    /// whether the call succeeded under the `called` key
+    fn forward(address: String, function: String, key: String, value: String) {
+        let params = ["String", key.as_str(), "String", value.as_str()];
+        let called = call(&address, &function, &params);
+
+        storage_set("called", &called.to_string());
+    }
+
+    /// Call `recurse` on the contract at `address` a level deeper, recording
+    /// the deepest level reached under the `depth` key
+    fn recurse(address: String, depth: u64) {
+        storage_set("depth", &depth.to_string());
+
+        let next = (depth + 1).to_string();
+        let params = ["String", address.as_str(), "U64", next.as_str()];
+        let called = call(&address, "recurse", &params);
+
+        storage_set("called", &called.to_string());
+    }
+}
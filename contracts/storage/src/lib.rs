@@ -0,0 +1,24 @@
+wit_bindgen::generate!("storage");
+
+pub struct Storage;
+
+export_contract!(Storage);
+
+impl Contract for Storage {
+    fn set(key: String, value: String) {
+        storage_set(&key, &value);
+    }
+
+    fn set_and_fail(key: String, value: String) {
+        storage_set(&key, &value);
+        panic!("failing after writing {}", key);
+    }
+
+    fn destruct(beneficiary: String) {
+        self_destruct(&beneficiary);
+    }
+
+    fn store_timestamp() {
+        storage_set("timestamp", &block_timestamp().to_string());
+    }
+}
@@ -0,0 +1,83 @@
+//! # Execution Context
+//!
+//! The inputs to a contract call: the block it runs in and the call itself.
+//! Host functions read what they expose to contracts from the context, so
+//! exposing another value only takes a field here and a host function.
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A contract function and its params, as pairs of type and value
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Input {
+    pub function: String,
+    pub params: Vec<String>,
+}
+
+impl Input {
+    pub fn new(function: &str, params: &[&str]) -> Self {
+        Self {
+            function: function.into(),
+            params: params.iter().map(|param| param.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionContext {
+    /// The number of the block the call runs in
+    pub block: u64,
+    /// The account credited with the block's fees
+    pub coinbase: String,
+    /// The block's timestamp, in seconds since the Unix epoch
+    pub timestamp: u64,
+    pub base_fee: u64,
+    pub gas_limit: u64,
+    /// The account that made the call
+    pub caller: String,
+    /// The contract being called
+    pub callee: String,
+    /// The wei sent with the call, in decimal
+    pub value: String,
+    pub input: Input,
+    /// The gas available to the call
+    pub gas: u64,
+}
+
+impl ExecutionContext {
+    /// The context of a call from this call's contract into another, in the
+    /// same block and without any value
+    pub(crate) fn call(&self, callee: String, input: Input, gas: u64) -> Self {
+        Self {
+            caller: self.callee.clone(),
+            callee,
+            value: "0".into(),
+            input,
+            gas,
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_keep_the_block_and_come_from_the_callee() {
+        let context = ExecutionContext {
+            block: 7,
+            timestamp: 1_700_000_000,
+            caller: "0xa".into(),
+            callee: "0xb".into(),
+            value: "100".into(),
+            ..Default::default()
+        };
+        let call = context.call("0xc".into(), Input::new("set", &["String", "key"]), 10);
+
+        assert_eq!((call.block, call.timestamp), (7, 1_700_000_000));
+        assert_eq!((call.caller.as_str(), call.callee.as_str()), ("0xb", "0xc"));
+        assert_eq!(call.value, "0");
+        assert_eq!(call.input.params, vec!["String", "key"]);
+        assert_eq!(call.gas, 10);
+    }
+}
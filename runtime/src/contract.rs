@@ -1,4 +1,14 @@
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use crate::context::{ExecutionContext, Input};
 use crate::error::{Result, RuntimeError};
+use crate::host::{self, Frame, SelfDestructs, State, StorageChanges, MAX_CALL_DEPTH};
+use lazy_static::lazy_static;
+use lru::LruCache;
+use utils::crypto::hash;
 use wasmtime::{
     self,
     component::{Component, Instance, Linker, Val},
@@ -6,38 +16,239 @@ use wasmtime::{
 };
 use wit_component::ComponentEncoder;
 
+/// Gas for calls made outside of a transaction, which aren't metered
+const UNMETERED_GAS: u64 = i64::MAX as u64;
+
+/// The most stack a contract's own code can use in each call
+const MAX_WASM_STACK: usize = 256 * 1024;
+
+/// The stack each call needs, the contract's own and the runtime's to set
+/// the call up
+const CALL_STACK_SIZE: usize = 2 * MAX_WASM_STACK;
+
+/// The most compiled contracts kept, dropping the least recently used
+const MAX_CACHED_COMPONENTS: usize = 256;
+
+/// The threads executions run on, each with the stack for the deepest calls.
+/// Executions past this many wait for a thread to finish.
+const EXECUTION_THREADS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+lazy_static! {
+    /// Components are compiled for an engine, so every execution shares one
+    static ref ENGINE: std::result::Result<Engine, String> =
+        engine().map_err(|error| error.to_string());
+
+    /// Compiled contracts, by the hash of their code
+    static ref COMPONENTS: Mutex<LruCache<[u8; 32], Component>> = Mutex::new(LruCache::new(
+        NonZeroUsize::new(MAX_CACHED_COMPONENTS).unwrap_or(NonZeroUsize::MIN)
+    ));
+
+    /// Executions waiting for an execution thread
+    static ref EXECUTIONS: std::result::Result<Mutex<mpsc::Sender<Job>>, String> =
+        spawn_execution_threads();
+}
+
+/// The result of executing a contract function
+#[derive(Debug)]
+pub struct Execution {
+    pub gas_used: u64,
+    /// The storage writes of every call made, if the execution succeeded
+    pub changes: Result<StorageChanges>,
+    /// The contracts that self-destructed, which is empty if the execution failed
+    pub self_destructs: SelfDestructs,
+}
+
 pub fn call_function(bytes: &[u8], function: &str, params: &[&str]) -> Result<()> {
-    let (mut store, instance) = load_contract(bytes)?;
+    let context = ExecutionContext {
+        input: Input::new(function, params),
+        gas: UNMETERED_GAS,
+        ..Default::default()
+    };
+    let frame = Frame::new(Arc::new(host::EmptyState), context, StorageChanges::new());
+    let (_, _, result) = run(&shared_engine()?, bytes, frame);
+
+    result
+}
+
+/// Execute the call described by the context against the contract at its
+/// callee, with the context's gas as the budget.
+/// The contract can read the chain's state and call other contracts, which are
+/// charged to the same budget. Calls into other contracts nest on the stack,
+/// so the execution runs on one of the execution threads, which have room
+/// for the deepest.
+pub fn execute(state: Arc<dyn State>, context: ExecutionContext) -> Execution {
+    let (sender, receiver) = mpsc::channel();
+    let job: Job = Box::new(move || {
+        // the caller may have stopped waiting
+        let _ = sender.send(execute_on_thread(state, context));
+    });
+    let execution = EXECUTIONS
+        .as_ref()
+        .map_err(|error| RuntimeError::ExecutionError(error.clone()))
+        .and_then(|executions| {
+            executions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .send(job)
+                .map_err(|_| RuntimeError::ExecutionError("the execution threads stopped".into()))
+        })
+        .and_then(|_| {
+            receiver
+                .recv()
+                .map_err(|_| RuntimeError::ExecutionError("the execution panicked".into()))
+        });
+
+    execution.unwrap_or_else(|error| Execution {
+        gas_used: 0,
+        changes: Err(error),
+        self_destructs: SelfDestructs::new(),
+    })
+}
+
+/// Start the execution threads, which take executions from the returned
+/// sender until it's dropped. A panicking execution leaves its thread running.
+fn spawn_execution_threads() -> std::result::Result<Mutex<mpsc::Sender<Job>>, String> {
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    for index in 0..EXECUTION_THREADS {
+        let receiver = Arc::clone(&receiver);
+
+        thread::Builder::new()
+            .name(format!("execution {}", index))
+            .stack_size(MAX_CALL_DEPTH * CALL_STACK_SIZE)
+            .spawn(move || loop {
+                let job = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+
+                match job {
+                    Ok(job) => {
+                        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                    }
+                    Err(_) => break,
+                }
+            })
+            .map_err(|error| error.to_string())?;
+    }
+
+    Ok(Mutex::new(sender))
+}
+
+fn execute_on_thread(state: Arc<dyn State>, context: ExecutionContext) -> Execution {
+    let Some(code) = state.code(&context.callee) else {
+        return Execution {
+            gas_used: 0,
+            changes: Err(RuntimeError::ExecutionError(format!(
+                "{} is not a contract",
+                context.callee
+            ))),
+            self_destructs: SelfDestructs::new(),
+        };
+    };
+
+    let engine = match shared_engine() {
+        Ok(engine) => engine,
+        Err(error) => {
+            return Execution {
+                gas_used: 0,
+                changes: Err(error),
+                self_destructs: SelfDestructs::new(),
+            }
+        }
+    };
+
+    let frame = Frame::new(state, context, StorageChanges::new());
+    let (frame, gas_used, result) = run(&engine, &code, frame);
+    let self_destructs = match result {
+        Ok(()) => frame.self_destructs,
+        Err(_) => SelfDestructs::new(),
+    };
+
+    Execution {
+        gas_used,
+        changes: result.map(|_| frame.changes),
+        self_destructs,
+    }
+}
+
+/// Run the call of a frame's context in the frame, returning the frame and
+/// the gas used alongside the result
+pub(crate) fn run(engine: &Engine, bytes: &[u8], frame: Frame) -> (Frame, u64, Result<()>) {
+    let gas = frame.context.gas;
+    let Input { function, params } = frame.context.input.clone();
+    let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+    let mut store = Store::new(engine, frame);
+    let result = store
+        .add_fuel(gas)
+        .map_err(RuntimeError::from)
+        .and_then(|_| call(&mut store, bytes, &function, &params));
+    let gas_used = store.fuel_consumed().unwrap_or_default();
+
+    (store.into_data(), gas_used, result)
+}
+
+fn call(store: &mut Store<Frame>, bytes: &[u8], function: &str, params: &[&str]) -> Result<()> {
+    let instance = load_contract(store, bytes)?;
     let parsed: Result<Vec<Val>> = params.chunks_exact(2).map(parse_params).collect();
 
     tracing::info!("{} params {:?}", function, parsed);
 
     let function = instance
-        .get_func(&mut store, function)
+        .get_func(&mut *store, function)
         .ok_or_else(|| RuntimeError::ExportFunctionError(function.into()))?;
 
     function
-        .call(&mut store, &parsed?, &mut [])
+        .call(&mut *store, &parsed?, &mut [])
         .map_err(|e| RuntimeError::CallFunctionError(e.to_string()))
 }
 
-fn load_contract(bytes: &[u8]) -> Result<(Store<i32>, Instance)> {
+/// Gas is metered as wasmtime fuel
+fn engine() -> Result<Engine> {
     let mut config = Config::new();
 
     Config::wasm_component_model(&mut config, true);
+    config.consume_fuel(true);
+    config.max_wasm_stack(MAX_WASM_STACK);
+
+    Ok(Engine::new(&config)?)
+}
+
+fn shared_engine() -> Result<Engine> {
+    ENGINE.clone().map_err(RuntimeError::WasmtimeError)
+}
+
+fn load_contract(store: &mut Store<Frame>, bytes: &[u8]) -> Result<Instance> {
+    let mut linker = Linker::new(store.engine());
+    host::add_to_linker(&mut linker)?;
+
+    let component = component(store.engine(), bytes)?;
+    let instance = linker.instantiate(&mut *store, &component)?;
+
+    Ok(instance)
+}
+
+/// Compile a contract's code, or reuse the component compiled for the same
+/// code, so a contract called many times is only compiled once
+fn component(engine: &Engine, bytes: &[u8]) -> Result<Component> {
+    let code_hash = hash(bytes);
 
-    let engine = Engine::new(&config)?;
-    let mut store = Store::new(&engine, 0);
-    let linker = Linker::new(&engine);
+    if let Some(component) = components().get(&code_hash) {
+        return Ok(component.clone());
+    }
 
     let component_bytes = ComponentEncoder::default()
         .module(bytes)?
         .validate(true)
         .encode()?;
-    let component = Component::from_binary(&engine, &component_bytes)?;
-    let instance = linker.instantiate(&mut store, &component)?;
+    let component = Component::from_binary(engine, &component_bytes)?;
+    components().put(code_hash, component.clone());
+
+    Ok(component)
+}
 
-    Ok((store, instance))
+fn components() -> std::sync::MutexGuard<'static, LruCache<[u8; 32], Component>> {
+    COMPONENTS.lock().unwrap_or_else(|e| e.into_inner())
 }
 
 // TODO(ddimaria): remove unwrap
@@ -82,7 +293,14 @@ mod tests {
     #[test]
     fn it_loads_a_contract() {
         let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm");
-        let _loaded = load_contract(bytes).unwrap();
+        let frame = Frame::new(
+            Arc::new(host::EmptyState),
+            ExecutionContext::default(),
+            StorageChanges::new(),
+        );
+        let mut store = Store::new(&shared_engine().unwrap(), frame);
+        store.add_fuel(UNMETERED_GAS).unwrap();
+        let _loaded = load_contract(&mut store, bytes).unwrap();
     }
 
     #[test]
@@ -94,6 +312,164 @@ mod tests {
         call_function(bytes, "mint", &params_2(&address)).unwrap();
     }
 
+    const CALLER: &str = "0xa";
+    const STORAGE: &str = "0xb";
+    const GAS: u64 = 10_000_000;
+
+    /// The caller and storage contracts, without any storage
+    struct Contracts;
+
+    impl State for Contracts {
+        fn code(&self, address: &str) -> Option<Vec<u8>> {
+            match address {
+                CALLER => Some(
+                    include_bytes!("./../../target/wasm32-unknown-unknown/release/caller_wit.wasm")
+                        .to_vec(),
+                ),
+                STORAGE => Some(
+                    include_bytes!(
+                        "./../../target/wasm32-unknown-unknown/release/storage_wit.wasm"
+                    )
+                    .to_vec(),
+                ),
+                _ => None,
+            }
+        }
+
+        fn storage(&self, _address: &str, _key: &str) -> Option<String> {
+            None
+        }
+    }
+
+    fn key(address: &str, key: &str) -> (String, String) {
+        (address.into(), key.into())
+    }
+
+    fn context(callee: &str, function: &str, params: &[&str], gas: u64) -> ExecutionContext {
+        ExecutionContext {
+            callee: callee.into(),
+            input: Input::new(function, params),
+            gas,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_calls_another_contract() {
+        let params = [
+            "String", STORAGE, "String", "set", "String", "key", "String", "value",
+        ];
+        let execution = execute(
+            Arc::new(Contracts),
+            context(CALLER, "forward", &params, GAS),
+        );
+        let changes = execution.changes.unwrap();
+
+        assert_eq!(changes.get(&key(STORAGE, "key")).unwrap(), "value");
+        assert_eq!(changes.get(&key(CALLER, "called")).unwrap(), "true");
+
+        // the caller is charged for the gas the storage contract used
+        let direct = execute(
+            Arc::new(Contracts),
+            context(STORAGE, "set", &["String", "key", "String", "value"], GAS),
+        );
+        assert!(execution.gas_used > direct.gas_used);
+    }
+
+    #[test]
+    fn it_reverts_the_writes_of_a_failed_call() {
+        let params = [
+            "String",
+            STORAGE,
+            "String",
+            "set-and-fail",
+            "String",
+            "key",
+            "String",
+            "value",
+        ];
+        let execution = execute(
+            Arc::new(Contracts),
+            context(CALLER, "forward", &params, GAS),
+        );
+        let changes = execution.changes.unwrap();
+
+        assert_eq!(changes.get(&key(STORAGE, "key")), None);
+        assert_eq!(changes.get(&key(CALLER, "called")).unwrap(), "false");
+    }
+
+    #[test]
+    fn it_defers_a_self_destruct() {
+        let execution = execute(
+            Arc::new(Contracts),
+            context(STORAGE, "destruct", &["String", CALLER], GAS),
+        );
+
+        assert!(execution.changes.is_ok());
+        assert_eq!(execution.self_destructs.get(STORAGE).unwrap(), CALLER);
+    }
+
+    #[test]
+    fn it_reads_the_block_timestamp() {
+        let context = ExecutionContext {
+            timestamp: 1_700_000_000,
+            ..context(STORAGE, "store-timestamp", &[], GAS)
+        };
+        let execution = execute(Arc::new(Contracts), context);
+        let changes = execution.changes.unwrap();
+
+        assert_eq!(
+            changes.get(&key(STORAGE, "timestamp")).unwrap(),
+            "1700000000"
+        );
+    }
+
+    #[test]
+    fn it_runs_more_executions_than_execution_threads() {
+        let executions = (0..EXECUTION_THREADS * 4)
+            .map(|_| {
+                thread::spawn(|| {
+                    execute(
+                        Arc::new(Contracts),
+                        context(STORAGE, "store-timestamp", &[], GAS),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for execution in executions {
+            assert!(execution.join().unwrap().changes.is_ok());
+        }
+    }
+
+    #[test]
+    fn it_fails_when_out_of_gas() {
+        let params = [
+            "String", STORAGE, "String", "set", "String", "key", "String", "value",
+        ];
+        let execution = execute(Arc::new(Contracts), context(CALLER, "forward", &params, 10));
+
+        assert!(execution.changes.is_err());
+        assert!(execution.gas_used <= 10);
+    }
+
+    #[test]
+    fn it_stops_a_call_past_the_maximum_call_depth() {
+        let params = ["String", CALLER, "U64", "0"];
+        let execution = execute(
+            Arc::new(Contracts),
+            context(CALLER, "recurse", &params, UNMETERED_GAS),
+        );
+        let changes = execution.changes.unwrap();
+
+        // the deepest call can't call any deeper, but the calls above it succeed
+        assert_eq!(
+            changes.get(&key(CALLER, "depth")).unwrap(),
+            &(MAX_CALL_DEPTH - 1).to_string()
+        );
+        assert_eq!(changes.get(&key(CALLER, "called")).unwrap(), "true");
+    }
+
     #[test]
     fn it_parses_string_params() {
         let parsed = parse_params(&[PARAMS_1[0], PARAMS_1[1]]).unwrap();
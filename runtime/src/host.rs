@@ -0,0 +1,216 @@
+//! # Host
+//!
+//! Functions the runtime exposes to contracts: reading and writing the
+//! contract's storage, reading the block, calling other contracts, and
+//! self-destructing.
+//! Every call runs in its own frame. Storage writes and self-destructs are
+//! buffered in the frame and only handed back to the caller's frame when the
+//! call succeeds, so a failed call leaves no trace. Self-destructs are left
+//! for the chain to apply once the transaction has finished.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use wasmtime::component::Linker;
+use wasmtime::StoreContextMut;
+
+use crate::context::{ExecutionContext, Input};
+use crate::contract::run;
+use crate::error::Result;
+
+/// The deepest a chain of calls can go, including the entry-point call
+pub const MAX_CALL_DEPTH: usize = 1024;
+
+/// Gas charged to the caller for setting up a call, before any is forwarded
+pub const CALL_GAS: u64 = 700;
+
+/// Storage writes, keyed by contract address and storage key
+pub type StorageChanges = BTreeMap<(String, String), String>;
+
+/// The beneficiary of each contract that self-destructed, keyed by contract address
+pub type SelfDestructs = BTreeMap<String, String>;
+
+/// The chain state that contracts can see
+pub trait State: Send + Sync {
+    /// The code of the contract at an address
+    fn code(&self, address: &str) -> Option<Vec<u8>>;
+
+    /// A value in the storage of the contract at an address
+    fn storage(&self, address: &str, key: &str) -> Option<String>;
+}
+
+/// State without any contracts, for running code in isolation
+#[derive(Debug, Default)]
+pub struct EmptyState;
+
+impl State for EmptyState {
+    fn code(&self, _address: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn storage(&self, _address: &str, _key: &str) -> Option<String> {
+        None
+    }
+}
+
+/// The data of a single call
+pub struct Frame {
+    state: Arc<dyn State>,
+    pub(crate) context: ExecutionContext,
+    /// The number of calls below this one, starting at 0 for the entry point
+    depth: usize,
+    /// Writes made by this call and the calls it made, on top of the caller's writes
+    pub(crate) changes: StorageChanges,
+    /// Self-destructs made by this call and the calls it made, on top of the caller's
+    pub(crate) self_destructs: SelfDestructs,
+}
+
+impl Frame {
+    pub(crate) fn new(
+        state: Arc<dyn State>,
+        context: ExecutionContext,
+        changes: StorageChanges,
+    ) -> Self {
+        Self {
+            state,
+            context,
+            depth: 0,
+            changes,
+            self_destructs: SelfDestructs::new(),
+        }
+    }
+
+    fn storage(&self, key: &str) -> Option<String> {
+        let address = &self.context.callee;
+
+        self.changes
+            .get(&(address.clone(), key.into()))
+            .cloned()
+            .or_else(|| self.state.storage(address, key))
+    }
+
+    /// A frame for a call into another contract, seeing this frame's writes
+    fn child(&self, context: ExecutionContext) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+            context,
+            depth: self.depth + 1,
+            changes: self.changes.clone(),
+            self_destructs: self.self_destructs.clone(),
+        }
+    }
+}
+
+/// Gas forwarded to a call: all but one 64th of what's left (EIP-150)
+pub fn forwarded_gas(remaining: u64) -> u64 {
+    remaining - remaining / 64
+}
+
+pub(crate) fn add_to_linker(linker: &mut Linker<Frame>) -> Result<()> {
+    let mut root = linker.root();
+
+    root.func_wrap(
+        "storage-get",
+        |store: StoreContextMut<'_, Frame>, (key,): (String,)| Ok((store.data().storage(&key),)),
+    )?;
+    root.func_wrap(
+        "storage-set",
+        |mut store: StoreContextMut<'_, Frame>, (key, value): (String, String)| {
+            let frame = store.data_mut();
+            frame
+                .changes
+                .insert((frame.context.callee.clone(), key), value);
+
+            Ok(())
+        },
+    )?;
+    root.func_wrap(
+        "self-destruct",
+        |mut store: StoreContextMut<'_, Frame>, (beneficiary,): (String,)| {
+            let frame = store.data_mut();
+            frame
+                .self_destructs
+                .insert(frame.context.callee.clone(), beneficiary);
+
+            Ok(())
+        },
+    )?;
+    root.func_wrap(
+        "block-timestamp",
+        |store: StoreContextMut<'_, Frame>, _: ()| Ok((store.data().context.timestamp,)),
+    )?;
+    root.func_wrap("call", call)?;
+
+    Ok(())
+}
+
+/// Call a function of another contract, returning whether it succeeded.
+/// The call itself and the callee's gas are charged to the caller, and the
+/// callee's writes are only kept when it succeeds.
+fn call(
+    mut store: StoreContextMut<'_, Frame>,
+    (address, function, params): (String, String, Vec<String>),
+) -> anyhow::Result<(bool,)> {
+    let frame = store.data();
+
+    if frame.depth + 1 >= MAX_CALL_DEPTH {
+        tracing::info!("call to {} exceeds the maximum call depth", address);
+        return Ok((false,));
+    }
+
+    let Some(code) = frame.state.code(&address) else {
+        tracing::info!("call to {}, which is not a contract", address);
+        return Ok((false,));
+    };
+
+    let gas = forwarded_gas(store.consume_fuel(CALL_GAS)?);
+    let frame = store.data();
+    let child = frame.child(frame.context.call(address, Input { function, params }, gas));
+    let engine = store.engine().clone();
+    let (child, gas_used, result) = run(&engine, &code, child);
+
+    store.consume_fuel(gas_used)?;
+
+    match result {
+        Ok(()) => {
+            let frame = store.data_mut();
+            frame.changes = child.changes;
+            frame.self_destructs = child.self_destructs;
+            Ok((true,))
+        }
+        Err(error) => {
+            tracing::info!("call to {} failed: {}", child.context.callee, error);
+            Ok((false,))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_forwards_all_but_one_64th_of_the_gas() {
+        assert_eq!(forwarded_gas(6_400), 6_300);
+        assert_eq!(forwarded_gas(63), 63);
+        assert_eq!(forwarded_gas(0), 0);
+    }
+
+    #[test]
+    fn child_frames_see_their_callers_writes() {
+        let changes = BTreeMap::from([(("0xa".into(), "key".into()), "value".into())]);
+        let context = ExecutionContext {
+            callee: "0xa".into(),
+            ..Default::default()
+        };
+        let frame = Frame::new(Arc::new(EmptyState), context, changes);
+        let child = frame.child(frame.context.call("0xb".into(), Input::default(), 0));
+
+        assert_eq!(frame.storage("key"), Some("value".into()));
+        assert_eq!(child.depth, 1);
+        assert_eq!(child.storage("key"), None);
+        assert_eq!(child.changes, frame.changes);
+    }
+}
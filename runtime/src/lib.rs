@@ -1,2 +1,4 @@
+pub mod context;
 pub mod contract;
 pub mod error;
+pub mod host;
@@ -9,7 +9,9 @@
 
 ////////////////////////////////////////////////////////////////////////////////
 
-use ethereum_types::{Address, U256};
+use std::collections::BTreeMap;
+
+use ethereum_types::{Address, H256, U256};
 use serde::{Deserialize, Serialize};
 
 use crate::bytes::Bytes;
@@ -36,3 +38,20 @@ impl AccountData {
         self.code_hash.is_some()
     }
 }
+
+/// A storage slot of a contract, as returned by `debug_storageRangeAt`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct StorageEntry {
+    /// The slot, if the node knows the preimage of its hash
+    pub key: Option<H256>,
+    pub value: H256,
+}
+
+/// A page of a contract's storage, keyed by the hash of each slot
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRange {
+    pub storage: BTreeMap<H256, StorageEntry>,
+    /// Where the next page starts, or `None` once the storage is exhausted
+    pub next_key: Option<H256>,
+}
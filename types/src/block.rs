@@ -19,15 +19,23 @@
 
 ////////////////////////////////////////////////////////////////////////////////
 
-use ethereum_types::{H256, U64};
-use serde::{Deserialize, Serialize};
+use ethereum_types::{Address, H256, U256, U64};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::convert::TryFrom;
+use std::fmt;
 use std::ops::Deref;
 use utils::crypto::hash;
 
+use crate::bytes::Bytes;
 use crate::error::{Result, TypeError};
-use crate::helpers::hex_to_u64;
-use crate::transaction::Transaction;
+use crate::helpers::{hex_to_u64, to_hex};
+use crate::transaction::{Transaction, TransactionReceipt};
+
+pub const DEFAULT_GAS_LIMIT: u64 = 30_000_000;
+pub const MIN_GAS_LIMIT: u64 = 5_000;
+
+/// The gas limit can move by less than 1/1024th of the parent's limit per block
+pub const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename = "block_number")]
@@ -62,17 +70,281 @@ impl TryFrom<&str> for BlockNumber {
     }
 }
 
+/// A block referenced by number or by tag.
+///
+///   * Earliest: the genesis block.
+///   * Latest: the most recent block in the chain.
+///   * Pending: the latest block with the mempool's transactions applied.
+///   * Number: a specific block.
+///
+/// Serializes to the string form the JSON-RPC API expects ("latest", "0x1", ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockTag {
+    Earliest,
+    #[default]
+    Latest,
+    Pending,
+    Number(U64),
+}
+
+impl BlockTag {
+    /// The number of the block a tag names when it's fixed, rather than
+    /// moving as blocks are added
+    pub fn number(&self) -> Option<U64> {
+        match self {
+            BlockTag::Earliest => Some(U64::zero()),
+            BlockTag::Number(number) => Some(*number),
+            BlockTag::Latest | BlockTag::Pending => None,
+        }
+    }
+}
+
+impl fmt::Display for BlockTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockTag::Earliest => write!(f, "earliest"),
+            BlockTag::Latest => write!(f, "latest"),
+            BlockTag::Pending => write!(f, "pending"),
+            BlockTag::Number(number) => write!(f, "{}", to_hex(number)),
+        }
+    }
+}
+
+impl From<BlockNumber> for BlockTag {
+    fn from(value: BlockNumber) -> BlockTag {
+        BlockTag::Number(*value)
+    }
+}
+
+/// `None` refers to the latest block
+impl From<Option<BlockNumber>> for BlockTag {
+    fn from(value: Option<BlockNumber>) -> BlockTag {
+        value.map_or(BlockTag::Latest, BlockTag::from)
+    }
+}
+
+impl TryFrom<&str> for BlockTag {
+    type Error = TypeError;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "earliest" => Ok(BlockTag::Earliest),
+            "latest" => Ok(BlockTag::Latest),
+            "pending" => Ok(BlockTag::Pending),
+            _ => Ok(BlockNumber::try_from(value)?.into()),
+        }
+    }
+}
+
+impl Serialize for BlockTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        BlockTag::try_from(value.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A block referenced by number/tag or by hash.
+///
+/// Tags serialize to their string form, while hashes serialize to the
+/// `{"blockHash": ...}` object described in EIP-1898.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockId {
+    Number(BlockTag),
+    Hash(H256),
+}
+
+impl Default for BlockId {
+    fn default() -> Self {
+        BlockId::Number(BlockTag::Latest)
+    }
+}
+
+impl From<BlockTag> for BlockId {
+    fn from(value: BlockTag) -> BlockId {
+        BlockId::Number(value)
+    }
+}
+
+impl From<BlockNumber> for BlockId {
+    fn from(value: BlockNumber) -> BlockId {
+        BlockId::Number(value.into())
+    }
+}
+
+/// `None` refers to the latest block
+impl From<Option<BlockNumber>> for BlockId {
+    fn from(value: Option<BlockNumber>) -> BlockId {
+        BlockId::Number(value.into())
+    }
+}
+
+impl From<H256> for BlockId {
+    fn from(value: H256) -> BlockId {
+        BlockId::Hash(value)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum BlockIdRepr {
+    Tag(BlockTag),
+    Hash {
+        #[serde(rename = "blockHash")]
+        block_hash: H256,
+    },
+}
+
+impl Serialize for BlockId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match *self {
+            BlockId::Number(tag) => BlockIdRepr::Tag(tag),
+            BlockId::Hash(block_hash) => BlockIdRepr::Hash { block_hash },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        Ok(match BlockIdRepr::deserialize(deserializer)? {
+            BlockIdRepr::Tag(tag) => BlockId::Number(tag),
+            BlockIdRepr::Hash { block_hash } => BlockId::Hash(block_hash),
+        })
+    }
+}
+
+/// A validator withdrawal from the beacon chain (EIP-4895)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct Withdrawal {
+    pub index: U64,
+    pub validator_index: U64,
+    pub address: Address,
+    /// The amount in Gwei
+    pub amount: U64,
+}
+
+/// Fees paid over a range of blocks, oldest first, as returned by `eth_feeHistory`.
+/// `base_fee_per_gas` includes the block after the newest in the range.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    pub oldest_block: U64,
+    pub base_fee_per_gas: Vec<U256>,
+    pub gas_used_ratio: Vec<f64>,
+    /// The tip at each requested percentile of gas used, per block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward: Option<Vec<Vec<U256>>>,
+}
+
 // TODO(ddimaria): add in `author` once we're injecting keys into nodes
+//
+// Other nodes use camelCase, so fields accept both spellings. The post-merge
+// fields are only present on mainnet blocks, and are None for local blocks.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all(serialize = "snake_case", deserialize = "snake_case"))]
 pub struct Block {
     pub number: U64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub hash: Option<H256>,
+    #[serde(alias = "parentHash")]
     pub parent_hash: H256,
+    #[serde(default)]
     pub transactions: Vec<Transaction>,
+    #[serde(alias = "transactionsRoot")]
     pub transactions_root: H256,
+    #[serde(default, alias = "receiptsRoot")]
+    pub receipts_root: H256,
+    #[serde(alias = "stateRoot")]
     pub state_root: H256,
+    #[serde(default, alias = "gasLimit")]
+    pub gas_limit: U256,
+    /// Seconds since the Unix epoch
+    #[serde(default)]
+    pub timestamp: U64,
+    /// The beacon chain's randomness, which replaced `mixHash` at the merge
+    #[serde(default, alias = "prevRandao", alias = "mixHash")]
+    pub prev_randao: Option<H256>,
+    #[serde(default)]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    #[serde(default, alias = "withdrawalsRoot")]
+    pub withdrawals_root: Option<H256>,
+    #[serde(default, alias = "blobGasUsed")]
+    pub blob_gas_used: Option<U64>,
+    #[serde(default, alias = "excessBlobGas")]
+    pub excess_blob_gas: Option<U64>,
+    #[serde(default, alias = "parentBeaconBlockRoot")]
+    pub parent_beacon_block_root: Option<H256>,
+    /// Proof that the block was produced under the chain's consensus rules
+    /// (e.g. an authority's signature), added after the block is hashed
+    #[serde(default)]
+    pub seal: Option<Bytes>,
+}
+
+/// A block without its full transactions, as returned by `eth_getBlockByNumber`
+/// when full transactions aren't requested. Transactions are listed by hash.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "snake_case", deserialize = "snake_case"))]
+pub struct BlockHeader {
+    pub number: U64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<H256>,
+    #[serde(alias = "parentHash")]
+    pub parent_hash: H256,
+    #[serde(default)]
+    pub transactions: Vec<H256>,
+    #[serde(alias = "transactionsRoot")]
+    pub transactions_root: H256,
+    #[serde(default, alias = "receiptsRoot")]
+    pub receipts_root: H256,
+    #[serde(alias = "stateRoot")]
+    pub state_root: H256,
+    #[serde(default, alias = "gasLimit")]
+    pub gas_limit: U256,
+    #[serde(default)]
+    pub timestamp: U64,
+    #[serde(default, alias = "prevRandao", alias = "mixHash")]
+    pub prev_randao: Option<H256>,
+    #[serde(default, alias = "withdrawalsRoot")]
+    pub withdrawals_root: Option<H256>,
+    #[serde(default, alias = "blobGasUsed")]
+    pub blob_gas_used: Option<U64>,
+    #[serde(default, alias = "excessBlobGas")]
+    pub excess_blob_gas: Option<U64>,
+    #[serde(default, alias = "parentBeaconBlockRoot")]
+    pub parent_beacon_block_root: Option<H256>,
+}
+
+impl From<&Block> for BlockHeader {
+    fn from(block: &Block) -> Self {
+        BlockHeader {
+            number: block.number,
+            hash: block.hash,
+            parent_hash: block.parent_hash,
+            // transactions are hashed once they're in a block
+            transactions: block
+                .transactions
+                .iter()
+                .filter_map(|transaction| transaction.hash)
+                .collect(),
+            transactions_root: block.transactions_root,
+            receipts_root: block.receipts_root,
+            state_root: block.state_root,
+            gas_limit: block.gas_limit,
+            timestamp: block.timestamp,
+            prev_randao: block.prev_randao,
+            withdrawals_root: block.withdrawals_root,
+            blob_gas_used: block.blob_gas_used,
+            excess_blob_gas: block.excess_blob_gas,
+            parent_beacon_block_root: block.parent_beacon_block_root,
+        }
+    }
 }
 
 impl Block {
@@ -80,21 +352,33 @@ impl Block {
         number: U64,
         parent_hash: H256,
         transactions: Vec<Transaction>,
+        receipts: &[TransactionReceipt],
         state_root: H256,
+        gas_limit: U256,
+        timestamp: U64,
     ) -> Result<Block> {
         let transactions_root = Transaction::root_hash(&transactions)?;
+        let receipts_root = TransactionReceipt::root_hash(receipts)?;
         let mut block = Block {
             number,
             hash: None,
             parent_hash,
             transactions,
             transactions_root,
+            receipts_root,
             state_root,
+            gas_limit,
+            timestamp,
+            prev_randao: None,
+            withdrawals: None,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            seal: None,
         };
 
-        let serialized = bincode::serialize(&block)?;
-        let hash: H256 = hash(&serialized).into();
-        block.hash = Some(hash);
+        block.hash = Some(block.compute_hash()?);
 
         Ok(block)
     }
@@ -103,8 +387,286 @@ impl Block {
         self.hash.ok_or(TypeError::MissingBlockHash)
     }
 
+    /// The hash of the block's contents, leaving out the hash itself and the
+    /// seal (which signs the hash)
+    pub fn compute_hash(&self) -> Result<H256> {
+        let block = Block {
+            hash: None,
+            seal: None,
+            ..self.clone()
+        };
+        let serialized = bincode::serialize(&block)?;
+
+        Ok(hash(&serialized).into())
+    }
+
     // TODO(ddimaria): add initial accounts and seed with coin (requires recalculation of the state_root)
     pub fn genesis() -> Result<Self> {
-        Self::new(U64::zero(), H256::zero(), vec![], H256::zero())
+        Self::new(
+            U64::zero(),
+            H256::zero(),
+            vec![],
+            &[],
+            H256::zero(),
+            U256::from(DEFAULT_GAS_LIMIT),
+            U64::zero(),
+        )
+    }
+
+    /// The gas limit of the next block, moving from this block's limit toward
+    /// the target by the largest step allowed (mirrors geth's `CalcGasLimit`).
+    pub fn next_gas_limit(&self, target: U256) -> U256 {
+        let parent = self.gas_limit;
+        let target = target.max(U256::from(MIN_GAS_LIMIT));
+        let delta = (parent / GAS_LIMIT_BOUND_DIVISOR).saturating_sub(U256::one());
+
+        if parent < target {
+            (parent + delta).min(target)
+        } else {
+            parent.saturating_sub(delta).max(target)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_serializes_block_tags() {
+        assert_eq!(BlockTag::Earliest.to_string(), "earliest");
+        assert_eq!(BlockTag::Latest.to_string(), "latest");
+        assert_eq!(BlockTag::Pending.to_string(), "pending");
+        assert_eq!(BlockTag::Number(U64::from(16)).to_string(), "0x10");
+        assert_eq!(BlockTag::from(None), BlockTag::Latest);
+    }
+
+    #[test]
+    fn it_gets_the_number_of_fixed_block_tags() {
+        assert_eq!(BlockTag::Earliest.number(), Some(U64::zero()));
+        assert_eq!(
+            BlockTag::Number(U64::from(16)).number(),
+            Some(U64::from(16))
+        );
+        assert_eq!(BlockTag::Latest.number(), None);
+        assert_eq!(BlockTag::Pending.number(), None);
+    }
+
+    #[test]
+    fn it_deserializes_block_tags() {
+        let tag: BlockTag = serde_json::from_str(r#""pending""#).unwrap();
+        assert_eq!(tag, BlockTag::Pending);
+
+        let tag: BlockTag = serde_json::from_str(r#""latest""#).unwrap();
+        assert_eq!(tag, BlockTag::Latest);
+
+        let tag: BlockTag = serde_json::from_str(r#""earliest""#).unwrap();
+        assert_eq!(tag, BlockTag::Earliest);
+    }
+
+    #[test]
+    fn it_bounds_the_gas_limit_change() {
+        let mut block = Block::genesis().unwrap();
+        let max_delta = block.gas_limit / GAS_LIMIT_BOUND_DIVISOR;
+
+        // moves up by less than 1/1024th
+        let raised = block.next_gas_limit(U256::from(DEFAULT_GAS_LIMIT * 2));
+        assert!(raised > block.gas_limit && raised - block.gas_limit < max_delta);
+
+        // moves down by less than 1/1024th
+        let lowered = block.next_gas_limit(U256::zero());
+        assert!(lowered < block.gas_limit && block.gas_limit - lowered < max_delta);
+
+        // doesn't overshoot a nearby target
+        let target = block.gas_limit + 1;
+        assert_eq!(block.next_gas_limit(target), target);
+
+        // never goes below the minimum
+        block.gas_limit = U256::from(MIN_GAS_LIMIT);
+        assert_eq!(
+            block.next_gas_limit(U256::zero()),
+            U256::from(MIN_GAS_LIMIT)
+        );
+    }
+
+    #[test]
+    fn it_deserializes_a_header_with_transaction_hashes() {
+        let header = serde_json::json!({
+            "number": "0x1",
+            "hash": H256::from_low_u64_be(1),
+            "parentHash": H256::zero(),
+            "transactions": [H256::from_low_u64_be(2), H256::from_low_u64_be(3)],
+            "transactionsRoot": H256::zero(),
+            "stateRoot": H256::zero(),
+        });
+        let header: BlockHeader = serde_json::from_value(header).unwrap();
+
+        assert_eq!(
+            header.transactions,
+            vec![H256::from_low_u64_be(2), H256::from_low_u64_be(3)]
+        );
+    }
+
+    #[test]
+    fn it_deserializes_a_mainnet_block_header() {
+        // the mainnet genesis block, as returned by `eth_getBlockByNumber`
+        let header = serde_json::json!({
+            "difficulty": "0x400000000",
+            "extraData": "0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa",
+            "gasLimit": "0x1388",
+            "gasUsed": "0x0",
+            "hash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+            "logsBloom": format!("0x{}", "0".repeat(512)),
+            "miner": "0x0000000000000000000000000000000000000000",
+            "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "nonce": "0x0000000000000042",
+            "number": "0x0",
+            "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "size": "0x21c",
+            "stateRoot": "0xd7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544",
+            "timestamp": "0x0",
+            "totalDifficulty": "0x400000000",
+            "transactions": [],
+            "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "uncles": []
+        });
+        let h256 = |hash: &str| H256::from_slice(&hex::decode(hash).unwrap());
+
+        // the hash sent is the keccak of the RLP-encoded header, fields in consensus order
+        let mut stream = utils::RlpStream::new_list(15);
+        for (field, is_quantity) in [
+            ("parentHash", false),
+            ("sha3Uncles", false),
+            ("miner", false),
+            ("stateRoot", false),
+            ("transactionsRoot", false),
+            ("receiptsRoot", false),
+            ("logsBloom", false),
+            ("difficulty", true),
+            ("number", true),
+            ("gasLimit", true),
+            ("gasUsed", true),
+            ("timestamp", true),
+            ("extraData", false),
+            ("mixHash", false),
+            ("nonce", false),
+        ] {
+            let value = header[field].as_str().unwrap().trim_start_matches("0x");
+            let bytes = match is_quantity {
+                true => {
+                    let quantity = u128::from_str_radix(value, 16).unwrap().to_be_bytes();
+                    let leading_zeros = quantity.iter().take_while(|byte| **byte == 0).count();
+                    quantity[leading_zeros..].to_vec()
+                }
+                false => hex::decode(value).unwrap(),
+            };
+            stream.append(&bytes);
+        }
+        let hash = H256::from(utils::crypto::hash(&stream.out()));
+        let block: Block = serde_json::from_value(header).unwrap();
+
+        assert_eq!(Some(hash), block.hash);
+        assert_eq!(
+            hash,
+            h256("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+        );
+        assert_eq!(block.number, U64::zero());
+        assert_eq!(block.parent_hash, H256::zero());
+        assert_eq!(
+            block.state_root,
+            h256("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544")
+        );
+        assert_eq!(block.gas_limit, U256::from(5_000));
+        assert_eq!(block.prev_randao, Some(H256::zero()));
+        assert_eq!(block.withdrawals, None);
+        assert_eq!(block.blob_gas_used, None);
+    }
+
+    #[test]
+    fn it_deserializes_the_post_merge_fields_of_a_block_header() {
+        // illustrative values in the shape of a post-Cancun header
+        let header = serde_json::json!({
+            "baseFeePerGas": "0x3b9aca00",
+            "blobGasUsed": "0x20000",
+            "difficulty": "0x0",
+            "excessBlobGas": "0x0",
+            "extraData": "0x6265617665726275696c642e6f7267",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0xb3ff8f",
+            "hash": "0x5c3bcb2bbc6d1bb9a7a24a59e8d8b0b77b6b4c8a9e4d4d0f3b1e5b1c2d3e4f50",
+            "logsBloom": format!("0x{}", "0".repeat(512)),
+            "miner": "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+            "mixHash": "0x1a8a2f2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7",
+            "nonce": "0x0000000000000000",
+            "number": "0x12a05f2",
+            "parentBeaconBlockRoot": "0x2b7cbea4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f80",
+            "parentHash": "0x3c8d9eafb0c1d2e3f405162738495a6b7c8d9eafb0c1d2e3f405162738495a6b",
+            "receiptsRoot": "0x4d9eafb0c1d2e3f405162738495a6b7c8d9eafb0c1d2e3f405162738495a6b7c",
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "size": "0x1b5e2",
+            "stateRoot": "0x5eafb0c1d2e3f405162738495a6b7c8d9eafb0c1d2e3f405162738495a6b7c8d",
+            "timestamp": "0x661f2a5b",
+            "totalDifficulty": "0xc70d815d562d3cfa955",
+            "transactionsRoot": "0x6fb0c1d2e3f405162738495a6b7c8d9eafb0c1d2e3f405162738495a6b7c8d9e",
+            "uncles": [],
+            "withdrawals": [
+                {
+                    "index": "0x2a4e8f1",
+                    "validatorIndex": "0x10f3a2",
+                    "address": "0xb9d7934878b5fb9610b3fe8a5e441e8fad7e293f",
+                    "amount": "0x11c37937"
+                }
+            ],
+            "withdrawalsRoot": "0x70c1d2e3f405162738495a6b7c8d9eafb0c1d2e3f405162738495a6b7c8d9eaf"
+        });
+        let block: Block = serde_json::from_value(header).unwrap();
+
+        assert_eq!(block.number, U64::from(19_531_250));
+        assert_eq!(block.gas_limit, U256::from(30_000_000));
+        assert!(block.transactions.is_empty());
+        assert!(block.prev_randao.is_some());
+        assert!(block.withdrawals_root.is_some());
+        assert!(block.parent_beacon_block_root.is_some());
+        assert_eq!(block.blob_gas_used, Some(U64::from(0x20000)));
+        assert_eq!(block.excess_blob_gas, Some(U64::zero()));
+
+        let withdrawals = block.withdrawals.unwrap();
+        assert_eq!(withdrawals[0].validator_index, U64::from(0x10f3a2));
+        assert_eq!(withdrawals[0].amount, U64::from(0x11c37937));
+    }
+
+    #[test]
+    fn local_blocks_have_no_post_merge_fields() {
+        let block = Block::genesis().unwrap();
+        let serialized = serde_json::to_value(&block).unwrap();
+        let deserialized: Block = serde_json::from_value(serialized).unwrap();
+
+        assert_eq!(deserialized.hash, block.hash);
+        assert_eq!(deserialized.prev_randao, None);
+        assert_eq!(deserialized.withdrawals, None);
+    }
+
+    #[test]
+    fn it_serializes_block_ids() {
+        let tag = serde_json::to_value(BlockId::from(BlockTag::Pending)).unwrap();
+        assert_eq!(tag, serde_json::json!("pending"));
+
+        let hash = serde_json::to_value(BlockId::from(H256::repeat_byte(1))).unwrap();
+        assert_eq!(
+            hash,
+            serde_json::json!({ "blockHash": format!("{:?}", H256::repeat_byte(1)) })
+        );
+    }
+
+    #[test]
+    fn it_deserializes_block_ids() {
+        let id: BlockId = serde_json::from_str(r#""0x10""#).unwrap();
+        assert_eq!(id, BlockId::Number(BlockTag::Number(U64::from(16))));
+
+        let json = format!(r#"{{"blockHash": "{:?}"}}"#, H256::repeat_byte(1));
+        let id: BlockId = serde_json::from_str(&json).unwrap();
+        assert_eq!(id, BlockId::Hash(H256::repeat_byte(1)));
     }
 }
@@ -8,6 +8,7 @@
 
 use thiserror::Error;
 use utils::error::UtilsError;
+use utils::DecoderError;
 
 #[derive(Error, Debug)]
 pub enum TypeError {
@@ -17,15 +18,30 @@ pub enum TypeError {
     #[error("Error converting a hex to U64: {0}")]
     HexToU64Error(String),
 
+    #[error("Invalid log filter: {0}")]
+    InvalidLogFilter(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
     #[error("Invalid transaction: {0}")]
     InvalidTransaction(String),
 
+    #[error("Invalid typed data: {0}")]
+    InvalidTypedData(String),
+
     #[error("Missing block hash")]
     MissingBlockHash,
 
     #[error("Missing transaction hash")]
     MissingTransactionHash,
 
+    #[error("Sender mismatch: {0}")]
+    SenderMismatch(String),
+
+    #[error("Signing failed: {0}")]
+    SigningFailed(String),
+
     #[error("{0}")]
     TrieError(String),
 
@@ -35,12 +51,31 @@ pub enum TypeError {
 
 pub type Result<T> = std::result::Result<T, TypeError>;
 
+// JSON-RPC error codes shared by the chain (server) and web3 (client).
+// Codes from -32000 to -32099 are reserved for implementation-defined server errors.
+// Reverts use 3, following geth.
+pub const EXECUTION_REVERTED_CODE: i32 = 3;
+pub const SERVER_ERROR_CODE: i32 = -32000;
+pub const NONCE_TOO_LOW_CODE: i32 = -32001;
+pub const NONCE_TOO_HIGH_CODE: i32 = -32002;
+pub const GAS_PRICE_TOO_LOW_CODE: i32 = -32003;
+pub const INSUFFICIENT_FUNDS_CODE: i32 = -32004;
+pub const TRANSACTION_TOO_LARGE_CODE: i32 = -32005;
+pub const INVALID_TRANSACTION_CODE: i32 = -32006;
+pub const REPLACEMENT_UNDERPRICED_CODE: i32 = -32007;
+
 impl From<Box<bincode::ErrorKind>> for TypeError {
     fn from(error: Box<bincode::ErrorKind>) -> Self {
         TypeError::EncodingDecodingError(error.to_string())
     }
 }
 
+impl From<DecoderError> for TypeError {
+    fn from(error: DecoderError) -> Self {
+        TypeError::EncodingDecodingError(error.to_string())
+    }
+}
+
 impl From<UtilsError> for TypeError {
     fn from(error: UtilsError) -> Self {
         TypeError::UtilError(error.to_string())
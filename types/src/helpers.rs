@@ -6,7 +6,9 @@
 
 use ethereum_types::U64;
 use std::fmt::{Display, LowerHex};
+use utils::crypto::hash;
 
+use crate::account::Account;
 use crate::error::TypeError;
 
 /// Convert a hexadecimal number into a U64, error on conversion failure
@@ -21,3 +23,107 @@ where
 {
     format!("{:#x}", num)
 }
+
+/// Remove a leading `0x` from a hexadecimal string, if there is one
+pub fn strip_0x(hex: &str) -> &str {
+    hex.strip_prefix("0x").unwrap_or(hex)
+}
+
+/// Format an address with an EIP-55 mixed-case checksum
+///
+/// See https://eips.ethereum.org/EIPS/eip-55
+pub fn to_checksum(address: &Account) -> String {
+    let address = hex::encode(address.as_bytes());
+    let address_hash = hex::encode(hash(address.as_bytes()));
+    let checksummed = address
+        .chars()
+        .zip(address_hash.chars())
+        .map(|(character, nibble)| match nibble.to_digit(16) {
+            Some(nibble) if nibble >= 8 => character.to_ascii_uppercase(),
+            _ => character,
+        })
+        .collect::<String>();
+
+    format!("0x{}", checksummed)
+}
+
+/// (De)serialize bytes as a `0x` prefixed hex string in JSON, and as plain
+/// bytes in binary formats
+pub mod hex_bytes {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::strip_0x;
+    use crate::bytes::Bytes;
+
+    pub fn serialize<S: Serializer>(bytes: &Bytes, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return bytes.serialize(serializer);
+        }
+
+        format!("0x{}", hex::encode(bytes)).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Bytes, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Bytes::deserialize(deserializer);
+        }
+
+        let hex = String::deserialize(deserializer)?;
+        let bytes = hex::decode(strip_0x(&hex)).map_err(D::Error::custom)?;
+
+        Ok(bytes.into())
+    }
+}
+
+/// Optional bytes as a 0x-prefixed hex string in JSON, as nodes send calldata
+pub mod hex_bytes_option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::bytes::Bytes;
+
+    pub fn serialize<S: Serializer>(
+        bytes: &Option<Bytes>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(bytes) if serializer.is_human_readable() => {
+                super::hex_bytes::serialize(bytes, serializer)
+            }
+            _ => bytes.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Bytes>, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Option::<Bytes>::deserialize(deserializer);
+        }
+
+        #[derive(Deserialize)]
+        struct Hex(#[serde(with = "super::hex_bytes")] Bytes);
+
+        Ok(Option::<Hex>::deserialize(deserializer)?.map(|Hex(bytes)| bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn it_strips_0x() {
+        assert_eq!(strip_0x("0x1a"), "1a");
+        assert_eq!(strip_0x("1a"), "1a");
+    }
+
+    #[test]
+    fn it_checksums_an_address() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let address = Account::from_str(strip_0x(checksummed)).unwrap();
+
+        assert_eq!(to_checksum(&address), checksummed);
+    }
+}
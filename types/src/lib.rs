@@ -10,3 +10,4 @@ pub mod bytes;
 pub mod error;
 pub mod helpers;
 pub mod transaction;
+pub mod typed_data;
@@ -12,19 +12,56 @@ use std::sync::Arc;
 
 use eth_trie::{EthTrie, MemoryDB, Trie};
 use ethereum_types::{Address, H160, H256, U256, U64};
-use serde::{Deserialize, Serialize};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 use serde_with::skip_serializing_none;
 use utils::crypto::{
-    hash, public_key_address, recover_public_key, sign_recovery, verify, Signature,
+    hash, private_key_address, public_key_address, recover, recover_public_key, sign_hash, verify,
+    Signature,
+};
+use utils::{
+    Decodable, DecoderError, Encodable, PublicKey, RecoverableSignature, RecoveryId, Rlp,
+    RlpStream, SecretKey,
 };
-use utils::{PublicKey, RecoverableSignature, RecoveryId, SecretKey};
 
 use crate::account::Account;
-use crate::block::BlockNumber;
+use crate::block::{BlockNumber, BlockTag};
 use crate::bytes::Bytes;
 use crate::error::{Result, TypeError};
+use crate::helpers::strip_0x;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// Gas charged for every transaction
+pub const TRANSACTION_GAS: u64 = 21_000;
+
+/// Additional gas charged when a transaction creates a contract
+pub const CONTRACT_CREATION_GAS: u64 = 32_000;
+
+/// Gas charged per zero byte of calldata
+pub const DATA_ZERO_BYTE_GAS: u64 = 4;
+
+/// Gas charged per non-zero byte of calldata
+pub const DATA_NON_ZERO_BYTE_GAS: u64 = 16;
+
+/// Prefix of the payload signed by an EIP-7702 authority
+pub const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+/// Half the order of the secp256k1 curve. A signature with `s` above it has a
+/// twin with `n - s` that recovers to the same signer, so only the low one is
+/// canonical (EIP-2).
+const SECP256K1_HALF_ORDER: U256 = U256([
+    0xdfe9_2f46_681b_20a0,
+    0x5d57_6e73_57a4_501d,
+    0xffff_ffff_ffff_ffff,
+    0x7fff_ffff_ffff_ffff,
+]);
+
+/// The largest chain id whose EIP-155 `v` (`chain_id * 2 + 36` at most) fits a u64
+const MAX_LEGACY_CHAIN_ID: u64 = (u64::MAX - 36) / 2;
+
+/// In JSON a transaction also carries its EIP-2718 `type` (see `Typed`).
+/// The derived (de)serializers below are the untyped form, used as is by
+/// binary formats so hashes don't depend on the tag.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(remote = "Self")]
 #[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
 pub struct Transaction {
     pub from: Address,
@@ -33,9 +70,166 @@ pub struct Transaction {
     pub hash: Option<H256>,
     pub nonce: Option<U256>,
     pub value: U256,
+    /// Nodes return calldata as `input`, and take it as `data`, both in hex
+    #[serde(default, alias = "input", with = "crate::helpers::hex_bytes_option")]
     pub data: Option<Bytes>,
     pub gas: U256,
     pub gas_price: U256,
+    #[serde(default)]
+    pub chain_id: Option<U64>,
+    #[serde(default)]
+    pub access_list: Option<AccessList>,
+    #[serde(default)]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    pub authorization_list: Option<Vec<SignedAuthorization>>,
+}
+
+/// Transactions are compared by their contents.
+/// The cached hash is ignored, so a transaction equals itself before and after
+/// its hash is computed (e.g. after a round-trip through serialization).
+impl PartialEq for Transaction {
+    fn eq(&self, other: &Self) -> bool {
+        self.from == other.from
+            && self.to == other.to
+            && self.nonce == other.nonce
+            && self.value == other.value
+            && self.data == other.data
+            && self.gas == other.gas
+            && self.gas_price == other.gas_price
+            && self.chain_id == other.chain_id
+            && self.access_list == other.access_list
+            && self.max_fee_per_gas == other.max_fee_per_gas
+            && self.max_priority_fee_per_gas == other.max_priority_fee_per_gas
+            && self.authorization_list == other.authorization_list
+    }
+}
+
+/// EIP-2718 transaction types.
+///
+///   * Legacy: the original untyped transaction.
+///   * AccessList: EIP-2930 transactions that declare the state they access.
+///   * DynamicFee: EIP-1559 transactions with a max fee and a priority fee.
+///   * SetCode: EIP-7702 transactions that let an account delegate to contract code.
+///
+/// They serialize as their type byte, e.g. `"0x2"`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(into = "U64", try_from = "U64")]
+pub enum TransactionType {
+    Legacy = 0,
+    AccessList = 1,
+    DynamicFee = 2,
+    SetCode = 4,
+}
+
+impl From<TransactionType> for U64 {
+    fn from(value: TransactionType) -> Self {
+        (value as u8).into()
+    }
+}
+
+impl TryFrom<U64> for TransactionType {
+    type Error = TypeError;
+
+    fn try_from(value: U64) -> Result<Self> {
+        match value.as_u64() {
+            0x00 => Ok(TransactionType::Legacy),
+            0x01 => Ok(TransactionType::AccessList),
+            0x02 => Ok(TransactionType::DynamicFee),
+            0x04 => Ok(TransactionType::SetCode),
+            other => Err(TypeError::InvalidTransaction(format!(
+                "unknown transaction type {other:#x}"
+            ))),
+        }
+    }
+}
+
+/// A transaction, or signed transaction, as it appears in JSON: its fields
+/// alongside its `type`.
+/// Without a `type`, the kind of transaction is told by its fields.
+#[derive(Serialize, Deserialize)]
+struct Typed<T> {
+    #[serde(rename = "type", default)]
+    transaction_type: Option<TransactionType>,
+    #[serde(flatten)]
+    transaction: T,
+}
+
+/// A transaction through its derived, untyped, (de)serializers
+struct Untyped<T>(T);
+
+impl Serialize for Untyped<&Transaction> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Transaction::serialize(self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Untyped<Transaction> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        Transaction::deserialize(deserializer).map(Untyped)
+    }
+}
+
+impl Serialize for Transaction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return Transaction::serialize(self, serializer);
+        }
+
+        Typed {
+            transaction_type: Some(self.transaction_type()),
+            transaction: Untyped(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The `type` decides the kind of transaction: an access list or
+/// authorization list it requires defaults to empty, and a max fee to the gas
+/// price.
+/// Fields of another kind of transaction are rejected.
+/// Without a `type`, the transaction is of the kind its fields make it.
+impl<'de> Deserialize<'de> for Transaction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Transaction::deserialize(deserializer);
+        }
+
+        let Typed {
+            transaction_type,
+            transaction: Untyped(mut transaction),
+        } = Typed::<Untyped<Transaction>>::deserialize(deserializer)?;
+        let Some(transaction_type) = transaction_type else {
+            return Ok(transaction);
+        };
+
+        match transaction_type {
+            TransactionType::Legacy => {}
+            TransactionType::AccessList => {
+                transaction.access_list.get_or_insert_with(AccessList::new);
+            }
+            TransactionType::DynamicFee => {
+                transaction
+                    .max_fee_per_gas
+                    .get_or_insert(transaction.gas_price);
+            }
+            TransactionType::SetCode => {
+                transaction.authorization_list.get_or_insert_with(Vec::new);
+            }
+        }
+
+        if transaction.transaction_type() != transaction_type {
+            return Err(de::Error::custom(format!(
+                "a transaction of type {:#x} has the fields of type {:#x}",
+                transaction_type as u8,
+                transaction.transaction_type() as u8
+            )));
+        }
+
+        Ok(transaction)
+    }
 }
 
 /// On Ethereum there are a few different types of transactions:
@@ -65,6 +259,11 @@ impl Transaction {
             data,
             gas: U256::from(10),
             gas_price: U256::from(10),
+            chain_id: None,
+            access_list: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            authorization_list: None,
         };
 
         transaction.hash()?;
@@ -72,6 +271,76 @@ impl Transaction {
         Ok(transaction)
     }
 
+    /// Build a transaction to sign locally from a request and the sender's
+    /// nonce, leaving the hash to be computed when it is signed.
+    ///
+    /// Unlike converting with `try_into`, nothing is filled in for the node to
+    /// decide: the request needs a sender, a gas limit, a fee and a recipient
+    /// (or data, to deploy a contract).
+    ///
+    /// ```rust
+    /// use ethereum_types::U256;
+    /// use types::account::Account;
+    /// use types::transaction::{Transaction, TransactionRequest};
+    ///
+    /// let transaction_request = TransactionRequest {
+    ///     from: Some(Account::random()),
+    ///     to: Some(Account::random()),
+    ///     gas: U256::from(21_000),
+    ///     gas_price: U256::from(1),
+    ///     ..Default::default()
+    /// };
+    /// let transaction = Transaction::from_request(transaction_request, U256::zero()).unwrap();
+    /// assert_eq!(transaction.gas, U256::from(21_000));
+    /// ```
+    pub fn from_request(transaction_request: TransactionRequest, nonce: U256) -> Result<Self> {
+        let missing = |field: &str| TypeError::InvalidTransaction(format!("missing {}", field));
+        let TransactionRequest {
+            from,
+            to,
+            gas,
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            value,
+            data,
+            chain_id,
+            access_list,
+            authorization_list,
+            ..
+        } = transaction_request;
+
+        let from = from.ok_or_else(|| missing("from"))?;
+
+        if gas.is_zero() {
+            return Err(missing("gas"));
+        }
+
+        if gas_price.is_zero() && max_fee_per_gas.is_none() {
+            return Err(missing("gasPrice or maxFeePerGas"));
+        }
+
+        if to.is_none() && data.as_ref().is_none_or(|data| data.is_empty()) {
+            return Err(missing("to, or data to deploy a contract"));
+        }
+
+        Ok(Self {
+            from,
+            to,
+            hash: None,
+            nonce: Some(nonce),
+            value: value.unwrap_or_default(),
+            data,
+            gas,
+            gas_price,
+            chain_id,
+            access_list,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            authorization_list,
+        })
+    }
+
     pub fn hash(&mut self) -> Result<H256> {
         let serialized = bincode::serialize(&self)?;
         let hash: H256 = hash(&serialized).into();
@@ -84,6 +353,80 @@ impl Transaction {
         self.hash.ok_or(TypeError::MissingTransactionHash)
     }
 
+    /// The gas consumed by a transaction before any execution takes place.
+    ///
+    /// Mirrors Ethereum's intrinsic gas: a flat fee, a surcharge for contract
+    /// creation, and a per-byte fee for calldata.
+    pub fn intrinsic_gas(&self) -> U256 {
+        let mut gas = TRANSACTION_GAS;
+
+        if self.to.is_none() {
+            gas += CONTRACT_CREATION_GAS;
+        }
+
+        if let Some(data) = &self.data {
+            gas += data
+                .iter()
+                .map(|byte| match byte {
+                    0 => DATA_ZERO_BYTE_GAS,
+                    _ => DATA_NON_ZERO_BYTE_GAS,
+                })
+                .sum::<u64>();
+        }
+
+        U256::from(gas)
+    }
+
+    /// The most the sender is willing to pay per unit of gas.
+    ///
+    /// EIP-1559 style transactions cap this with `max_fee_per_gas`, while
+    /// legacy and access list transactions pay `gas_price`.
+    pub fn fee_cap(&self) -> U256 {
+        match self.transaction_type() {
+            TransactionType::DynamicFee | TransactionType::SetCode => {
+                self.max_fee_per_gas.unwrap_or_default()
+            }
+            TransactionType::Legacy | TransactionType::AccessList => self.gas_price,
+        }
+    }
+
+    /// The most a transaction can cost its sender: its value and its gas
+    /// limit at its fee cap, or `None` if that doesn't fit in a `U256`
+    pub fn max_cost(&self) -> Option<U256> {
+        self.gas
+            .checked_mul(self.fee_cap())?
+            .checked_add(self.value)
+    }
+
+    /// What the block producer earns per unit of gas on top of the base fee.
+    ///
+    /// EIP-1559 style transactions tip up to `max_priority_fee_per_gas`, within
+    /// what's left of `max_fee_per_gas` after the base fee. Legacy and access
+    /// list transactions tip whatever their `gas_price` leaves over.
+    pub fn effective_tip(&self, base_fee: U256) -> U256 {
+        let tip = self.fee_cap().saturating_sub(base_fee);
+
+        match self.transaction_type() {
+            TransactionType::DynamicFee | TransactionType::SetCode => {
+                tip.min(self.max_priority_fee_per_gas.unwrap_or_default())
+            }
+            TransactionType::Legacy | TransactionType::AccessList => tip,
+        }
+    }
+
+    /// The first four bytes of the calldata, which identify the function being called
+    pub fn method_selector(&self) -> Option<[u8; 4]> {
+        self.data
+            .as_ref()
+            .and_then(|data| data.get(..4))
+            .map(|selector| [selector[0], selector[1], selector[2], selector[3]])
+    }
+
+    /// Contract creation transactions have no `to` address
+    pub fn is_contract_creation(&self) -> bool {
+        self.to.is_none()
+    }
+
     pub fn kind(self) -> Result<TransactionKind> {
         match (self.from, self.to, self.data) {
             (from, Some(to), None) => Ok(TransactionKind::Regular(from, to, self.value)),
@@ -93,12 +436,201 @@ impl Transaction {
         }
     }
 
+    /// The type is inferred from the fields that are present, favoring the
+    /// most recent type that the fields support.
+    pub fn transaction_type(&self) -> TransactionType {
+        if self.authorization_list.is_some() {
+            TransactionType::SetCode
+        } else if self.max_fee_per_gas.is_some() || self.max_priority_fee_per_gas.is_some() {
+            TransactionType::DynamicFee
+        } else if self.access_list.is_some() {
+            TransactionType::AccessList
+        } else {
+            TransactionType::Legacy
+        }
+    }
+
+    /// The payload that is hashed and signed by the sender.
+    ///
+    /// This is the RLP encoding of the unsigned fields of the transaction.
+    /// Typed transactions are prefixed with their type byte (EIP-2718).
+    pub fn signing_payload(&self) -> Result<Vec<u8>> {
+        self.encode(None)
+    }
+
+    /// Encode the transaction, appending the signature's `v`, `r` and `s` to
+    /// the fields when signed. A signed legacy transaction fails to encode if
+    /// its chain id is too large for an EIP-155 `v`.
+    fn encode(&self, signature: Option<&Signature>) -> Result<Vec<u8>> {
+        let transaction_type = self.transaction_type();
+        let chain_id = self.chain_id.unwrap_or_default();
+        let nonce = self.nonce.unwrap_or_default();
+        let max_priority_fee_per_gas = self.max_priority_fee_per_gas.unwrap_or_default();
+        let max_fee_per_gas = self.max_fee_per_gas.unwrap_or_default();
+        let data = self
+            .data
+            .as_ref()
+            .map(|data| data.to_vec())
+            .unwrap_or_default();
+        let access_list = self.access_list.as_deref().unwrap_or_default();
+        let authorization_list = self.authorization_list.as_deref().unwrap_or_default();
+        let signature_size = if signature.is_some() { 3 } else { 0 };
+        let mut stream = RlpStream::new();
+
+        match transaction_type {
+            TransactionType::Legacy => {
+                // EIP-155 replay protection signs over `chain_id, 0, 0`, which the
+                // signature then takes the place of
+                let replay_protection_size = match (self.chain_id, signature) {
+                    (Some(_), None) => 3,
+                    _ => 0,
+                };
+
+                stream.begin_list(6 + replay_protection_size + signature_size);
+                stream
+                    .append(&nonce)
+                    .append(&self.gas_price)
+                    .append(&self.gas);
+                self.rlp_append_to(&mut stream);
+                stream.append(&self.value).append(&data);
+
+                if replay_protection_size > 0 {
+                    stream.append(&chain_id).append(&0_u8).append(&0_u8);
+                }
+            }
+            TransactionType::AccessList => {
+                stream.begin_list(8 + signature_size);
+                stream
+                    .append(&chain_id)
+                    .append(&nonce)
+                    .append(&self.gas_price)
+                    .append(&self.gas);
+                self.rlp_append_to(&mut stream);
+                stream
+                    .append(&self.value)
+                    .append(&data)
+                    .append_list::<AccessListItem, _>(access_list);
+            }
+            TransactionType::DynamicFee => {
+                stream.begin_list(9 + signature_size);
+                stream
+                    .append(&chain_id)
+                    .append(&nonce)
+                    .append(&max_priority_fee_per_gas)
+                    .append(&max_fee_per_gas)
+                    .append(&self.gas);
+                self.rlp_append_to(&mut stream);
+                stream
+                    .append(&self.value)
+                    .append(&data)
+                    .append_list::<AccessListItem, _>(access_list);
+            }
+            TransactionType::SetCode => {
+                // EIP-7702 transactions cannot create contracts
+                let to = self.to.ok_or_else(|| {
+                    TypeError::InvalidTransaction("set code transactions require a `to`".into())
+                })?;
+
+                stream.begin_list(10 + signature_size);
+                stream
+                    .append(&chain_id)
+                    .append(&nonce)
+                    .append(&max_priority_fee_per_gas)
+                    .append(&max_fee_per_gas)
+                    .append(&self.gas)
+                    .append(&to)
+                    .append(&self.value)
+                    .append(&data)
+                    .append_list::<AccessListItem, _>(access_list)
+                    .append_list::<SignedAuthorization, _>(authorization_list);
+            }
+        }
+
+        if let Some(signature) = signature {
+            // legacy transactions carry the recovery id offset by 27, or by
+            // `chain_id * 2 + 35` under EIP-155, typed transactions carry the
+            // bare y parity
+            let v = match (transaction_type, self.chain_id) {
+                (TransactionType::Legacy, Some(chain_id))
+                    if chain_id > MAX_LEGACY_CHAIN_ID.into() =>
+                {
+                    return Err(TypeError::InvalidTransaction(format!(
+                        "chain id {} is too large for a legacy transaction",
+                        chain_id
+                    )));
+                }
+                (TransactionType::Legacy, Some(chain_id)) => {
+                    signature.v + chain_id.as_u64() * 2 + 35
+                }
+                (TransactionType::Legacy, None) => signature.v + 27,
+                _ => signature.v,
+            };
+
+            stream
+                .append(&v)
+                .append(&U256::from_big_endian(signature.r.as_bytes()))
+                .append(&U256::from_big_endian(signature.s.as_bytes()));
+        }
+
+        let mut payload = stream.out().to_vec();
+
+        if transaction_type != TransactionType::Legacy {
+            payload.insert(0, transaction_type as u8);
+        }
+
+        Ok(payload)
+    }
+
+    fn rlp_append_to(&self, stream: &mut RlpStream) {
+        match self.to {
+            Some(to) => stream.append(&to),
+            None => stream.append_empty_data(),
+        };
+    }
+
+    /// Parse a raw signed transaction (e.g. from a wallet) given as hex, with
+    /// or without a `0x` prefix, recovering the sender from the signature.
+    ///
+    /// ```rust
+    /// use types::transaction::Transaction;
+    ///
+    /// let raw = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000801ba08383adc8b8ae116f918fb44ca7ff9dfd8012596a5c130c6246a2cc717ba41cdaa053ddfacf5bd4aa7e46d1575acf52636ea659b91f29e2fb91c75567a279738f38";
+    /// let signed_transaction = Transaction::from_raw_hex(raw).unwrap();
+    /// let transaction: Transaction = signed_transaction.try_into().unwrap();
+    /// assert_eq!(format!("{:?}", transaction.from), "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    /// ```
+    pub fn from_raw_hex(raw: &str) -> Result<SignedTransaction> {
+        let envelope = hex::decode(strip_0x(raw))
+            .map_err(|e| TypeError::EncodingDecodingError(e.to_string()))?;
+
+        SignedTransaction::from_envelope(&envelope)
+    }
+
     pub fn sign(&self, key: SecretKey) -> Result<SignedTransaction> {
+        self.sign_with(key, sign_hash)
+    }
+
+    fn sign_with(
+        &self,
+        key: SecretKey,
+        signer: impl Fn(H256, &SecretKey) -> Signature,
+    ) -> Result<SignedTransaction> {
         let encoded = bincode::serialize(&self)?;
-        let recoverable_signature = sign_recovery(&encoded, &key)?;
-        let (_, signature_bytes) = recoverable_signature.serialize_compact();
-        let Signature { v, r, s } = recoverable_signature.into();
-        let transaction_hash = hash(&signature_bytes).into();
+        let message = hash(&self.signing_payload()?).into();
+        let signature = signer(message, &key);
+
+        // in debug builds, check the signature recovers to the signing key
+        if cfg!(debug_assertions)
+            && recover(message, &signature).ok() != Some(private_key_address(&key))
+        {
+            return Err(TypeError::SigningFailed(format!(
+                "signature of {:?} does not recover to the signing key",
+                self.hash
+            )));
+        }
+
+        let Signature { v, r, s } = signature;
+        let transaction_hash = hash(&[r.as_bytes(), s.as_bytes()].concat()).into();
 
         let signed_transaction = SignedTransaction {
             v,
@@ -121,8 +653,9 @@ impl Transaction {
     }
 
     pub fn recover_address(signed_transaction: SignedTransaction) -> Result<H160> {
-        let key = Self::recover_public_key(signed_transaction)?;
-        let address = public_key_address(&key);
+        let transaction: Transaction = signed_transaction.clone().try_into()?;
+        let message = hash(&transaction.signing_payload()?).into();
+        let address = recover(message, &signed_transaction.into())?;
 
         Ok(address)
     }
@@ -137,12 +670,13 @@ impl Transaction {
     fn recover_pieces(
         signed_transaction: SignedTransaction,
     ) -> Result<(Vec<u8>, RecoveryId, [u8; 64])> {
-        let message = signed_transaction.raw_transaction.to_owned();
+        let transaction: Transaction = signed_transaction.clone().try_into()?;
+        let message = transaction.signing_payload()?;
         let signature: Signature = signed_transaction.into();
         let recoverable_signature: RecoverableSignature = signature.try_into()?;
         let (recovery_id, signature_bytes) = recoverable_signature.serialize_compact();
 
-        Ok((message.to_vec(), recovery_id, signature_bytes))
+        Ok((message, recovery_id, signature_bytes))
     }
 
     fn to_trie(transactions: &[Transaction]) -> Result<EthTrie<MemoryDB>> {
@@ -170,7 +704,142 @@ impl Transaction {
     }
 }
 
+/// A list of addresses and storage keys a transaction intends to access (EIP-2930)
+pub type AccessList = Vec<AccessListItem>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<H256>,
+}
+
+impl Encodable for AccessListItem {
+    fn rlp_append(&self, stream: &mut RlpStream) {
+        stream.begin_list(2);
+        stream.append(&self.address);
+        stream.append_list::<H256, _>(&self.storage_keys);
+    }
+}
+
+impl Decodable for AccessListItem {
+    fn decode(rlp: &Rlp) -> std::result::Result<Self, DecoderError> {
+        Ok(AccessListItem {
+            address: rlp.val_at(0)?,
+            storage_keys: rlp.list_at(1)?,
+        })
+    }
+}
+
+/// An EIP-7702 authorization: the authority allows its account to delegate
+/// execution to the code at `address`.
+///
+/// see https://eips.ethereum.org/EIPS/eip-7702
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct Authorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: U64,
+}
+
+impl Authorization {
+    /// The payload signed by the authority: `MAGIC || rlp([chain_id, address, nonce])`
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new();
+        stream.begin_list(3);
+        stream
+            .append(&self.chain_id)
+            .append(&self.address)
+            .append(&self.nonce);
+
+        let mut payload = vec![AUTHORIZATION_MAGIC];
+        payload.extend_from_slice(&stream.out());
+
+        payload
+    }
+
+    pub fn sign(&self, key: &SecretKey) -> Result<SignedAuthorization> {
+        let Signature { v, r, s } = sign_hash(hash(&self.signing_payload()).into(), key);
+
+        Ok(SignedAuthorization {
+            chain_id: self.chain_id,
+            address: self.address,
+            nonce: self.nonce,
+            y_parity: U64::from(v),
+            r: U256::from_big_endian(r.as_bytes()),
+            s: U256::from_big_endian(s.as_bytes()),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct SignedAuthorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: U64,
+    pub y_parity: U64,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl SignedAuthorization {
+    pub fn authorization(&self) -> Authorization {
+        Authorization {
+            chain_id: self.chain_id,
+            address: self.address,
+            nonce: self.nonce,
+        }
+    }
+
+    /// Recover the address of the account that signed the authorization
+    pub fn authority(&self) -> Result<Address> {
+        let mut signature = Signature {
+            v: self.y_parity.as_u64(),
+            r: H256::zero(),
+            s: H256::zero(),
+        };
+        self.r.to_big_endian(signature.r.as_bytes_mut());
+        self.s.to_big_endian(signature.s.as_bytes_mut());
+
+        let message = hash(&self.authorization().signing_payload()).into();
+        let address = recover(message, &signature)?;
+
+        Ok(address)
+    }
+}
+
+impl Encodable for SignedAuthorization {
+    fn rlp_append(&self, stream: &mut RlpStream) {
+        stream.begin_list(6);
+        stream
+            .append(&self.chain_id)
+            .append(&self.address)
+            .append(&self.nonce)
+            .append(&self.y_parity)
+            .append(&self.r)
+            .append(&self.s);
+    }
+}
+
+impl Decodable for SignedAuthorization {
+    fn decode(rlp: &Rlp) -> std::result::Result<Self, DecoderError> {
+        Ok(SignedAuthorization {
+            chain_id: rlp.val_at(0)?,
+            address: rlp.val_at(1)?,
+            nonce: rlp.val_at(2)?,
+            y_parity: rlp.val_at(3)?,
+            r: rlp.val_at(4)?,
+            s: rlp.val_at(5)?,
+        })
+    }
+}
+
+/// In JSON a signed transaction also carries the `type` of the transaction it
+/// encodes, which must match it.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(remote = "Self")]
 pub struct SignedTransaction {
     pub v: u64,
     pub r: H256,
@@ -179,6 +848,64 @@ pub struct SignedTransaction {
     pub transaction_hash: H256,
 }
 
+impl SignedTransaction {
+    fn transaction_type(&self) -> Result<TransactionType> {
+        let transaction: Transaction = self.clone().try_into()?;
+        Ok(transaction.transaction_type())
+    }
+}
+
+impl Serialize for Untyped<&SignedTransaction> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        SignedTransaction::serialize(self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Untyped<SignedTransaction> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        SignedTransaction::deserialize(deserializer).map(Untyped)
+    }
+}
+
+impl Serialize for SignedTransaction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return SignedTransaction::serialize(self, serializer);
+        }
+
+        Typed {
+            transaction_type: Some(self.transaction_type().map_err(ser::Error::custom)?),
+            transaction: Untyped(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SignedTransaction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return SignedTransaction::deserialize(deserializer);
+        }
+
+        let Typed {
+            transaction_type,
+            transaction: Untyped(signed_transaction),
+        } = Typed::<Untyped<SignedTransaction>>::deserialize(deserializer)?;
+        let encoded = signed_transaction
+            .transaction_type()
+            .map_err(de::Error::custom)?;
+
+        if let Some(transaction_type) = transaction_type.filter(|&tagged| tagged != encoded) {
+            return Err(de::Error::custom(format!(
+                "a signed transaction of type {:#x} encodes a transaction of type {:#x}",
+                transaction_type as u8, encoded as u8
+            )));
+        }
+
+        Ok(signed_transaction)
+    }
+}
+
 impl From<SignedTransaction> for Signature {
     fn from(value: SignedTransaction) -> Self {
         Signature {
@@ -189,6 +916,210 @@ impl From<SignedTransaction> for Signature {
     }
 }
 
+impl SignedTransaction {
+    /// The EIP-2718 envelope sent over the wire and included in blocks:
+    /// `type || rlp(fields, v, r, s)` for typed transactions and bare
+    /// `rlp(fields, v, r, s)` for legacy transactions.
+    pub fn to_envelope(&self) -> Result<Vec<u8>> {
+        let transaction: Transaction = self.clone().try_into()?;
+
+        transaction.encode(Some(&self.clone().into()))
+    }
+
+    /// Check the transaction is consistent with its signature before trusting
+    /// it: the signature is canonical (low `s` and a recovery id of 0 or 1),
+    /// the chain id can be signed, the hash is of the signature and the
+    /// signature recovers to `from`.
+    pub fn verify(&self) -> Result<()> {
+        if U256::from_big_endian(self.s.as_bytes()) > SECP256K1_HALF_ORDER {
+            return Err(TypeError::InvalidSignature(
+                "s is in the upper half of the curve order".into(),
+            ));
+        }
+
+        if self.v > 1 {
+            return Err(TypeError::InvalidSignature(format!(
+                "recovery id {} is not 0 or 1",
+                self.v
+            )));
+        }
+
+        let transaction: Transaction = self.clone().try_into()?;
+
+        match (transaction.transaction_type(), transaction.chain_id) {
+            (TransactionType::Legacy, Some(chain_id)) if chain_id > MAX_LEGACY_CHAIN_ID.into() => {
+                return Err(TypeError::InvalidTransaction(format!(
+                    "chain id {} is too large for a legacy transaction",
+                    chain_id
+                )));
+            }
+            // typed envelopes always carry a chain id, so one signed without
+            // would decode as chain 0
+            (TransactionType::Legacy, _) | (_, Some(_)) => {}
+            (_, None) => {
+                return Err(TypeError::InvalidTransaction(
+                    "typed transaction without a chain id".into(),
+                ));
+            }
+        }
+
+        let signature_hash: H256 = hash(&[self.r.as_bytes(), self.s.as_bytes()].concat()).into();
+
+        if self.transaction_hash != signature_hash {
+            return Err(TypeError::InvalidTransaction(format!(
+                "transaction hash {:?} is not the hash of the signature",
+                self.transaction_hash
+            )));
+        }
+
+        let message = hash(&transaction.signing_payload()?).into();
+        let signer = recover(message, &self.clone().into())
+            .map_err(|e| TypeError::InvalidSignature(e.to_string()))?;
+
+        if signer != transaction.from {
+            return Err(TypeError::SenderMismatch(format!(
+                "signed by {:?}, not {:?}",
+                signer, transaction.from
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Decode an EIP-2718 envelope, recovering the sender from the signature.
+    ///
+    /// A first byte within `0x00..=0x7f` is a transaction type, while a
+    /// legacy transaction starts with an RLP list prefix (`0xc0..=0xff`).
+    pub fn from_envelope(envelope: &[u8]) -> Result<SignedTransaction> {
+        let (transaction_type, payload) = match envelope.first() {
+            Some(0x01) => (TransactionType::AccessList, &envelope[1..]),
+            Some(0x02) => (TransactionType::DynamicFee, &envelope[1..]),
+            Some(0x04) => (TransactionType::SetCode, &envelope[1..]),
+            Some(0xc0..=0xff) => (TransactionType::Legacy, envelope),
+            Some(byte) => {
+                return Err(TypeError::InvalidTransaction(format!(
+                    "unsupported transaction type {:#04x}",
+                    byte
+                )))
+            }
+            None => return Err(TypeError::InvalidTransaction("empty envelope".into())),
+        };
+
+        let rlp = Rlp::new(payload);
+        let mut transaction = Transaction {
+            from: Address::zero(),
+            to: None,
+            hash: None,
+            nonce: None,
+            value: U256::zero(),
+            data: None,
+            gas: U256::zero(),
+            gas_price: U256::zero(),
+            chain_id: None,
+            access_list: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            authorization_list: None,
+        };
+
+        // the index of the first field shared by every type (`gas`)
+        let gas_index = match transaction_type {
+            TransactionType::Legacy => {
+                transaction.nonce = Some(rlp.val_at(0)?);
+                transaction.gas_price = rlp.val_at(1)?;
+                2
+            }
+            TransactionType::AccessList => {
+                transaction.chain_id = Some(rlp.val_at(0)?);
+                transaction.nonce = Some(rlp.val_at(1)?);
+                transaction.gas_price = rlp.val_at(2)?;
+                3
+            }
+            TransactionType::DynamicFee | TransactionType::SetCode => {
+                let max_fee_per_gas = rlp.val_at(3)?;
+
+                transaction.chain_id = Some(rlp.val_at(0)?);
+                transaction.nonce = Some(rlp.val_at(1)?);
+                transaction.max_priority_fee_per_gas = Some(rlp.val_at(2)?);
+                transaction.max_fee_per_gas = Some(max_fee_per_gas);
+                // the most the sender can be charged per gas
+                transaction.gas_price = max_fee_per_gas;
+                4
+            }
+        };
+
+        let to = rlp.at(gas_index + 1)?;
+        let data: Vec<u8> = rlp.val_at(gas_index + 3)?;
+
+        transaction.gas = rlp.val_at(gas_index)?;
+        transaction.to = (!to.is_empty()).then(|| to.as_val()).transpose()?;
+        transaction.value = rlp.val_at(gas_index + 2)?;
+        transaction.data = (!data.is_empty()).then(|| data.into());
+
+        let mut signature_index = gas_index + 4;
+
+        if transaction_type != TransactionType::Legacy {
+            let access_list: AccessList = rlp.list_at(signature_index)?;
+            signature_index += 1;
+
+            // an empty access list is what marks a transaction as type 1
+            transaction.access_list = match transaction_type {
+                TransactionType::AccessList => Some(access_list),
+                _ => (!access_list.is_empty()).then_some(access_list),
+            };
+        }
+
+        if transaction_type == TransactionType::SetCode {
+            transaction.authorization_list = Some(rlp.list_at(signature_index)?);
+            signature_index += 1;
+        }
+
+        if rlp.item_count()? != signature_index + 3 {
+            return Err(TypeError::InvalidTransaction(
+                "unexpected number of fields in envelope".into(),
+            ));
+        }
+
+        let v: u64 = rlp.val_at(signature_index)?;
+        let r: U256 = rlp.val_at(signature_index + 1)?;
+        let s: U256 = rlp.val_at(signature_index + 2)?;
+        let v = match (transaction_type, v) {
+            (TransactionType::Legacy, 27 | 28) => v - 27,
+            (TransactionType::Legacy, 35..) => {
+                transaction.chain_id = Some(U64::from((v - 35) / 2));
+                (v - 35) % 2
+            }
+            (TransactionType::Legacy, _) => {
+                return Err(TypeError::InvalidTransaction(format!(
+                    "unsupported v {}",
+                    v
+                )))
+            }
+            _ => v,
+        };
+        let mut signature = Signature {
+            v,
+            r: H256::zero(),
+            s: H256::zero(),
+        };
+        r.to_big_endian(signature.r.as_bytes_mut());
+        s.to_big_endian(signature.s.as_bytes_mut());
+
+        let message = hash(&transaction.signing_payload()?).into();
+        transaction.from = recover(message, &signature)?;
+        transaction.hash()?;
+
+        Ok(SignedTransaction {
+            v: signature.v,
+            r: signature.r,
+            s: signature.s,
+            raw_transaction: bincode::serialize(&transaction)?.into(),
+            transaction_hash: hash(&[signature.r.as_bytes(), signature.s.as_bytes()].concat())
+                .into(),
+        })
+    }
+}
+
 impl TryInto<Transaction> for SignedTransaction {
     type Error = TypeError;
 
@@ -198,24 +1129,49 @@ impl TryInto<Transaction> for SignedTransaction {
     }
 }
 
+/// Fields are declared, and so serialized, in the order of the JSON-RPC spec.
+/// Unset fields are omitted, as are a zero `value` and empty `data`.
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
 pub struct TransactionRequest {
-    pub data: Option<Bytes>,
-    pub gas: U256,
-    pub gas_price: U256,
     pub from: Option<Address>,
     pub to: Option<Address>,
+    /// Zero is omitted, leaving the node to pick a limit (e.g. when estimating gas)
+    #[serde(default, skip_serializing_if = "U256::is_zero")]
+    pub gas: U256,
+    #[serde(default, skip_serializing_if = "U256::is_zero")]
+    pub gas_price: U256,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(default, skip_serializing_if = "is_zero")]
     pub value: Option<U256>,
+    #[serde(
+        default,
+        alias = "input",
+        skip_serializing_if = "is_empty",
+        with = "crate::helpers::hex_bytes_option"
+    )]
+    pub data: Option<Bytes>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub nonce: Option<U256>,
+    pub chain_id: Option<U64>,
+    pub access_list: Option<AccessList>,
+    pub authorization_list: Option<Vec<SignedAuthorization>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub r: Option<U256>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub s: Option<U256>,
 }
 
+fn is_zero(value: &Option<U256>) -> bool {
+    value.is_none_or(|value| value.is_zero())
+}
+
+fn is_empty(data: &Option<Bytes>) -> bool {
+    data.as_ref().is_none_or(|data| data.is_empty())
+}
+
 impl From<Transaction> for TransactionRequest {
     fn from(value: Transaction) -> TransactionRequest {
         TransactionRequest {
@@ -228,6 +1184,11 @@ impl From<Transaction> for TransactionRequest {
             nonce: value.nonce,
             r: None,
             s: None,
+            chain_id: value.chain_id,
+            access_list: value.access_list,
+            max_fee_per_gas: value.max_fee_per_gas,
+            max_priority_fee_per_gas: value.max_priority_fee_per_gas,
+            authorization_list: value.authorization_list,
         }
     }
 }
@@ -238,7 +1199,25 @@ impl TryInto<Transaction> for TransactionRequest {
     fn try_into(self) -> Result<Transaction> {
         let value = self.value.unwrap_or(U256::zero());
         let from = self.from.unwrap_or(H160::zero());
-        Transaction::new(from, self.to, value, self.nonce, self.data)
+        let mut transaction = Transaction::new(from, self.to, value, self.nonce, self.data)?;
+
+        // an unset gas limit or price keeps the defaults of `Transaction::new`
+        if !self.gas.is_zero() {
+            transaction.gas = self.gas;
+        }
+
+        if !self.gas_price.is_zero() {
+            transaction.gas_price = self.gas_price;
+        }
+
+        transaction.chain_id = self.chain_id;
+        transaction.access_list = self.access_list;
+        transaction.max_fee_per_gas = self.max_fee_per_gas;
+        transaction.max_priority_fee_per_gas = self.max_priority_fee_per_gas;
+        transaction.authorization_list = self.authorization_list;
+        transaction.hash()?;
+
+        Ok(transaction)
     }
 }
 
@@ -248,25 +1227,187 @@ pub struct TransactionReceipt {
     pub block_hash: Option<H256>,
     pub block_number: Option<BlockNumber>,
     pub contract_address: Option<H160>,
+    #[serde(default)]
+    pub from: Address,
+    pub to: Option<Address>,
     pub transaction_hash: H256,
+    pub transaction_index: Option<U64>,
+    #[serde(default)]
+    pub gas_used: U256,
+    #[serde(default)]
+    pub status: U64,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all(serialize = "snake_case", deserialize = "camelCase"))]
+impl TransactionReceipt {
+    fn to_trie(receipts: &[TransactionReceipt]) -> Result<EthTrie<MemoryDB>> {
+        let memdb = Arc::new(MemoryDB::new(true));
+        let mut trie = EthTrie::new(memdb);
+
+        receipts.iter().try_for_each(|receipt| {
+            trie.insert(
+                receipt.transaction_hash.as_bytes(),
+                receipt.root_encoding().as_slice(),
+            )
+            .map_err(|e| TypeError::TrieError(format!("Error inserting receipts: {}", e)))
+        })?;
+
+        Ok(trie)
+    }
+
+    // The outcome of the transaction, RLP encoded so the root doesn't move
+    // when fields are added to the receipt. The block is assigned after the
+    // root is calculated, and the sender and recipient come from the
+    // transaction itself, so they're left out.
+    fn root_encoding(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(3);
+        stream.append(&self.status).append(&self.gas_used);
+
+        match self.contract_address {
+            Some(contract_address) => stream.append(&contract_address),
+            None => stream.append_empty_data(),
+        };
+
+        stream.out().to_vec()
+    }
+
+    pub fn root_hash(receipts: &[TransactionReceipt]) -> Result<H256> {
+        let mut trie = Self::to_trie(receipts)?;
+        let root_hash = trie
+            .root_hash()
+            .map_err(|e| TypeError::TrieError(format!("Error calculating root hash: {}", e)))?;
+
+        Ok(H256::from_slice(root_hash.as_bytes()))
+    }
+}
+
+/// The outcome of executing a single transaction against a state.
+///
+/// A status of 1 indicates success, 0 indicates the execution failed.
+/// Failed executions still consume gas and advance the sender's nonce.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct ExecutionResult {
+    pub status: U64,
+    pub gas_used: U256,
+    pub contract_address: Option<H160>,
+    pub logs: Vec<Log>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ExecutionResult {
+    /// A failed execution that still consumed gas
+    pub fn failure(gas_used: U256, error: String) -> Self {
+        ExecutionResult {
+            status: U64::zero(),
+            gas_used,
+            contract_address: None,
+            logs: vec![],
+            error: Some(error),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status == U64::one()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
 pub struct Log {
     pub address: H160,
     pub block_hash: Option<H256>,
     pub block_number: Option<U64>,
+    /// Nodes send the data as hex
+    #[serde(with = "crate::helpers::hex_bytes")]
     pub data: Bytes,
     pub log_index: Option<U256>,
     pub log_type: Option<String>,
     pub removed: Option<bool>,
     pub topics: Vec<H256>,
     pub transaction_hash: Option<H256>,
-    pub transaction_index: Option<String>,
+    pub transaction_index: Option<U64>,
     pub transaction_log_index: Option<U256>,
 }
 
+/// Criteria for selecting logs with `eth_getLogs`.
+/// Logs are selected from a range of blocks or from a single block by its
+/// hash, but not both.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct LogFilter {
+    pub from_block: Option<BlockTag>,
+    pub to_block: Option<BlockTag>,
+    #[serde(default)]
+    pub block_hash: Option<H256>,
+    pub address: Option<OneOrMany<Address>>,
+    /// Topics by position, where `None` matches any topic in that position
+    pub topics: Option<Vec<Option<OneOrMany<H256>>>>,
+}
+
+impl LogFilter {
+    pub fn validate(&self) -> Result<()> {
+        let has_range = self.from_block.is_some() || self.to_block.is_some();
+
+        if has_range && self.block_hash.is_some() {
+            return Err(TypeError::InvalidLogFilter(
+                "blockHash can't be combined with fromBlock or toBlock".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Whether a log has one of the filter's addresses and, position by
+    /// position, one of its topics.
+    /// The block range is left to the node, which knows which blocks it spans.
+    pub fn matches(&self, log: &Log) -> bool {
+        let address_matches = self
+            .address
+            .as_ref()
+            .is_none_or(|address| address.contains(&log.address));
+        let topics_match = self.topics.iter().flatten().enumerate().all(|(i, topic)| {
+            topic.as_ref().is_none_or(|topic| {
+                log.topics
+                    .get(i)
+                    .is_some_and(|log_topic| topic.contains(log_topic))
+            })
+        });
+
+        address_matches && topics_match
+    }
+}
+
+/// A single value, or several where any of them matches
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T: PartialEq> OneOrMany<T> {
+    pub fn contains(&self, value: &T) -> bool {
+        match self {
+            OneOrMany::One(one) => one == value,
+            OneOrMany::Many(many) => many.contains(value),
+        }
+    }
+}
+
+impl<T> From<T> for OneOrMany<T> {
+    fn from(value: T) -> Self {
+        OneOrMany::One(value)
+    }
+}
+
+impl<T> From<Vec<T>> for OneOrMany<T> {
+    fn from(values: Vec<T>) -> Self {
+        OneOrMany::Many(values)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -282,6 +1423,26 @@ mod tests {
         Transaction::new(from, Some(to), value, None, None).unwrap()
     }
 
+    #[test]
+    fn it_exposes_the_method_selector() {
+        let to = Some(Account::random());
+        let data = Some(Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb, 0x00, 0x01]));
+        let call = Transaction::new(Account::random(), to, U256::zero(), None, data).unwrap();
+        assert_eq!(call.method_selector(), Some([0xa9, 0x05, 0x9c, 0xbb]));
+        assert!(!call.is_contract_creation());
+
+        let transfer = Transaction::new(Account::random(), to, U256::one(), None, None).unwrap();
+        assert_eq!(transfer.method_selector(), None);
+
+        let short = Some(Bytes::from(vec![0xa9, 0x05]));
+        let short = Transaction::new(Account::random(), to, U256::zero(), None, short).unwrap();
+        assert_eq!(short.method_selector(), None);
+
+        let data = Some(Bytes::from(vec![0, 1]));
+        let deploy = Transaction::new(Account::random(), None, U256::zero(), None, data).unwrap();
+        assert!(deploy.is_contract_creation());
+    }
+
     #[test]
     fn it_recovers_an_address_from_a_signed_transaction() {
         let (secret_key, public_key) = keypair();
@@ -303,14 +1464,717 @@ mod tests {
         assert!(verifies);
     }
 
+    #[test]
+    fn it_checks_a_signed_transaction_is_self_consistent() {
+        let (secret_key, public_key) = keypair();
+        let mut transaction = new_transaction();
+        transaction.from = public_key_address(&public_key);
+        transaction.chain_id = Some(U64::from(1337));
+        let signed = transaction.sign(secret_key).unwrap();
+        assert!(signed.verify().is_ok());
+
+        // the twin signature with `n - s` recovers to the same signer
+        let order = SECP256K1_HALF_ORDER * U256::from(2) + U256::one();
+        let mut high_s = signed.clone();
+        (order - U256::from_big_endian(signed.s.as_bytes())).to_big_endian(high_s.s.as_bytes_mut());
+        high_s.v ^= 1;
+        assert!(matches!(
+            high_s.verify(),
+            Err(TypeError::InvalidSignature(_))
+        ));
+
+        let mut bad_recovery_id = signed.clone();
+        bad_recovery_id.v = 2;
+        assert!(matches!(
+            bad_recovery_id.verify(),
+            Err(TypeError::InvalidSignature(_))
+        ));
+
+        let mut bad_hash = signed.clone();
+        bad_hash.transaction_hash = H256::random();
+        assert!(matches!(
+            bad_hash.verify(),
+            Err(TypeError::InvalidTransaction(_))
+        ));
+
+        let tamper = |tamper: fn(&mut Transaction)| {
+            let mut tampered: Transaction = signed.clone().try_into().unwrap();
+            tamper(&mut tampered);
+            SignedTransaction {
+                raw_transaction: bincode::serialize(&tampered).unwrap().into(),
+                ..signed.clone()
+            }
+            .verify()
+        };
+        assert!(matches!(
+            tamper(|transaction| transaction.from = Account::random()),
+            Err(TypeError::SenderMismatch(_))
+        ));
+        assert!(matches!(
+            tamper(|transaction| transaction.value = U256::from(1_000)),
+            Err(TypeError::SenderMismatch(_))
+        ));
+        // replayed onto another chain
+        assert!(matches!(
+            tamper(|transaction| transaction.chain_id = Some(U64::from(1))),
+            Err(TypeError::SenderMismatch(_))
+        ));
+        assert!(matches!(
+            tamper(|transaction| transaction.chain_id = Some(U64::MAX)),
+            Err(TypeError::InvalidTransaction(_))
+        ));
+
+        let mut typed = transaction;
+        typed.chain_id = None;
+        typed.max_fee_per_gas = Some(U256::from(10));
+        let signed = typed.sign(secret_key).unwrap();
+        assert!(matches!(
+            signed.verify(),
+            Err(TypeError::InvalidTransaction(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn it_catches_a_corrupted_signature() {
+        let (secret_key, _) = keypair();
+        let transaction = new_transaction();
+        let flip_recovery_id = |message: H256, key: &SecretKey| {
+            let signature = sign_hash(message, key);
+            Signature {
+                v: 1 - signature.v,
+                ..signature
+            }
+        };
+
+        assert!(transaction.sign_with(secret_key, sign_hash).is_ok());
+        assert!(matches!(
+            transaction.sign_with(secret_key, flip_recovery_id),
+            Err(TypeError::SigningFailed(_))
+        ));
+    }
+
+    #[test]
+    fn equality_ignores_the_cached_hash() {
+        let mut transaction = new_transaction();
+        transaction.hash = None;
+
+        let mut hashed = transaction.clone();
+        hashed.hash().unwrap();
+        assert_eq!(transaction, hashed);
+
+        hashed.value = U256::from(2);
+        assert_ne!(transaction, hashed);
+    }
+
+    #[test]
+    fn it_calculates_intrinsic_gas() {
+        let mut transaction = new_transaction();
+        assert_eq!(transaction.intrinsic_gas(), U256::from(TRANSACTION_GAS));
+
+        transaction.data = Some(vec![0, 1].into());
+        assert_eq!(
+            transaction.intrinsic_gas(),
+            U256::from(TRANSACTION_GAS + DATA_ZERO_BYTE_GAS + DATA_NON_ZERO_BYTE_GAS)
+        );
+
+        transaction.to = None;
+        assert_eq!(
+            transaction.intrinsic_gas(),
+            U256::from(
+                TRANSACTION_GAS
+                    + CONTRACT_CREATION_GAS
+                    + DATA_ZERO_BYTE_GAS
+                    + DATA_NON_ZERO_BYTE_GAS
+            )
+        );
+    }
+
+    #[test]
+    fn it_calculates_the_effective_tip() {
+        let mut transaction = new_transaction();
+        transaction.gas_price = U256::from(30);
+        assert_eq!(transaction.effective_tip(U256::from(10)), U256::from(20));
+        assert_eq!(transaction.effective_tip(U256::from(40)), U256::zero());
+
+        transaction.max_fee_per_gas = Some(U256::from(30));
+        transaction.max_priority_fee_per_gas = Some(U256::from(5));
+        assert_eq!(transaction.effective_tip(U256::from(10)), U256::from(5));
+        assert_eq!(transaction.effective_tip(U256::from(28)), U256::from(2));
+    }
+
+    #[test]
+    fn it_recovers_the_authority_of_an_authorization() {
+        let (secret_key, public_key) = keypair();
+        let authorization = Authorization {
+            chain_id: U256::from(1),
+            address: H160::from_str("0x6b78fa07883d5c5b527da9828ac77f5aa5a61d3b").unwrap(),
+            nonce: U64::zero(),
+        };
+        let signed = authorization.sign(&secret_key).unwrap();
+
+        assert_eq!(signed.authority().unwrap(), public_key_address(&public_key));
+    }
+
+    #[test]
+    fn it_encodes_a_set_code_transaction() {
+        let mut transaction = new_transaction();
+        let to = transaction.to.unwrap();
+        transaction.chain_id = Some(U64::from(1));
+        transaction.max_priority_fee_per_gas = Some(U256::from(1));
+        transaction.max_fee_per_gas = Some(U256::from(2));
+        transaction.access_list = Some(vec![]);
+        transaction.authorization_list = Some(vec![SignedAuthorization {
+            chain_id: U256::from(1),
+            address: to,
+            nonce: U64::zero(),
+            y_parity: U64::from(1),
+            r: U256::from(1),
+            s: U256::from(2),
+        }]);
+
+        assert_eq!(transaction.transaction_type(), TransactionType::SetCode);
+        assert_eq!(
+            hex::encode(transaction.signing_payload().unwrap()),
+            "04f839018001020a946b78fa07883d5c5b527da9828ac77f5aa5a61d3b0180c0dbda01946b78fa07883d5c5b527da9828ac77f5aa5a61d3b80010102"
+        );
+
+        // set code transactions cannot deploy contracts
+        transaction.to = None;
+        assert!(transaction.signing_payload().is_err());
+    }
+
+    #[test]
+    fn it_verifies_a_signed_set_code_transaction() {
+        let (secret_key, public_key) = keypair();
+        let (authority_key, authority_public_key) = keypair();
+        let mut transaction = new_transaction();
+        transaction.from = public_key_address(&public_key);
+        transaction.chain_id = Some(U64::from(1));
+        transaction.authorization_list = Some(vec![Authorization {
+            chain_id: U256::from(1),
+            address: transaction.to.unwrap(),
+            nonce: U64::zero(),
+        }
+        .sign(&authority_key)
+        .unwrap()]);
+
+        let signed = transaction.sign(secret_key).unwrap();
+        let recovered: Transaction = signed.clone().try_into().unwrap();
+
+        assert!(Transaction::verify(signed, transaction.from).unwrap());
+        assert_eq!(
+            recovered.authorization_list.unwrap()[0]
+                .authority()
+                .unwrap(),
+            public_key_address(&authority_public_key)
+        );
+    }
+
+    #[test]
+    fn it_serializes_a_minimal_transaction_request() {
+        let transaction_request = TransactionRequest {
+            to: Some(H160::from_low_u64_be(1)),
+            gas: U256::from(21_000),
+            gas_price: U256::from(1),
+            value: Some(U256::zero()),
+            data: Some(Bytes::new()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_string(&transaction_request).unwrap(),
+            r#"{"to":"0x0000000000000000000000000000000000000001","gas":"0x5208","gasPrice":"0x1"}"#
+        );
+    }
+
+    #[test]
+    fn it_leaves_gas_for_the_node_to_fill_in() {
+        let transaction_request = TransactionRequest {
+            to: Some(H160::from_low_u64_be(1)),
+            ..Default::default()
+        };
+        let serialized = serde_json::to_string(&transaction_request).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"to":"0x0000000000000000000000000000000000000001"}"#
+        );
+
+        let deserialized: TransactionRequest = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.gas, U256::zero());
+        assert_eq!(deserialized.gas_price, U256::zero());
+    }
+
+    #[test]
+    fn it_serializes_a_transaction_request_in_spec_order() {
+        let transaction_request = TransactionRequest {
+            from: Some(H160::from_low_u64_be(2)),
+            to: Some(H160::from_low_u64_be(1)),
+            gas: U256::from(21_000),
+            gas_price: U256::from(1),
+            value: Some(U256::from(10)),
+            nonce: Some(U256::zero()),
+            chain_id: Some(U64::from(1)),
+            ..Default::default()
+        };
+        let serialized = serde_json::to_string(&transaction_request).unwrap();
+        let keys = ["from", "to", "gas", "gasPrice", "value", "nonce", "chainId"];
+        let positions = keys
+            .iter()
+            .map(|key| serialized.find(&format!("\"{}\"", key)).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn it_turns_a_fetched_transaction_into_a_request() {
+        let fetched = serde_json::json!({
+            "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "blockNumber": "0x1",
+            "from": "0x0000000000000000000000000000000000000002",
+            "to": "0x0000000000000000000000000000000000000001",
+            "gas": "0x5208",
+            "gasPrice": "0x1",
+            "input": "0xa9059cbb",
+            "nonce": "0x0",
+            "value": "0xa",
+            "transactionIndex": "0x0",
+        });
+        let transaction: Transaction = serde_json::from_value(fetched).unwrap();
+        assert_eq!(
+            transaction.data,
+            Some(Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]))
+        );
+
+        let transaction_request =
+            serde_json::to_value(TransactionRequest::from(transaction)).unwrap();
+        assert_eq!(transaction_request["data"], "0xa9059cbb");
+        assert!(transaction_request.get("input").is_none());
+    }
+
+    #[test]
+    fn it_tags_each_type_of_transaction_in_json() {
+        let legacy = new_transaction();
+        let mut access_list = new_transaction();
+        access_list.access_list = Some(vec![]);
+        let mut dynamic_fee = new_transaction();
+        dynamic_fee.max_fee_per_gas = Some(U256::from(20));
+        let mut set_code = dynamic_fee.clone();
+        set_code.authorization_list = Some(vec![]);
+
+        for (transaction, tag) in [
+            (legacy, "0x0"),
+            (access_list, "0x1"),
+            (dynamic_fee, "0x2"),
+            (set_code, "0x4"),
+        ] {
+            let serialized = serde_json::to_value(&transaction).unwrap();
+            assert_eq!(serialized["type"], tag);
+
+            let deserialized: Transaction = serde_json::from_value(serialized).unwrap();
+            assert_eq!(deserialized, transaction);
+            assert_eq!(
+                deserialized.transaction_type(),
+                transaction.transaction_type()
+            );
+
+            let signed = transaction.sign(keypair().0).unwrap();
+            let serialized = serde_json::to_value(&signed).unwrap();
+            assert_eq!(serialized["type"], tag);
+            assert_eq!(
+                serde_json::from_value::<SignedTransaction>(serialized).unwrap(),
+                signed
+            );
+        }
+
+        let mut untagged = serde_json::to_value(new_transaction()).unwrap();
+        untagged.as_object_mut().unwrap().remove("type");
+        let deserialized: Transaction = serde_json::from_value(untagged.clone()).unwrap();
+        assert_eq!(deserialized.transaction_type(), TransactionType::Legacy);
+
+        let mut dynamic_fee = new_transaction();
+        dynamic_fee.max_fee_per_gas = Some(U256::from(20));
+        let mut untagged_dynamic_fee = serde_json::to_value(&dynamic_fee).unwrap();
+        untagged_dynamic_fee.as_object_mut().unwrap().remove("type");
+        let deserialized: Transaction = serde_json::from_value(untagged_dynamic_fee).unwrap();
+        assert_eq!(deserialized, dynamic_fee);
+        assert_eq!(deserialized.transaction_type(), TransactionType::DynamicFee);
+
+        let signed = dynamic_fee.sign(keypair().0).unwrap();
+        let mut untagged_signed = serde_json::to_value(&signed).unwrap();
+        untagged_signed.as_object_mut().unwrap().remove("type");
+        assert_eq!(
+            serde_json::from_value::<SignedTransaction>(untagged_signed).unwrap(),
+            signed
+        );
+
+        untagged["type"] = "0x2".into();
+        let deserialized: Transaction = serde_json::from_value(untagged.clone()).unwrap();
+        assert_eq!(deserialized.transaction_type(), TransactionType::DynamicFee);
+
+        untagged["type"] = "0x0".into();
+        untagged["maxFeePerGas"] = "0x14".into();
+        assert!(serde_json::from_value::<Transaction>(untagged).is_err());
+    }
+
+    #[test]
+    fn it_caps_the_cost_of_a_transaction() {
+        let mut transaction = new_transaction();
+        transaction.gas = U256::from(21_000);
+        transaction.gas_price = U256::from(2);
+        assert_eq!(transaction.max_cost(), Some(U256::from(42_001)));
+
+        transaction.value = U256::MAX;
+        assert_eq!(transaction.max_cost(), None);
+    }
+
+    #[test]
+    fn it_builds_a_transaction_to_sign_from_a_request() {
+        let (key, _) = keypair();
+        let from = private_key_address(&key);
+        let transaction_request = TransactionRequest {
+            from: Some(from),
+            to: Some(Account::random()),
+            gas: U256::from(21_000),
+            gas_price: U256::from(2),
+            value: Some(U256::from(5)),
+            chain_id: Some(U64::from(1337)),
+            ..Default::default()
+        };
+
+        let mut transaction =
+            Transaction::from_request(transaction_request.clone(), U256::from(7)).unwrap();
+        assert_eq!(transaction.hash, None);
+        assert_eq!(
+            (transaction.nonce, transaction.gas, transaction.gas_price),
+            (Some(U256::from(7)), U256::from(21_000), U256::from(2))
+        );
+
+        transaction.hash().unwrap();
+        let signed_transaction = transaction.sign(key).unwrap();
+        assert!(signed_transaction.verify().is_ok());
+        assert_eq!(
+            TryInto::<Transaction>::try_into(signed_transaction).unwrap(),
+            transaction
+        );
+
+        let missing = |transaction_request| match Transaction::from_request(
+            transaction_request,
+            U256::zero(),
+        ) {
+            Err(TypeError::InvalidTransaction(reason)) => reason,
+            other => panic!("expected a missing field, got {:?}", other),
+        };
+        let without_gas = TransactionRequest {
+            gas: U256::zero(),
+            ..transaction_request.clone()
+        };
+        assert_eq!(missing(without_gas), "missing gas");
+        let without_to = TransactionRequest {
+            to: None,
+            ..transaction_request.clone()
+        };
+        assert!(missing(without_to).starts_with("missing to"));
+        let without_from = TransactionRequest {
+            from: None,
+            ..transaction_request
+        };
+        assert_eq!(missing(without_from), "missing from");
+    }
+
+    #[test]
+    fn it_filters_logs_by_block_hash() {
+        let block_hash = H256::from_low_u64_be(1);
+        let filter = LogFilter {
+            block_hash: Some(block_hash),
+            ..Default::default()
+        };
+
+        assert!(filter.validate().is_ok());
+        assert_eq!(
+            serde_json::to_value(&filter).unwrap(),
+            serde_json::json!({ "blockHash": block_hash })
+        );
+
+        let filter = LogFilter {
+            from_block: Some(BlockTag::Latest),
+            ..filter
+        };
+        assert!(matches!(
+            filter.validate(),
+            Err(TypeError::InvalidLogFilter(_))
+        ));
+    }
+
+    #[test]
+    fn it_parses_a_log_from_a_node() {
+        let fetched = serde_json::json!({
+            "address": "0x0000000000000000000000000000000000000001",
+            "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+            "blockNumber": "0x3",
+            "data": "0x000000000000000000000000000000000000000000000000000000000000000a",
+            "logIndex": "0x0",
+            "removed": false,
+            "topics": ["0x0000000000000000000000000000000000000000000000000000000000000004"],
+            "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000005",
+            "transactionIndex": "0x1"
+        });
+        let log: Log = serde_json::from_value(fetched.clone()).unwrap();
+
+        assert_eq!(log.block_number, Some(U64::from(3)));
+        assert_eq!(log.data.len(), 32);
+        assert_eq!(log.data[31], 0x0a);
+        assert_eq!(log.transaction_index, Some(U64::one()));
+
+        // logs serialize as nodes send them, so they round-trip
+        let serialized = serde_json::to_value(&log).unwrap();
+        assert_eq!(serialized["data"], fetched["data"]);
+        assert_eq!(serialized["logIndex"], fetched["logIndex"]);
+        assert_eq!(serde_json::from_value::<Log>(serialized).unwrap(), log);
+    }
+
+    #[test]
+    fn it_matches_logs_by_address_and_topic_position() {
+        let (contract, other) = (Account::random(), Account::random());
+        let (transfer, approval) = (H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+        let owner = H256::from_low_u64_be(3);
+        let log = Log {
+            address: contract,
+            block_hash: None,
+            block_number: None,
+            data: Bytes::new(),
+            log_index: None,
+            log_type: None,
+            removed: None,
+            topics: vec![transfer, owner],
+            transaction_hash: None,
+            transaction_index: None,
+            transaction_log_index: None,
+        };
+        let filter = |address: Option<OneOrMany<Address>>, topics| LogFilter {
+            address,
+            topics: Some(topics),
+            ..Default::default()
+        };
+
+        assert!(LogFilter::default().matches(&log));
+        assert!(filter(Some(vec![other, contract].into()), vec![]).matches(&log));
+        assert!(!filter(Some(other.into()), vec![]).matches(&log));
+
+        // a missing position matches anything, several topics match any of them
+        assert!(filter(None, vec![None, Some(owner.into())]).matches(&log));
+        assert!(filter(None, vec![Some(vec![approval, transfer].into())]).matches(&log));
+        assert!(!filter(None, vec![Some(approval.into())]).matches(&log));
+        assert!(!filter(None, vec![None, None, Some(owner.into())]).matches(&log));
+
+        assert_eq!(
+            serde_json::to_value(filter(
+                Some(contract.into()),
+                vec![None, Some(owner.into())]
+            ))
+            .unwrap(),
+            serde_json::json!({ "address": contract, "topics": [null, owner] })
+        );
+    }
+
     #[test]
     fn root_hash() {
         let transaction_1 = new_transaction();
         let transaction_2 = new_transaction();
-        let root = Transaction::root_hash(&vec![transaction_1, transaction_2]).unwrap();
+        let root = Transaction::root_hash(&[transaction_1, transaction_2]).unwrap();
         let expected =
-            H256::from_str("0xa3b8c35bab6501806ed681220afe26a0d46774a6aa56d044b0f6aef0f3f0d682")
+            H256::from_str("0x518363d576c5a0a19e94153b51d15784479119f7ef2baf9e626bf5e231f63772")
                 .unwrap();
         assert_eq!(root, expected);
     }
+
+    #[test]
+    fn receipts_root_hash() {
+        let receipt = TransactionReceipt {
+            block_hash: None,
+            block_number: None,
+            contract_address: None,
+            from: H160::repeat_byte(3),
+            to: Some(H160::repeat_byte(4)),
+            transaction_hash: H256::repeat_byte(1),
+            transaction_index: None,
+            gas_used: U256::from(21_000),
+            status: U64::one(),
+        };
+        let root = TransactionReceipt::root_hash(std::slice::from_ref(&receipt)).unwrap();
+
+        // identical receipts produce identical roots
+        assert_eq!(
+            TransactionReceipt::root_hash(std::slice::from_ref(&receipt)).unwrap(),
+            root
+        );
+
+        // the block the receipt ends up in doesn't change the root
+        let mined = TransactionReceipt {
+            block_hash: Some(H256::repeat_byte(2)),
+            block_number: Some(BlockNumber(U64::one())),
+            transaction_index: Some(U64::zero()),
+            ..receipt.clone()
+        };
+        assert_eq!(TransactionReceipt::root_hash(&[mined]).unwrap(), root);
+
+        // neither do the sender and recipient, which come from the transaction
+        let addressed = TransactionReceipt {
+            from: H160::repeat_byte(5),
+            to: None,
+            ..receipt.clone()
+        };
+        assert_eq!(TransactionReceipt::root_hash(&[addressed]).unwrap(), root);
+
+        let failed = TransactionReceipt {
+            status: U64::zero(),
+            ..receipt
+        };
+        assert_ne!(TransactionReceipt::root_hash(&[failed]).unwrap(), root);
+    }
+
+    fn assert_envelope_round_trips(transaction: Transaction) {
+        let (secret_key, public_key) = keypair();
+        let mut transaction = transaction;
+        transaction.from = public_key_address(&public_key);
+        transaction.nonce = Some(U256::one());
+        transaction.hash = None;
+        transaction.hash().unwrap();
+
+        let signed = transaction.sign(secret_key).unwrap();
+        let envelope = signed.to_envelope().unwrap();
+        let decoded = SignedTransaction::from_envelope(&envelope).unwrap();
+        let decoded_transaction: Transaction = decoded.clone().try_into().unwrap();
+
+        assert_eq!(decoded_transaction, transaction);
+        assert_eq!(decoded, signed);
+        assert_eq!(decoded.to_envelope().unwrap(), envelope);
+    }
+
+    #[test]
+    fn it_round_trips_a_legacy_envelope() {
+        let transaction = new_transaction();
+        let envelope = transaction
+            .sign(keypair().0)
+            .unwrap()
+            .to_envelope()
+            .unwrap();
+
+        // a bare RLP list
+        assert!(envelope[0] >= 0xc0);
+        assert_envelope_round_trips(transaction);
+    }
+
+    #[test]
+    fn it_round_trips_an_access_list_envelope() {
+        let mut transaction = new_transaction();
+        transaction.chain_id = Some(U64::from(1));
+        transaction.access_list = Some(vec![AccessListItem {
+            address: transaction.to.unwrap(),
+            storage_keys: vec![H256::from_low_u64_be(1)],
+        }]);
+        let envelope = transaction
+            .sign(keypair().0)
+            .unwrap()
+            .to_envelope()
+            .unwrap();
+
+        assert_eq!(envelope[0], TransactionType::AccessList as u8);
+        assert_envelope_round_trips(transaction);
+    }
+
+    #[test]
+    fn it_round_trips_a_dynamic_fee_envelope() {
+        let mut transaction = new_transaction();
+        transaction.chain_id = Some(U64::from(1));
+        transaction.data = Some(Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]));
+        transaction.max_priority_fee_per_gas = Some(U256::from(1));
+        transaction.max_fee_per_gas = Some(U256::from(20));
+        transaction.gas_price = U256::from(20);
+        let envelope = transaction
+            .sign(keypair().0)
+            .unwrap()
+            .to_envelope()
+            .unwrap();
+
+        assert_eq!(envelope[0], TransactionType::DynamicFee as u8);
+        assert_envelope_round_trips(transaction);
+    }
+
+    #[test]
+    fn it_round_trips_a_replay_protected_legacy_envelope() {
+        let mut transaction = new_transaction();
+        transaction.chain_id = Some(U64::from(1337));
+        let envelope = transaction
+            .sign(keypair().0)
+            .unwrap()
+            .to_envelope()
+            .unwrap();
+
+        assert!(envelope[0] >= 0xc0);
+        assert_envelope_round_trips(transaction);
+    }
+
+    #[test]
+    fn it_rejects_encoding_a_legacy_envelope_past_the_largest_chain_id() {
+        let mut transaction = new_transaction();
+        transaction.chain_id = Some(U64::from(MAX_LEGACY_CHAIN_ID));
+        assert!(transaction.sign(keypair().0).unwrap().to_envelope().is_ok());
+
+        transaction.chain_id = Some(U64::from(MAX_LEGACY_CHAIN_ID + 1));
+        let error = transaction
+            .sign(keypair().0)
+            .unwrap()
+            .to_envelope()
+            .unwrap_err();
+        assert!(matches!(error, TypeError::InvalidTransaction(_)));
+    }
+
+    #[test]
+    fn it_parses_a_raw_transaction() {
+        // the transaction of the EIP-155 example without replay protection,
+        // signed by the key 0x4646...46
+        let raw = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000801ba08383adc8b8ae116f918fb44ca7ff9dfd8012596a5c130c6246a2cc717ba41cdaa053ddfacf5bd4aa7e46d1575acf52636ea659b91f29e2fb91c75567a279738f38";
+        let transaction: Transaction = Transaction::from_raw_hex(raw).unwrap().try_into().unwrap();
+
+        assert_eq!(
+            transaction.from,
+            H160::from_str("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").unwrap()
+        );
+        assert_eq!(transaction.nonce, Some(U256::from(9)));
+        assert_eq!(transaction.value, U256::exp10(18));
+        assert_eq!(transaction.gas_price, U256::from(20_000_000_000_u64));
+        assert_eq!(transaction.chain_id, None);
+
+        // without the prefix
+        assert_eq!(
+            Transaction::from_raw_hex(&raw[2..]).unwrap(),
+            Transaction::from_raw_hex(raw).unwrap()
+        );
+        assert!(Transaction::from_raw_hex("0xnothex").is_err());
+    }
+
+    #[test]
+    fn it_parses_a_replay_protected_raw_transaction() {
+        // the example from EIP-155, signed by the key 0x4646...46
+        let raw = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+        let transaction: Transaction = Transaction::from_raw_hex(raw).unwrap().try_into().unwrap();
+
+        assert_eq!(
+            transaction.from,
+            H160::from_str("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").unwrap()
+        );
+        assert_eq!(transaction.nonce, Some(U256::from(9)));
+        assert_eq!(transaction.value, U256::exp10(18));
+        assert_eq!(transaction.gas_price, U256::from(20_000_000_000_u64));
+        assert_eq!(transaction.chain_id, Some(U64::from(1)));
+    }
+
+    #[test]
+    fn it_rejects_an_invalid_envelope() {
+        assert!(SignedTransaction::from_envelope(&[]).is_err());
+        assert!(SignedTransaction::from_envelope(&[0x03, 0xc0]).is_err());
+        assert!(SignedTransaction::from_envelope(&[0x02, 0xc0]).is_err());
+    }
 }
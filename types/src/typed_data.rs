@@ -0,0 +1,368 @@
+//! # Typed Data
+//!
+//! Hash and sign structured data as defined by EIP-712.
+//! The digest binds a message to its type definitions and to a domain (dapp name,
+//! chain id, verifying contract), so a signature can't be replayed elsewhere.
+//!
+//! see https://eips.ethereum.org/EIPS/eip-712
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use ethereum_types::{Address, H256, U256};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utils::crypto::{hash, recover, sign_hash, Signature};
+use utils::SecretKey;
+
+use crate::account::Account;
+use crate::error::{Result, TypeError};
+use crate::helpers::strip_0x;
+
+pub const DOMAIN_TYPE: &str = "EIP712Domain";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TypedDataField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+/// The payload of `eth_signTypedData_v4`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<TypedDataField>>,
+    pub primary_type: String,
+    pub domain: Value,
+    pub message: Value,
+}
+
+impl TypedData {
+    /// The hash that is signed: keccak256(0x1901 || domainSeparator || hashStruct(message))
+    pub fn digest(&self) -> Result<H256> {
+        let mut payload = vec![0x19, 0x01];
+        payload.extend_from_slice(self.domain_separator()?.as_bytes());
+        payload.extend_from_slice(
+            self.hash_struct(&self.primary_type, &self.message)?
+                .as_bytes(),
+        );
+
+        Ok(hash(&payload).into())
+    }
+
+    pub fn domain_separator(&self) -> Result<H256> {
+        self.hash_struct(DOMAIN_TYPE, &self.domain)
+    }
+
+    /// Encode a struct type and the struct types it references, which are
+    /// appended in alphabetical order (e.g. `Mail(Person from)Person(string name)`).
+    pub fn encode_type(&self, type_name: &str) -> Result<String> {
+        let mut dependencies = BTreeSet::new();
+        self.dependencies(type_name, &mut dependencies);
+        dependencies.remove(type_name);
+
+        std::iter::once(type_name)
+            .chain(dependencies)
+            .map(|name| {
+                let fields = self
+                    .fields(name)?
+                    .iter()
+                    .map(|field| format!("{} {}", field.field_type, field.name))
+                    .collect::<Vec<_>>()
+                    .join(",");
+
+                Ok(format!("{}({})", name, fields))
+            })
+            .collect()
+    }
+
+    pub fn hash_struct(&self, type_name: &str, value: &Value) -> Result<H256> {
+        let mut encoded = hash(self.encode_type(type_name)?.as_bytes()).to_vec();
+
+        for field in self.fields(type_name)? {
+            let field_value = value.get(&field.name).ok_or_else(|| {
+                TypeError::InvalidTypedData(format!("{} is missing {}", type_name, field.name))
+            })?;
+            encoded.extend_from_slice(
+                self.encode_value(&field.field_type, field_value)?
+                    .as_bytes(),
+            );
+        }
+
+        Ok(hash(&encoded).into())
+    }
+
+    fn fields(&self, type_name: &str) -> Result<&Vec<TypedDataField>> {
+        self.types
+            .get(type_name)
+            .ok_or_else(|| TypeError::InvalidTypedData(format!("unknown type {}", type_name)))
+    }
+
+    fn dependencies<'a>(&'a self, type_name: &str, found: &mut BTreeSet<&'a str>) {
+        let Some((name, fields)) = self.types.get_key_value(type_name) else {
+            return;
+        };
+
+        if found.insert(name.as_str()) {
+            fields
+                .iter()
+                .for_each(|field| self.dependencies(base_type(&field.field_type), found));
+        }
+    }
+
+    /// Encode a value as a single 32 byte word.
+    /// Dynamic values (strings, bytes, arrays and structs) are hashed.
+    fn encode_value(&self, field_type: &str, value: &Value) -> Result<H256> {
+        if let Some(item_type) = array_item_type(field_type) {
+            let items = value
+                .as_array()
+                .ok_or_else(|| invalid_value(field_type, value))?;
+            let encoded = items
+                .iter()
+                .map(|item| Ok(self.encode_value(item_type, item)?.to_fixed_bytes()))
+                .collect::<Result<Vec<_>>>()?
+                .concat();
+
+            return Ok(hash(&encoded).into());
+        }
+
+        if self.types.contains_key(field_type) {
+            return self.hash_struct(field_type, value);
+        }
+
+        match field_type {
+            "string" => {
+                let string = value
+                    .as_str()
+                    .ok_or_else(|| invalid_value(field_type, value))?;
+
+                Ok(hash(string.as_bytes()).into())
+            }
+            "bytes" => Ok(hash(&parse_bytes(field_type, value)?).into()),
+            "bool" => {
+                let boolean = value
+                    .as_bool()
+                    .ok_or_else(|| invalid_value(field_type, value))?;
+
+                Ok(word(U256::from(boolean as u8)))
+            }
+            "address" => {
+                let address = value
+                    .as_str()
+                    .and_then(|address| Address::from_str(address).ok())
+                    .ok_or_else(|| invalid_value(field_type, value))?;
+
+                Ok(address.into())
+            }
+            _ if field_type.starts_with("uint") => Ok(word(parse_uint(field_type, value)?)),
+            _ if field_type.starts_with("int") => Ok(word(parse_int(field_type, value)?)),
+            _ if field_type.starts_with("bytes") => {
+                let bytes = parse_bytes(field_type, value)?;
+
+                if bytes.len() > 32 {
+                    return Err(invalid_value(field_type, value));
+                }
+
+                Ok(H256::from_slice(&[bytes, vec![0; 32]].concat()[..32]))
+            }
+            _ => Err(TypeError::InvalidTypedData(format!(
+                "unknown type {}",
+                field_type
+            ))),
+        }
+    }
+}
+
+/// Sign the EIP-712 digest of typed data
+pub fn sign_typed_data(data: &TypedData, key: &SecretKey) -> Result<Signature> {
+    Ok(sign_hash(data.digest()?, key))
+}
+
+/// Recover the signer of typed data and check it is the expected account.
+/// Accepts both a raw recovery id and the 27/28 `v` that wallets return.
+pub fn verify_typed_data(
+    data: &TypedData,
+    signature: &Signature,
+    expected: Account,
+) -> Result<bool> {
+    let signature = Signature {
+        v: signature.v % 27,
+        ..*signature
+    };
+    let signer = recover(data.digest()?, &signature)?;
+
+    Ok(signer == expected)
+}
+
+/// Strip any array suffixes (e.g. `Person[][2]` -> `Person`)
+fn base_type(field_type: &str) -> &str {
+    field_type.split('[').next().unwrap_or(field_type)
+}
+
+/// The type of an array's items (e.g. `Person[2]` -> `Person`)
+fn array_item_type(field_type: &str) -> Option<&str> {
+    field_type
+        .strip_suffix(']')
+        .and_then(|field_type| field_type.rsplit_once('['))
+        .map(|(item_type, _)| item_type)
+}
+
+fn word(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+
+    H256(bytes)
+}
+
+fn parse_bytes(field_type: &str, value: &Value) -> Result<Vec<u8>> {
+    value
+        .as_str()
+        .and_then(|bytes| hex::decode(strip_0x(bytes)).ok())
+        .ok_or_else(|| invalid_value(field_type, value))
+}
+
+fn parse_uint(field_type: &str, value: &Value) -> Result<U256> {
+    match value {
+        Value::Number(number) => number.as_u64().map(U256::from),
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => U256::from_str(hex).ok(),
+            None => U256::from_dec_str(string).ok(),
+        },
+        _ => None,
+    }
+    .ok_or_else(|| invalid_value(field_type, value))
+}
+
+/// Signed integers are encoded as 256 bit two's complement
+fn parse_int(field_type: &str, value: &Value) -> Result<U256> {
+    let int = match value {
+        Value::Number(number) => number.as_i64().map(i128::from),
+        Value::String(string) if !string.starts_with("0x") => string.parse::<i128>().ok(),
+        _ => return parse_uint(field_type, value),
+    }
+    .ok_or_else(|| invalid_value(field_type, value))?;
+
+    let magnitude = U256::from(int.unsigned_abs());
+
+    if int.is_negative() {
+        return Ok(U256::zero().overflowing_sub(magnitude).0);
+    }
+
+    Ok(magnitude)
+}
+
+fn invalid_value(field_type: &str, value: &Value) -> TypeError {
+    TypeError::InvalidTypedData(format!("invalid {}: {}", field_type, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::crypto::dev_account;
+
+    // the example from EIP-712
+    fn mail() -> TypedData {
+        serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallet", "type": "address" }
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person" },
+                    { "name": "contents", "type": "string" }
+                ]
+            },
+            "primaryType": "Mail",
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+            },
+            "message": {
+                "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+                "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+                "contents": "Hello, Bob!"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_hashes_typed_data() {
+        let typed_data = mail();
+
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            typed_data.domain_separator().unwrap(),
+            H256::from_str("0xf2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+                .unwrap()
+        );
+        assert_eq!(
+            typed_data.digest().unwrap(),
+            H256::from_str("0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_verifies_signed_typed_data() {
+        let (private_key, address) = dev_account(0);
+        let typed_data = mail();
+        let signature = sign_typed_data(&typed_data, &private_key).unwrap();
+
+        assert!(verify_typed_data(&typed_data, &signature, address).unwrap());
+        assert!(!verify_typed_data(&typed_data, &signature, dev_account(1).1).unwrap());
+    }
+
+    #[test]
+    fn it_rejects_a_tampered_message() {
+        let (private_key, address) = dev_account(0);
+        let mut typed_data = mail();
+        let signature = sign_typed_data(&typed_data, &private_key).unwrap();
+
+        typed_data.message["contents"] = Value::from("Goodbye, Bob!");
+        assert!(!verify_typed_data(&typed_data, &signature, address).unwrap());
+    }
+
+    #[test]
+    fn it_verifies_a_wallet_signature() {
+        // the signature from EIP-712, signed by keccak256("cow")
+        let signature = Signature {
+            v: 28,
+            r: H256::from_str("0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d")
+                .unwrap(),
+            s: H256::from_str("0x07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562")
+                .unwrap(),
+        };
+        let cow = Address::from_str("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826").unwrap();
+
+        assert!(verify_typed_data(&mail(), &signature, cow).unwrap());
+    }
+
+    #[test]
+    fn it_encodes_signed_integers_as_twos_complement() {
+        assert_eq!(
+            parse_int("int256", &Value::from(-1)).unwrap(),
+            U256::max_value()
+        );
+        assert_eq!(
+            parse_int("int256", &Value::from("-2")).unwrap(),
+            U256::max_value() - 1
+        );
+    }
+}
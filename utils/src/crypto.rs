@@ -1,18 +1,37 @@
 use crate::error::{Result, UtilsError};
 use ethereum_types::{Address, H160, H256, U256};
 use lazy_static::lazy_static;
+use rand::RngCore;
+use rayon::prelude::*;
 use rlp::{Encodable, RlpStream};
 pub use secp256k1::{
     ecdsa::{RecoverableSignature, RecoveryId, Signature as EcdsaSignature},
     generate_keypair, rand, All, Message, PublicKey, Secp256k1, SecretKey,
 };
 use sha3::{Digest, Keccak256};
+use std::str::FromStr;
 
 // reuse context throughout
 lazy_static! {
     pub(crate) static ref CONTEXT: Secp256k1<All> = Secp256k1::new();
 }
 
+/// Well-known development private keys (the default Hardhat/Anvil accounts).
+/// These are public knowledge, so never use them outside of development.
+pub const DEV_PRIVATE_KEYS: [&str; 10] = [
+    "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+    "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a",
+    "7c852118294e51e653712a81e05800f419141751be58f605c371e15141b007a6",
+    "47e179ec197488593b187f80a00eb0da91f1b9d0b13f8733639f19c30a34926a",
+    "8b3a350cf5c34c9194ca85829a2df0ec3153be0318b5e2d3348e872092edffba",
+    "92db14e403b83dfe3df233f83dfa3a0d7096f21ca9b0d6d6b8d88b2b4ec1564e",
+    "4bbbf85ce3377467afe5d46f804f221813b2bb87f24d81f60f1fcdbf7cbf4356",
+    "dbda1821b80551c9d65939329250298aa3472ba22feea921c0cf5d620ea67b97",
+    "2a871d0798f97d79848a013d4936a73bf4cc922c825d33c1cf7073dff6d409c6",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Signature {
     pub v: u64,
     pub r: H256,
@@ -85,6 +104,69 @@ pub fn keypair() -> (SecretKey, PublicKey) {
     generate_keypair(&mut rand::thread_rng())
 }
 
+/// Generate secret keys and derive their public key and address.
+/// Implemented as a trait since `SecretKey` is defined by `secp256k1`.
+pub trait SecretKeyExt: Sized {
+    /// Generate a key from the operating system's secure random number generator
+    ///
+    /// ```rust
+    /// use utils::crypto::SecretKeyExt;
+    /// use utils::SecretKey;
+    ///
+    /// let key = SecretKey::generate();
+    /// let address = key.address();
+    /// ```
+    fn generate() -> Self {
+        Self::generate_from(&mut rand::rngs::OsRng)
+    }
+
+    /// Generate a key from the given source of entropy (e.g. a seeded rng in tests).
+    /// Draws outside of the valid range of keys are discarded.
+    fn generate_from(rng: &mut impl RngCore) -> Self;
+
+    fn to_public_key(&self) -> PublicKey;
+
+    fn address(&self) -> Address;
+}
+
+impl SecretKeyExt for SecretKey {
+    fn generate_from(rng: &mut impl RngCore) -> Self {
+        let mut bytes = [0u8; 32];
+
+        loop {
+            rng.fill_bytes(&mut bytes);
+
+            // zero, or not less than the order of the curve (odds of about 2^-128)
+            if let Ok(key) = SecretKey::from_slice(&bytes) {
+                return key;
+            }
+        }
+    }
+
+    fn to_public_key(&self) -> PublicKey {
+        self.public_key(&CONTEXT)
+    }
+
+    fn address(&self) -> Address {
+        private_key_address(self)
+    }
+}
+
+/// A deterministic development account with a fixed, well-known key.
+/// Panics if `index` is not less than `DEV_PRIVATE_KEYS.len()`.
+///
+/// ```rust
+/// use utils::crypto::dev_account;
+///
+/// let (private_key, address) = dev_account(0);
+/// assert_eq!(format!("{:?}", address), "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+/// ```
+pub fn dev_account(index: usize) -> (SecretKey, Address) {
+    let key = SecretKey::from_str(DEV_PRIVATE_KEYS[index]).expect("valid development key");
+
+    (key, private_key_address(&key))
+}
+
 /// Convert a public key into an address using the last 20 bytes of the hash
 ///
 /// ```rust
@@ -164,8 +246,67 @@ pub fn sign(message: &[u8], key: &SecretKey) -> Result<EcdsaSignature> {
 /// let signature = sign_recovery(message, &private_key);
 /// ```
 pub fn sign_recovery(message: &[u8], key: &SecretKey) -> Result<RecoverableSignature> {
-    let message = hash_message(message)?;
-    Ok(CONTEXT.sign_ecdsa_recoverable(&message, key))
+    sign_hash(hash(message).into(), key).try_into()
+}
+
+/// Sign a 32 byte hash with a private key, producing a recoverable signature.
+/// All higher level signing (transactions, authorizations, messages) builds on this.
+///
+/// ```rust
+/// use ethereum_types::H256;
+/// use utils::crypto::{keypair, sign_hash};
+///
+/// let (private_key, public_key) = keypair();
+/// let signature = sign_hash(H256::random(), &private_key);
+/// ```
+pub fn sign_hash(hash: H256, key: &SecretKey) -> Signature {
+    let message = hash_to_message(hash);
+
+    CONTEXT.sign_ecdsa_recoverable(&message, key).into()
+}
+
+/// Recover the address that signed a 32 byte hash
+///
+/// ```rust
+/// use ethereum_types::H256;
+/// use utils::crypto::{keypair, private_key_address, recover, sign_hash};
+///
+/// let (private_key, public_key) = keypair();
+/// let hash = H256::random();
+/// let signature = sign_hash(hash, &private_key);
+/// let address = recover(hash, &signature).unwrap();
+/// assert_eq!(address, private_key_address(&private_key));
+/// ```
+pub fn recover(hash: H256, signature: &Signature) -> Result<Address> {
+    let message = hash_to_message(hash);
+    let recoverable_signature: RecoverableSignature = (*signature).try_into()?;
+    let public_key = CONTEXT
+        .recover_ecdsa(&message, &recoverable_signature)
+        .map_err(|e| UtilsError::RecoverError(e.to_string()))?;
+
+    Ok(public_key_address(&public_key))
+}
+
+/// Check that each hash was signed by its address.
+/// Signers are recovered in parallel, sharing the secp256k1 context.
+///
+/// ```rust
+/// use ethereum_types::H256;
+/// use utils::crypto::{dev_account, sign_hash, verify_batch};
+///
+/// let (private_key, address) = dev_account(0);
+/// let hash = H256::random();
+/// let signature = sign_hash(hash, &private_key);
+/// let items = [(hash, signature, address), (H256::random(), signature, address)];
+/// assert_eq!(verify_batch(&items), vec![true, false]);
+/// ```
+pub fn verify_batch(items: &[(H256, Signature, Address)]) -> Vec<bool> {
+    items
+        .par_iter()
+        .map(|(hash, signature, address)| {
+            recover(*hash, signature).is_ok_and(|signer| signer == *address)
+        })
+        .collect()
 }
 
 /// Verify that a message was signed using a public key
@@ -237,8 +378,13 @@ pub fn recover_address(message: &[u8], signature: &[u8], recovery_id: i32) -> Re
 
 // Helper function to hash bytes and convert to a Message
 pub fn hash_message(message: &[u8]) -> Result<Message> {
-    let hashed = hash(message);
-    Message::from_slice(&hashed).map_err(|e| UtilsError::CreateMessage(e.to_string()))
+    Ok(hash_to_message(hash(message).into()))
+}
+
+/// The message signed for a hash, shared by every signing and recovery path
+fn hash_to_message(hash: H256) -> Message {
+    // a H256 is always 32 bytes, so this cannot fail
+    Message::from_slice(hash.as_bytes()).expect("32 byte message")
 }
 
 /// Encode items in a RlpStream
@@ -280,6 +426,69 @@ mod tests {
         assert_eq!(private_key_address, public_key_address);
     }
 
+    #[test]
+    fn dev_accounts_are_stable() {
+        let (secret_key, address) = dev_account(0);
+        let expected = Address::from_str("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap();
+        assert_eq!(address, expected);
+        assert_eq!(dev_account(0).1, address);
+
+        let hash = H256::random();
+        let signature = sign_hash(hash, &secret_key);
+        assert_eq!(recover(hash, &signature).unwrap(), address);
+
+        let addresses = (0..DEV_PRIVATE_KEYS.len())
+            .map(|index| dev_account(index).1)
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(addresses.len(), DEV_PRIVATE_KEYS.len());
+    }
+
+    /// An rng that hands out the given draws in order
+    struct Draws(Vec<[u8; 32]>);
+
+    impl RngCore for Draws {
+        fn next_u32(&mut self) -> u32 {
+            let mut bytes = [0; 4];
+            self.fill_bytes(&mut bytes);
+            u32::from_le_bytes(bytes)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let mut bytes = [0; 8];
+            self.fill_bytes(&mut bytes);
+            u64::from_le_bytes(bytes)
+        }
+
+        /// Fill `dest` from the start of the next draw
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            let draw = self.0.remove(0);
+            dest.copy_from_slice(&draw[..dest.len()]);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_generates_a_secret_key() {
+        let key = SecretKey::generate();
+        assert_eq!(key.address(), public_key_address(&key.to_public_key()));
+        assert_ne!(key.address(), H160::zero());
+        assert_ne!(SecretKey::generate(), key);
+
+        // zero and the order of the curve are out of range
+        let order =
+            H256::from_str("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+                .unwrap()
+                .0;
+        let mut draws = Draws(vec![[0; 32], order, [1; 32]]);
+        let key = SecretKey::generate_from(&mut draws);
+        assert_eq!(key.secret_bytes(), [1; 32]);
+        assert!(draws.0.is_empty());
+    }
+
     #[test]
     fn it_hashes() {
         let message = b"The message";
@@ -309,6 +518,48 @@ mod tests {
         assert_eq!(recovered_address, public_key_address(&public_key));
     }
 
+    #[test]
+    fn it_signs_and_recovers_hashes() {
+        let (secret_key, _) = keypair();
+        let address = private_key_address(&secret_key);
+
+        for _ in 0..10 {
+            let hash = H256::random();
+            let signature = sign_hash(hash, &secret_key);
+
+            assert_eq!(recover(hash, &signature).unwrap(), address);
+            assert_ne!(recover(H256::random(), &signature).ok(), Some(address));
+        }
+    }
+
+    #[test]
+    fn it_verifies_a_batch() {
+        let items = (0..10)
+            .map(|index| {
+                let (private_key, address) = dev_account(index);
+                let hash = H256::random();
+                let signature = sign_hash(hash, &private_key);
+
+                match index % 3 {
+                    // signed by another account
+                    0 => (hash, signature, dev_account((index + 1) % 10).1),
+                    // a different hash
+                    1 => (H256::random(), signature, address),
+                    _ => (hash, signature, address),
+                }
+            })
+            .collect::<Vec<_>>();
+        let expected = (0..10).map(|index| index % 3 == 2).collect::<Vec<_>>();
+        let single = items
+            .iter()
+            .map(|(hash, signature, address)| recover(*hash, signature).ok() == Some(*address))
+            .collect::<Vec<_>>();
+
+        assert_eq!(verify_batch(&items), expected);
+        assert_eq!(single, expected);
+        assert!(verify_batch(&[]).is_empty());
+    }
+
     #[test]
     fn it_verifies() {
         let (secret_key, public_key) = keypair();
@@ -1,4 +1,4 @@
-pub use rlp::{Encodable, RlpStream};
+pub use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
 pub use secp256k1::{
     ecdsa::{RecoverableSignature, RecoveryId, Signature as EcdsaSignature},
     generate_keypair, rand, All, Message, PublicKey, Secp256k1, SecretKey,
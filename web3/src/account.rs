@@ -6,10 +6,12 @@
 
 ////////////////////////////////////////////////////////////////////////////////
 
+use std::future::Future;
+
 use ethereum_types::U256;
 use jsonrpsee::rpc_params;
 use types::account::Account;
-use types::block::BlockNumber;
+use types::block::{BlockId, BlockTag};
 use types::helpers::to_hex;
 use types::transaction::{SignedTransaction, Transaction};
 use utils::crypto::SecretKey;
@@ -17,7 +19,83 @@ use utils::crypto::SecretKey;
 use crate::error::{Result, Web3Error};
 use crate::Web3;
 
+/// The balance, nonce and code of an account at a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountState {
+    pub balance: U256,
+    pub nonce: U256,
+    pub code: Vec<u8>,
+    pub is_contract: bool,
+}
+
+/// An account given by its address or by an alias registered with
+/// `Web3::set_alias`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressOrAlias {
+    Address(Account),
+    Alias(String),
+}
+
+impl From<Account> for AddressOrAlias {
+    fn from(address: Account) -> Self {
+        AddressOrAlias::Address(address)
+    }
+}
+
+impl From<&Account> for AddressOrAlias {
+    fn from(address: &Account) -> Self {
+        AddressOrAlias::Address(*address)
+    }
+}
+
+impl From<&str> for AddressOrAlias {
+    fn from(alias: &str) -> Self {
+        AddressOrAlias::Alias(alias.into())
+    }
+}
+
+impl From<String> for AddressOrAlias {
+    fn from(alias: String) -> Self {
+        AddressOrAlias::Alias(alias)
+    }
+}
+
 impl Web3 {
+    /// Register a human-readable alias for an address on this client, and
+    /// the clients cloned from it, replacing any address it already had.
+    /// Aliases are local to the client, not names resolved on chain.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// web3.set_alias("treasury", account).unwrap();
+    /// let balance = web3.get_balance("treasury").await;
+    /// assert!(balance.is_ok());
+    /// ```
+    pub fn set_alias(&self, alias: &str, address: Account) -> Result<()> {
+        self.aliases
+            .write()
+            .map_err(|_| Web3Error::AliasesPoisoned)?
+            .insert(alias.into(), address);
+
+        Ok(())
+    }
+
+    /// The address of an account, looking up its alias if it has one
+    pub fn resolve(&self, account: impl Into<AddressOrAlias>) -> Result<Account> {
+        match account.into() {
+            AddressOrAlias::Address(address) => Ok(address),
+            AddressOrAlias::Alias(alias) => self
+                .aliases
+                .read()
+                .map_err(|_| Web3Error::AliasesPoisoned)?
+                .get(&alias)
+                .copied()
+                .ok_or(Web3Error::UnknownAlias(alias)),
+        }
+    }
+
     /// Retrieve the eth balance for an accout at the current block.
     ///
     /// See https://eth.wiki/json-rpc/API#eth_getBalance
@@ -30,8 +108,26 @@ impl Web3 {
     /// let balance = web3.get_balance(account).await;
     /// assert!(balance.is_ok());
     /// ```
-    pub async fn get_balance(&self, address: Account) -> Result<U256> {
-        let balance: U256 = self.get_balance_by_block(address, None).await?;
+    pub async fn get_balance(&self, address: impl Into<AddressOrAlias>) -> Result<U256> {
+        let balance: U256 = self.get_balance_by_block(address, BlockTag::Latest).await?;
+
+        Ok(balance)
+    }
+
+    /// Retrieve the eth balance for an account including its unmined transactions.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let account = web3.get_all_accounts().await.unwrap()[0];
+    /// let balance = web3.get_pending_balance(account).await;
+    /// assert!(balance.is_ok());
+    /// ```
+    pub async fn get_pending_balance(&self, address: impl Into<AddressOrAlias>) -> Result<U256> {
+        let balance: U256 = self
+            .get_balance_by_block(address, BlockTag::Pending)
+            .await?;
 
         Ok(balance)
     }
@@ -43,30 +139,118 @@ impl Web3 {
     /// # Examples
     ///
     /// ```ignore
-    /// use types::block::BlockNumber;
+    /// use types::block::{BlockNumber, BlockTag};
     /// let block = BlockNumber(0.into());
     /// let account = web3.get_all_accounts().await.unwrap()[0];
-    /// let balance = web3.get_balance_by_block(account, Some(block)).await;
+    /// let balance = web3.get_balance_by_block(account, block).await;
+    /// let pending = web3.get_balance_by_block(account, BlockTag::Pending).await;
     /// assert!(balance.is_ok());
     /// ```
     pub async fn get_balance_by_block(
         &self,
-        address: Account,
-        block_number: Option<BlockNumber>,
+        address: impl Into<AddressOrAlias>,
+        block: impl Into<BlockId>,
     ) -> Result<U256> {
-        let block_number = Web3::get_hex_blocknumber(block_number);
-        let params = rpc_params![to_hex(address), block_number];
+        let params = rpc_params![to_hex(self.resolve(address)?), block.into()];
         let response = self.send_rpc("eth_getBalanceByBlock", params).await?;
-        let balance: U256 = serde_json::from_value(response)?;
+        let balance: U256 = self.deserialize(response)?;
 
         Ok(balance)
     }
 
-    pub fn sign_transaction(
+    /// Retrieve the eth balances of several accounts at the current block in a
+    /// single batch request, in the order the accounts were given.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let accounts = web3.get_all_accounts().await.unwrap();
+    /// let balances = web3.get_balances(&accounts).await.unwrap();
+    /// assert_eq!(balances.len(), accounts.len());
+    /// ```
+    pub async fn get_balances(
+        &self,
+        addresses: impl IntoIterator<Item = impl Into<AddressOrAlias>>,
+    ) -> Result<Vec<U256>> {
+        let addresses = addresses
+            .into_iter()
+            .map(|address| self.resolve(address))
+            .collect::<Result<Vec<_>>>()?;
+        let mut batch = self.typed_batch();
+        let calls = addresses
+            .iter()
+            .map(|address| {
+                batch.add::<U256, _>(
+                    "eth_getBalanceByBlock",
+                    rpc_params![to_hex(address), BlockId::from(BlockTag::Latest)],
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut results = batch.send().await?;
+
+        calls.into_iter().map(|call| results.take(call)).collect()
+    }
+
+    /// Retrieve the eth balance for an account before and after awaiting an action.
+    ///
+    /// Returns `(before, after)`, which is useful for asserting balance deltas.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let (before, after) = web3
+    ///     .balance_delta(account, web3.send(transaction_request))
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn balance_delta<F, T>(
         &self,
-        transaction: Transaction,
+        address: impl Into<AddressOrAlias>,
+        action: F,
+    ) -> Result<(U256, U256)>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let address = self.resolve(address)?;
+        let before = self.get_balance(address).await?;
+        action.await?;
+        let after = self.get_balance(address).await?;
+
+        Ok((before, after))
+    }
+
+    /// Sign a transaction for the node's chain (see `chain_id`), so its `v`
+    /// follows EIP-155 and it can't be replayed on another chain.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let signed_transaction = web3.sign_transaction(transaction, key).await.unwrap();
+    /// ```
+    pub async fn sign_transaction(
+        &self,
+        mut transaction: Transaction,
         key: SecretKey,
     ) -> Result<SignedTransaction> {
+        let chain_id = self.chain_id().await?;
+
+        match transaction.chain_id {
+            Some(signed_for) if signed_for.as_u64() != chain_id => {
+                return Err(Web3Error::TransactionSigningError(format!(
+                    "{:?} is for chain {}, not {}",
+                    transaction.hash, signed_for, chain_id
+                )));
+            }
+            _ => transaction.chain_id = Some(chain_id.into()),
+        }
+
+        transaction
+            .hash()
+            .map_err(|e| Web3Error::TransactionSigningError(e.to_string()))?;
+
         let signed_transaction = transaction.sign(key).map_err(|e| {
             Web3Error::TransactionSigningError(format!("{:?} {}", transaction.hash, e))
         })?;
@@ -84,19 +268,99 @@ impl Web3 {
     /// let nonce = web3.get_transaction_count(account).await;
     /// assert!(nonce.is_ok());
     /// ```
-    pub async fn get_transaction_count(&self, address: Account) -> Result<U256> {
-        let params = rpc_params![to_hex(address)];
+    pub async fn get_transaction_count(&self, address: impl Into<AddressOrAlias>) -> Result<U256> {
+        let params = rpc_params![to_hex(self.resolve(address)?)];
         let response = self.send_rpc("eth_getTransactionCount", params).await?;
-        let balance: U256 = serde_json::from_value(response)?;
+        let balance: U256 = self.deserialize(response)?;
 
         Ok(balance)
     }
+
+    /// The number of an account's transactions waiting in the mempool, from
+    /// its pending and latest transaction counts in a single batch request.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let account = web3.get_all_accounts().await.unwrap()[0];
+    /// let pending = web3.pending_nonce_gap(account).await.unwrap();
+    /// println!("{pending} pending");
+    /// ```
+    pub async fn pending_nonce_gap(&self, account: impl Into<AddressOrAlias>) -> Result<u64> {
+        let address = to_hex(self.resolve(account)?);
+        let mut batch = self.typed_batch();
+        let pending = batch.add::<U256, _>(
+            "eth_getTransactionCount",
+            rpc_params![address.clone(), BlockTag::Pending],
+        )?;
+        let latest = batch.add::<U256, _>(
+            "eth_getTransactionCount",
+            rpc_params![address, BlockTag::Latest],
+        )?;
+
+        let mut results = batch.send().await?;
+        let gap = results.take(pending)?.saturating_sub(results.take(latest)?);
+
+        Ok(gap.low_u64())
+    }
+
+    /// Retrieve the balance, nonce and code of an account at a block in a
+    /// single batch request.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let account = web3.get_all_accounts().await.unwrap()[0];
+    /// let state = web3.get_account_state(account, BlockTag::Latest).await.unwrap();
+    /// assert!(!state.is_contract);
+    /// ```
+    pub async fn get_account_state(
+        &self,
+        address: impl Into<AddressOrAlias>,
+        block: impl Into<BlockId>,
+    ) -> Result<AccountState> {
+        let address = to_hex(self.resolve(address)?);
+        let block = block.into();
+        let mut batch = self.typed_batch();
+        let balance =
+            batch.add::<U256, _>("eth_getBalanceByBlock", rpc_params![address.clone(), block])?;
+        let nonce = batch.add::<U256, _>(
+            "eth_getTransactionCount",
+            rpc_params![address.clone(), block],
+        )?;
+        let code = batch.add::<Vec<u8>, _>("eth_getCode", rpc_params![address, block])?;
+
+        let mut results = batch.send().await?;
+        let code = results.take(code)?;
+
+        Ok(AccountState {
+            balance: results.take(balance)?,
+            nonce: results.take(nonce)?,
+            is_contract: !code.is_empty(),
+            code,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::helpers::tests::{web3, ACCOUNT_1};
+    use crate::helpers::tests::{mock_node, mock_node_with_logger, web3, ACCOUNT_1};
+    use crate::transaction::tests::send_transaction;
+    use ethereum_types::H256;
+    use jsonrpsee::server::logger::{HttpRequest, Logger, MethodKind, Params, TransportProtocol};
+    use jsonrpsee::RpcModule;
+    use serde_json::{json, Value};
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use types::block::BlockNumber;
+    use types::transaction::TransactionRequest;
 
     #[tokio::test]
     async fn it_gets_a_balance() {
@@ -105,6 +369,287 @@ mod tests {
         assert!(response.is_ok());
     }
 
+    #[tokio::test]
+    async fn it_gets_a_balance_of_an_owned_or_borrowed_account() {
+        let mut module = RpcModule::new(());
+        module
+            .register_method("eth_getBalanceByBlock", |_, _| Ok(U256::from(100)))
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = crate::Web3::new(&url).unwrap();
+        let account = Account::random();
+
+        assert_eq!(web3.get_balance(&account).await.unwrap(), U256::from(100));
+        assert_eq!(web3.get_balance(account).await.unwrap(), U256::from(100));
+    }
+
+    #[tokio::test]
+    async fn it_gets_a_balance_by_block_tag() {
+        let mut module = RpcModule::new(());
+        module
+            .register_method("eth_getBalanceByBlock", |params, _| {
+                let (_, block): (Account, BlockId) = params.parse()?;
+                let balance = match block {
+                    BlockId::Number(BlockTag::Earliest) => 0,
+                    BlockId::Number(BlockTag::Pending) => 90,
+                    _ => 100,
+                };
+
+                Ok(U256::from(balance))
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = crate::Web3::new(&url).unwrap();
+        let account = Account::random();
+
+        assert_eq!(web3.get_balance(account).await.unwrap(), U256::from(100));
+        assert_eq!(
+            web3.get_pending_balance(account).await.unwrap(),
+            U256::from(90)
+        );
+        assert_eq!(
+            web3.get_balance_by_block(account, BlockTag::Earliest)
+                .await
+                .unwrap(),
+            U256::zero()
+        );
+        assert_eq!(
+            web3.get_balance_by_block(account, None::<BlockNumber>)
+                .await
+                .unwrap(),
+            U256::from(100)
+        );
+    }
+
+    #[tokio::test]
+    async fn it_gets_a_balance_by_alias() {
+        let treasury = Account::random();
+        let mut module = RpcModule::new(treasury);
+        module
+            .register_method("eth_getBalanceByBlock", |params, treasury| {
+                let (account, _): (Account, BlockId) = params.parse()?;
+                let balance = if account == *treasury { 100 } else { 0 };
+
+                Ok(U256::from(balance))
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = crate::Web3::new(&url).unwrap();
+        web3.set_alias("treasury", treasury).unwrap();
+
+        assert_eq!(web3.get_balance("treasury").await.unwrap(), U256::from(100));
+        assert_eq!(web3.resolve("treasury").unwrap(), treasury);
+        assert!(matches!(
+            web3.get_balance("vault").await,
+            Err(Web3Error::UnknownAlias(alias)) if alias == "vault"
+        ));
+
+        // a panic while the address book is written leaves it poisoned
+        let aliases = web3.aliases.clone();
+        let _ = std::thread::spawn(move || {
+            let _aliases = aliases.write().unwrap();
+            panic!("poisoning the address book");
+        })
+        .join();
+        assert!(matches!(
+            web3.resolve("treasury"),
+            Err(Web3Error::AliasesPoisoned)
+        ));
+        assert!(matches!(
+            web3.set_alias("vault", treasury),
+            Err(Web3Error::AliasesPoisoned)
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_gets_balances_in_one_request() {
+        let requests = RequestCounter::default();
+        let mut module = RpcModule::new(());
+        module
+            .register_method("eth_getBalanceByBlock", |params, _| {
+                let (address, _): (Account, BlockId) = params.parse()?;
+                Ok(U256::from(address.to_low_u64_be()))
+            })
+            .unwrap();
+        let (url, _handle) = mock_node_with_logger(requests.clone(), module).await;
+        let web3 = crate::Web3::new(&url).unwrap();
+        let accounts = (1..=50).map(Account::from_low_u64_be).collect::<Vec<_>>();
+
+        let balances = web3.get_balances(&accounts).await.unwrap();
+        assert_eq!(requests.0.load(Ordering::SeqCst), 1);
+        assert_eq!(balances, (1..=50).map(U256::from).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn it_matches_balances_to_accounts_when_responses_are_out_of_order() {
+        // a bare HTTP server answering a batch in reverse, with each balance
+        // being the low bytes of the account
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![];
+            let mut buffer = [0; 4096];
+
+            let body = loop {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let request = String::from_utf8_lossy(&request);
+
+                if let Some((headers, body)) = request.split_once("\r\n\r\n") {
+                    let length = headers
+                        .lines()
+                        .find_map(|line| {
+                            line.to_lowercase()
+                                .strip_prefix("content-length: ")
+                                .map(String::from)
+                        })
+                        .unwrap()
+                        .parse::<usize>()
+                        .unwrap();
+
+                    if body.len() >= length {
+                        break body.to_string();
+                    }
+                }
+            };
+
+            let calls: Vec<Value> = serde_json::from_str(&body).unwrap();
+            let responses = calls
+                .iter()
+                .rev()
+                .map(|call| {
+                    let address: Account =
+                        serde_json::from_value(call["params"][0].clone()).unwrap();
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "result": U256::from(address.to_low_u64_be()),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let body = serde_json::to_string(&responses).unwrap();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let web3 = crate::Web3::new(&url).unwrap();
+        let accounts = (1..=3).map(Account::from_low_u64_be).collect::<Vec<_>>();
+
+        let balances = web3.get_balances(&accounts).await.unwrap();
+        assert_eq!(balances, vec![U256::from(1), U256::from(2), U256::from(3)]);
+    }
+
+    /// Counts the HTTP requests a server receives, rather than the calls in them
+    #[derive(Clone, Default)]
+    struct RequestCounter(Arc<AtomicU64>);
+
+    impl Logger for RequestCounter {
+        type Instant = ();
+
+        fn on_connect(&self, _: SocketAddr, _: &HttpRequest, _: TransportProtocol) {}
+
+        fn on_request(&self, _: TransportProtocol) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_call(&self, _: &str, _: Params, _: MethodKind, _: TransportProtocol) {}
+
+        fn on_result(&self, _: &str, _: bool, _: (), _: TransportProtocol) {}
+
+        fn on_response(&self, _: &str, _: (), _: TransportProtocol) {}
+
+        fn on_disconnect(&self, _: SocketAddr, _: TransportProtocol) {}
+    }
+
+    #[tokio::test]
+    async fn it_gets_the_state_of_an_account_in_one_request() {
+        let requests = RequestCounter::default();
+        let blocks = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut module = RpcModule::new(blocks.clone());
+        module
+            .register_method("eth_getBalanceByBlock", |params, blocks| {
+                let (_, block): (Account, BlockId) = params.parse()?;
+                blocks.lock().unwrap().push(block);
+                Ok(U256::from(100))
+            })
+            .unwrap();
+        module
+            .register_method("eth_getTransactionCount", |params, blocks| {
+                let (_, block): (Account, BlockId) = params.parse()?;
+                blocks.lock().unwrap().push(block);
+                Ok(U256::from(3))
+            })
+            .unwrap();
+        module
+            .register_method("eth_getCode", |params, blocks| {
+                let (_, block): (Account, BlockId) = params.parse()?;
+                blocks.lock().unwrap().push(block);
+                Ok(vec![0_u8, 97, 115, 109])
+            })
+            .unwrap();
+        let (url, _handle) = mock_node_with_logger(requests.clone(), module).await;
+        let web3 = crate::Web3::new(&url).unwrap();
+        let account = Account::random();
+        let block_hash = H256::random();
+
+        let state = web3.get_account_state(account, block_hash).await.unwrap();
+        assert_eq!(requests.0.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            state,
+            AccountState {
+                balance: U256::from(100),
+                nonce: U256::from(3),
+                code: vec![0, 97, 115, 109],
+                is_contract: true,
+            }
+        );
+        // every part of the state is of the requested block
+        assert_eq!(*blocks.lock().unwrap(), vec![BlockId::Hash(block_hash); 3]);
+    }
+
+    #[tokio::test]
+    async fn it_gets_the_pending_nonce_gap() {
+        let unmined = Arc::new(AtomicU64::new(0));
+        let mut module = RpcModule::new(unmined);
+        module
+            .register_method("eth_getTransactionCount", |params, unmined| {
+                let mut seq = params.sequence();
+                let _account = seq.next::<Value>()?;
+                let count = match seq.next::<String>()?.as_str() {
+                    "pending" => 3 + unmined.load(Ordering::SeqCst),
+                    _ => 3,
+                };
+                Ok(U256::from(count))
+            })
+            .unwrap();
+        module
+            .register_method("eth_sendTransaction", |_, unmined| {
+                unmined.fetch_add(1, Ordering::SeqCst);
+                Ok(H256::random())
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = crate::Web3::new(&url).unwrap();
+        let account = Account::random();
+
+        assert_eq!(web3.pending_nonce_gap(account).await.unwrap(), 0);
+
+        web3.send(TransactionRequest {
+            from: Some(account),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(web3.pending_nonce_gap(account).await.unwrap(), 1);
+    }
+
     #[tokio::test]
     async fn it_gets_a_balance_by_block() {
         // crate::transaction::tests::send_transaction().await.unwrap();
@@ -115,10 +660,99 @@ mod tests {
         assert!(response.is_ok());
     }
 
+    #[tokio::test]
+    async fn it_gets_a_pending_balance() {
+        let mut module = RpcModule::new(());
+        module
+            .register_method("eth_getBalanceByBlock", |params, _| {
+                let mut seq = params.sequence();
+                let _account = seq.next::<Value>()?;
+                let balance = match seq.next::<String>()?.as_str() {
+                    // a transfer of 10 is waiting in the mempool
+                    "pending" => 90,
+                    _ => 100,
+                };
+                Ok(U256::from(balance))
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = crate::Web3::new(&url).unwrap();
+        let account = Account::random();
+
+        assert_eq!(web3.get_balance(account).await.unwrap(), U256::from(100));
+        assert_eq!(
+            web3.get_pending_balance(account).await.unwrap(),
+            U256::from(90)
+        );
+        assert_eq!(
+            web3.get_balance_by_block(account, BlockTag::Pending)
+                .await
+                .unwrap(),
+            U256::from(90)
+        );
+    }
+
+    #[tokio::test]
+    async fn it_gets_a_balance_delta() {
+        let web3 = web3();
+        let account = *ACCOUNT_1;
+        let transfer = async {
+            let tx_hash = send_transaction().await?;
+
+            web3.wait_for_receipt_with_timeout(tx_hash, Duration::from_secs(10))
+                .await
+        };
+        let (before, after) = web3.balance_delta(account, transfer).await.unwrap();
+
+        assert!(after < before);
+    }
+
     #[tokio::test]
     async fn it_gets_a_transaction_count() {
         let account = *ACCOUNT_1;
         let response = web3().get_transaction_count(account).await;
         assert!(response.is_ok());
     }
+
+    #[tokio::test]
+    async fn it_signs_for_a_chain() {
+        let web3 = web3();
+        let (key, account) = utils::crypto::dev_account(0);
+        let transaction = Transaction::new(
+            account,
+            Some(Account::random()),
+            U256::from(10),
+            Some(U256::one()),
+            None,
+        )
+        .unwrap();
+        // the `v` of a legacy envelope follows its six transaction fields
+        let mut signed = vec![];
+        for chain_id in [1, 1337] {
+            web3.set_chain_id(Some(chain_id)).await;
+            let signed_transaction = web3
+                .sign_transaction(transaction.clone(), key)
+                .await
+                .unwrap();
+            let v: u64 = utils::Rlp::new(&signed_transaction.to_envelope().unwrap())
+                .val_at(6)
+                .unwrap();
+
+            signed.push((v, signed_transaction.transaction_hash));
+        }
+        let (mainnet_v, mainnet_hash) = signed[0];
+        let (local_v, local_hash) = signed[1];
+
+        assert!(mainnet_v == 37 || mainnet_v == 38);
+        assert!(local_v == 2709 || local_v == 2710);
+        assert_ne!(mainnet_hash, local_hash);
+
+        // a transaction already bound to one chain can't be signed for another
+        let mut bound = transaction;
+        bound.chain_id = Some(ethereum_types::U64::from(1));
+        assert!(matches!(
+            web3.sign_transaction(bound, key).await,
+            Err(Web3Error::TransactionSigningError(_))
+        ));
+    }
 }
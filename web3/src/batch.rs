@@ -0,0 +1,270 @@
+//! # Batches
+//!
+//! Send several JSON-RPC calls in a single HTTP request.
+//! A `TypedBatch` remembers the response type of each call it queues, so the
+//! results come back deserialized rather than as raw JSON values.
+//!
+//! see https://www.jsonrpc.org/specification#batch
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::params::BatchRequestBuilder;
+use jsonrpsee::core::traits::ToRpcParams;
+use log::*;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::{Result, Web3Error};
+use crate::{call_error, Web3};
+
+/// Deserializes the response of a single call into its expected type
+trait Decoder: Send {
+    fn decode(&self, web3: &Web3, response: Value) -> Result<Box<dyn Any + Send>>;
+}
+
+struct TypedDecoder<T>(PhantomData<fn() -> T>);
+
+impl<T: DeserializeOwned + Send + 'static> Decoder for TypedDecoder<T> {
+    fn decode(&self, web3: &Web3, response: Value) -> Result<Box<dyn Any + Send>> {
+        Ok(Box::new(web3.deserialize::<T>(response)?))
+    }
+}
+
+/// A handle to the result of a call queued in a `TypedBatch`
+#[derive(Debug)]
+pub struct BatchCall<T> {
+    id: usize,
+    response: PhantomData<fn() -> T>,
+}
+
+pub struct TypedBatch<'a> {
+    web3: &'a Web3,
+    requests: BatchRequestBuilder<'a>,
+    decoders: HashMap<usize, Box<dyn Decoder>>,
+}
+
+impl<'a> TypedBatch<'a> {
+    /// Queue a call, returning a handle to retrieve its result once the batch is sent.
+    pub fn add<T, Params>(&mut self, method: &'a str, params: Params) -> Result<BatchCall<T>>
+    where
+        T: DeserializeOwned + Send + 'static,
+        Params: ToRpcParams,
+    {
+        let id = self.decoders.len();
+
+        self.requests
+            .insert(method, params)
+            .map_err(|e| Web3Error::JsonParseError(e.to_string()))?;
+        self.decoders
+            .insert(id, Box::new(TypedDecoder::<T>(PhantomData)));
+
+        Ok(BatchCall {
+            id,
+            response: PhantomData,
+        })
+    }
+
+    pub async fn send(self) -> Result<BatchResults> {
+        let responses = self.web3.batch_responses(self.requests).await?;
+
+        decode_responses(self.web3, &self.decoders, responses)
+    }
+}
+
+/// The decoded results of a `TypedBatch`.
+/// Each call's result, including any error the node returned for it, is
+/// retrieved with the handle from `TypedBatch::add`.
+pub struct BatchResults {
+    results: HashMap<usize, Result<Box<dyn Any + Send>>>,
+}
+
+impl BatchResults {
+    pub fn take<T: 'static>(&mut self, call: BatchCall<T>) -> Result<T> {
+        let result = self.results.remove(&call.id).ok_or_else(|| {
+            Web3Error::RpcResponseError(format!("no response for call {} in batch", call.id))
+        })??;
+
+        result.downcast::<T>().map(|value| *value).map_err(|_| {
+            Web3Error::RpcResponseError(format!("unexpected type for call {} in batch", call.id))
+        })
+    }
+}
+
+fn decode_responses(
+    web3: &Web3,
+    decoders: &HashMap<usize, Box<dyn Decoder>>,
+    responses: Vec<Result<Value>>,
+) -> Result<BatchResults> {
+    let mut results = HashMap::new();
+
+    for (id, response) in responses.into_iter().enumerate() {
+        let decoder = decoders.get(&id).ok_or_else(|| {
+            Web3Error::RpcResponseError(format!("unexpected response {} in batch", id))
+        })?;
+
+        results.insert(id, response.and_then(|value| decoder.decode(web3, value)));
+    }
+
+    Ok(BatchResults { results })
+}
+
+impl Web3 {
+    /// Send a batch of calls, returning the raw results in the order the calls were added.
+    /// Fails if any call in the batch fails.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let mut batch = BatchRequestBuilder::new();
+    /// batch.insert("eth_blockNumber", rpc_params![]).unwrap();
+    /// batch.insert("eth_accounts", rpc_params![]).unwrap();
+    /// let results = web3.batch(batch).await;
+    /// ```
+    pub async fn batch(&self, batch: BatchRequestBuilder<'_>) -> Result<Vec<Value>> {
+        self.batch_responses(batch).await?.into_iter().collect()
+    }
+
+    /// Start a batch whose results are deserialized into the type of each call.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let mut batch = web3.typed_batch();
+    /// let block_number = batch.add::<U64, _>("eth_blockNumber", rpc_params![]).unwrap();
+    /// let balance = batch.add::<U256, _>("eth_getBalance", rpc_params![account]).unwrap();
+    ///
+    /// let mut results = batch.send().await.unwrap();
+    /// let block_number: U64 = results.take(block_number).unwrap();
+    /// let balance: U256 = results.take(balance).unwrap();
+    /// ```
+    pub fn typed_batch(&self) -> TypedBatch<'_> {
+        TypedBatch {
+            web3: self,
+            requests: BatchRequestBuilder::new(),
+            decoders: HashMap::new(),
+        }
+    }
+
+    /// The result of each call in a batch, in the order the calls were added.
+    /// The batch is sent like a single call, with its retries, failover and
+    /// timeout, and each call's result is checked and recorded as its own.
+    pub(crate) async fn batch_responses(
+        &self,
+        batch: BatchRequestBuilder<'_>,
+    ) -> Result<Vec<Result<Value>>> {
+        let methods = batch.iter().map(|(method, _)| method).collect::<Vec<_>>();
+        let label = format!("batch [{}]", methods.join(", "));
+        trace!("Sending RPC {}", label);
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        let response = self
+            .send_request(&label, &methods, |client| {
+                let batch = batch.clone();
+                async move { client.batch_request::<Value>(batch).await }
+            })
+            .await;
+
+        trace!("RPC Response {:?}", response);
+
+        let responses = response.map(|response| {
+            response
+                .into_iter()
+                .zip(&methods)
+                .map(|(result, method)| {
+                    let response = result.map_err(|error| call_error(method, error.into_owned()));
+
+                    #[cfg(all(feature = "validate-responses", debug_assertions))]
+                    let response = response.and_then(|response| {
+                        crate::validation::validate(method, &response)?;
+                        Ok(response)
+                    });
+
+                    response
+                })
+                .collect::<Vec<_>>()
+        });
+
+        #[cfg(feature = "metrics")]
+        for (index, method) in methods.iter().enumerate() {
+            let outcome = match &responses {
+                Ok(responses) => (&responses[index]).into(),
+                Err(error) => error.into(),
+            };
+
+            self.metrics.record(method, started.elapsed(), outcome);
+        }
+
+        responses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::{web3, ACCOUNT_1};
+    use ethereum_types::{U256, U64};
+    use jsonrpsee::rpc_params;
+    use jsonrpsee::types::error::ErrorObjectOwned;
+    use serde_json::json;
+    use types::error::NONCE_TOO_LOW_CODE;
+
+    #[test]
+    fn it_decodes_each_call_into_its_type() {
+        let web3 = web3();
+        let mut batch = web3.typed_batch();
+        let block_number = batch
+            .add::<U256, _>("eth_blockNumber", rpc_params![])
+            .unwrap();
+        let chain_id = batch.add::<U64, _>("eth_chainId", rpc_params![]).unwrap();
+        let responses = vec![Ok(json!("0x10")), Ok(json!("0x539"))];
+
+        let mut results = decode_responses(&web3, &batch.decoders, responses).unwrap();
+        assert_eq!(results.take(block_number).unwrap(), U256::from(16));
+        assert_eq!(results.take(chain_id).unwrap(), U64::from(1337));
+    }
+
+    #[test]
+    fn it_keeps_errors_with_their_call() {
+        let web3 = web3();
+        let mut batch = web3.typed_batch();
+        let accounts = batch
+            .add::<Vec<String>, _>("eth_accounts", rpc_params![])
+            .unwrap();
+        let transaction_hash = batch
+            .add::<String, _>("eth_sendTransaction", rpc_params![])
+            .unwrap();
+        let error = ErrorObjectOwned::owned(NONCE_TOO_LOW_CODE, "Nonce 1 too low", None::<()>);
+        let responses = vec![Ok(json!([])), Err(error.into())];
+
+        let mut results = decode_responses(&web3, &batch.decoders, responses).unwrap();
+        assert!(results.take(accounts).unwrap().is_empty());
+        assert!(matches!(
+            results.take(transaction_hash),
+            Err(Web3Error::NonceTooLow(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_sends_a_typed_batch() {
+        let web3 = web3();
+        let mut batch = web3.typed_batch();
+        let block_number = batch
+            .add::<U64, _>("eth_blockNumber", rpc_params![])
+            .unwrap();
+        let balance = batch
+            .add::<U256, _>("eth_getBalance", rpc_params![*ACCOUNT_1])
+            .unwrap();
+
+        let mut results = batch.send().await.unwrap();
+        assert!(results.take(block_number).is_ok());
+        assert!(results.take(balance).is_ok());
+    }
+}
@@ -6,25 +6,22 @@
 
 ////////////////////////////////////////////////////////////////////////////////
 
-use ethereum_types::U64;
+use ethereum_types::{H256, U256, U64};
 use jsonrpsee::rpc_params;
-use types::block::{Block, BlockNumber};
+use std::collections::HashMap;
+use std::time::Duration;
+use types::block::{Block, BlockHeader, BlockNumber, BlockTag};
 use types::helpers::to_hex;
+use types::transaction::TransactionReceipt;
 
-use crate::error::Result;
+use crate::cache::CacheKey;
+use crate::error::{Result, Web3Error};
 use crate::Web3;
 
-impl Web3 {
-    /// Utility function for unwrapping an optional BlockNumber.
-    /// If the Option is Some, convert it to a hex string.
-    /// If the Option is None, use "latest".
-    pub(crate) fn get_hex_blocknumber(block_number: Option<BlockNumber>) -> String {
-        block_number.map_or_else(
-            || "latest".to_string(),
-            |block_number| to_hex(*block_number),
-        )
-    }
+/// How often the block number is checked while waiting for a block
+pub const BLOCK_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
+impl Web3 {
     /// Retrieve the block number of the current block.
     ///
     /// See https://eth.wiki/json-rpc/API#eth_blockNumber
@@ -38,12 +35,45 @@ impl Web3 {
     /// ```
     pub async fn get_block_number(&self) -> Result<BlockNumber> {
         let response = self.send_rpc("eth_blockNumber", rpc_params![]).await?;
-        let block_number: BlockNumber = serde_json::from_value(response)?;
+        let block_number: BlockNumber = self.deserialize(response)?;
 
         Ok(block_number)
     }
 
+    /// Wait until the chain's head reaches `target`, giving up with
+    /// `Web3Error::Timeout` once `timeout` has passed. Polls that fail with a
+    /// retryable error (e.g. the node restarting) are tried again until then.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let block_number = web3.get_block_number().await.unwrap();
+    /// let next = U256::from(block_number.as_u64() + 1);
+    /// web3.wait_for_block(next, Duration::from_secs(30)).await.unwrap();
+    /// ```
+    pub async fn wait_for_block(&self, target: U256, timeout: Duration) -> Result<()> {
+        let wait = async {
+            let mut interval = tokio::time::interval(BLOCK_POLL_INTERVAL);
+
+            loop {
+                interval.tick().await;
+
+                match self.get_block_number().await {
+                    Ok(head) if U256::from(head.as_u64()) >= target => return Ok(()),
+                    Err(error) if !error.is_retryable() => return Err(error),
+                    _ => {}
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| Web3Error::Timeout(format!("block {}", target)))?
+    }
+
     /// Retrieve the block information using the block number.
+    /// A number can be reorged onto another block, so it's never cached.
     ///
     /// See https://eth.wiki/json-rpc/API#eth_getBlockByNumber
     ///
@@ -56,18 +86,184 @@ impl Web3 {
     /// assert!(block.is_ok());
     /// ```
     pub async fn get_block(&self, block_number: U64) -> Result<Block> {
-        let block_number = to_hex(block_number);
-        let params = rpc_params![block_number];
+        let params = rpc_params![to_hex(block_number), true];
+        let response = self.send_rpc("eth_getBlockByNumber", params).await?;
+        let block: Block = self.deserialize(response)?;
+
+        Ok(block)
+    }
+
+    /// Retrieve a block's header, with its transactions as hashes rather than
+    /// full transactions, which is much lighter for blocks with many transactions.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_getBlockByNumber
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let header = web3.get_block_header(U64::from(0)).await;
+    /// assert!(header.is_ok());
+    /// ```
+    pub async fn get_block_header(&self, block_number: U64) -> Result<BlockHeader> {
+        let params = rpc_params![to_hex(block_number), false];
         let response = self.send_rpc("eth_getBlockByNumber", params).await?;
-        let block: Block = serde_json::from_value(response)?;
+        let header: BlockHeader = self.deserialize(response)?;
+
+        Ok(header)
+    }
+
+    /// Retrieve the block information using the block hash.
+    /// Blocks are cached when caching is enabled.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_getBlockByHash
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let block = web3.get_block_by_hash(block_hash).await;
+    /// assert!(block.is_ok());
+    /// ```
+    pub async fn get_block_by_hash(&self, block_hash: H256) -> Result<Block> {
+        let key = CacheKey::Block(block_hash);
+        let params = rpc_params![block_hash, true];
+        let response = self
+            .send_cached_rpc(Some(key), "eth_getBlockByHash", params)
+            .await?;
+        let block: Block = self.deserialize(response)?;
 
         Ok(block)
     }
+
+    /// Retrieve the receipts of every transaction in a block in a single call.
+    ///
+    /// Nodes that don't support `eth_getBlockReceipts` fall back to fetching
+    /// each transaction's receipt individually.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let receipts = web3.get_block_receipts(BlockTag::Latest).await;
+    /// assert!(receipts.is_ok());
+    /// ```
+    pub async fn get_block_receipts(&self, block: BlockTag) -> Result<Vec<TransactionReceipt>> {
+        let params = rpc_params![block];
+        let response = match self.send_rpc("eth_getBlockReceipts", params).await {
+            Err(Web3Error::MethodNotFound(_)) => {
+                return self.get_block_receipts_individually(block).await
+            }
+            response => response?,
+        };
+        let receipts: Vec<TransactionReceipt> = self.deserialize(response)?;
+
+        Ok(receipts)
+    }
+
+    /// Retrieve a block's receipts with one `eth_getTransactionReceipt` call per transaction.
+    pub(crate) async fn get_block_receipts_individually(
+        &self,
+        block: BlockTag,
+    ) -> Result<Vec<TransactionReceipt>> {
+        let block_number = match block {
+            BlockTag::Number(block_number) => block_number,
+            BlockTag::Earliest => U64::zero(),
+            BlockTag::Latest => *self.get_block_number().await?,
+            BlockTag::Pending => return Ok(vec![]),
+        };
+        let block = self.get_block(block_number).await?;
+        let mut receipts = Vec::with_capacity(block.transactions.len());
+
+        for transaction in block.transactions.iter() {
+            let tx_hash = transaction
+                .transaction_hash()
+                .map_err(|e| Web3Error::RpcResponseError(e.to_string()))?;
+            receipts.push(self.transaction_receipt(tx_hash).await?);
+        }
+
+        Ok(receipts)
+    }
+
+    /// Pair each transaction hash in a block with its receipt, in block order.
+    ///
+    /// Receipts are fetched with `get_block_receipts`, so a single call is
+    /// made where the node supports it.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let block = web3.get_block(U64::from(1)).await.unwrap();
+    /// let receipts = web3.transaction_receipts(&block).await;
+    /// assert!(receipts.is_ok());
+    /// ```
+    pub async fn transaction_receipts(
+        &self,
+        block: &Block,
+    ) -> Result<Vec<(H256, TransactionReceipt)>> {
+        let receipts = self
+            .get_block_receipts(BlockTag::Number(block.number))
+            .await?;
+
+        pair_receipts(block, receipts)
+    }
+}
+
+/// Zip the transaction hashes of a block with their receipts.
+/// The receipts don't have to be in block order.
+fn pair_receipts(
+    block: &Block,
+    receipts: Vec<TransactionReceipt>,
+) -> Result<Vec<(H256, TransactionReceipt)>> {
+    let mut receipts: HashMap<H256, TransactionReceipt> = receipts
+        .into_iter()
+        .map(|receipt| (receipt.transaction_hash, receipt))
+        .collect();
+
+    block
+        .transactions
+        .iter()
+        .map(|transaction| {
+            let tx_hash = transaction
+                .transaction_hash()
+                .map_err(|e| Web3Error::RpcResponseError(e.to_string()))?;
+            let receipt = receipts.remove(&tx_hash).ok_or_else(|| {
+                Web3Error::RpcResponseError(format!("missing receipt for {:?}", tx_hash))
+            })?;
+
+            Ok((tx_hash, receipt))
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::helpers::tests::web3;
+    use super::*;
+    use crate::config::Web3Config;
+    use crate::helpers::tests::{mock_node, web3};
+    use crate::transaction::tests::send_transaction;
+    use jsonrpsee::server::ServerHandle;
+    use jsonrpsee::types::error::{CallError, ErrorObject, INTERNAL_ERROR_CODE};
+    use jsonrpsee::RpcModule;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Instant;
+    use types::account::Account;
+    use types::transaction::Transaction;
+
+    fn receipt(transaction: &Transaction) -> TransactionReceipt {
+        TransactionReceipt {
+            block_hash: None,
+            block_number: None,
+            contract_address: None,
+            from: transaction.from,
+            to: transaction.to,
+            transaction_hash: transaction.hash.unwrap(),
+            transaction_index: None,
+            gas_used: U256::zero(),
+            status: U64::one(),
+        }
+    }
 
     #[tokio::test]
     async fn it_gets_a_block_number() {
@@ -75,10 +271,167 @@ mod tests {
         assert!(response.is_ok());
     }
 
+    /// A node whose head moves up a block each time it's asked for it, up to `last`
+    async fn growing_node(last: u64) -> (crate::Web3, ServerHandle) {
+        let mut module = RpcModule::new(AtomicU64::new(0));
+        module
+            .register_method("eth_blockNumber", move |_, head| {
+                let head = head.fetch_add(1, Ordering::SeqCst).min(last);
+                Ok(BlockNumber(U64::from(head)))
+            })
+            .unwrap();
+        let (url, handle) = mock_node(module).await;
+
+        (crate::Web3::new(&url).unwrap(), handle)
+    }
+
+    #[tokio::test]
+    async fn it_waits_for_a_block() {
+        let (web3, _handle) = growing_node(10).await;
+        let started = Instant::now();
+
+        web3.wait_for_block(U256::from(2), Duration::from_secs(10))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn it_waits_for_a_block_through_transient_errors() {
+        let mut module = RpcModule::new(AtomicU64::new(0));
+        module
+            .register_method("eth_blockNumber", |_, calls| {
+                if calls.fetch_add(1, Ordering::SeqCst) < 3 {
+                    let error = ErrorObject::owned(INTERNAL_ERROR_CODE, "restarting", None::<()>);
+                    return Err(CallError::Custom(error).into());
+                }
+
+                Ok(BlockNumber(U64::from(5)))
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+
+        // without retries in send_rpc, each failure reaches wait_for_block
+        let config = Web3Config::builder().max_retries(0).build();
+        let web3 = crate::Web3::with_config(&url, config).unwrap();
+        web3.wait_for_block(U256::from(5), Duration::from_secs(10))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_times_out_waiting_for_a_block_that_never_comes() {
+        let (web3, _handle) = growing_node(1).await;
+        let response = web3
+            .wait_for_block(U256::from(5), Duration::from_millis(1200))
+            .await;
+
+        assert!(matches!(response, Err(Web3Error::Timeout(_))));
+    }
+
     #[tokio::test]
     async fn it_gets_the_latest_block() {
         let block_number = web3().get_block_number().await.unwrap();
         let response = web3().get_block(*block_number).await;
         assert!(response.is_ok());
     }
+
+    #[tokio::test]
+    async fn it_gets_a_header_without_full_transactions() {
+        let transaction = Transaction::new(
+            Account::random(),
+            Some(Account::random()),
+            U256::from(10),
+            Some(U256::one()),
+            None,
+        )
+        .unwrap();
+        let block = Block::new(
+            U64::one(),
+            H256::zero(),
+            vec![transaction.clone()],
+            &[],
+            H256::zero(),
+            U256::zero(),
+            U64::zero(),
+        )
+        .unwrap();
+        let mut module = RpcModule::new(block);
+        module
+            .register_method("eth_getBlockByNumber", |params, block| {
+                let (_, full_transactions): (U64, bool) = params.parse()?;
+
+                match full_transactions {
+                    true => Ok(serde_json::to_value(block)?),
+                    false => Ok(serde_json::to_value(BlockHeader::from(block))?),
+                }
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = crate::Web3::new(&url).unwrap();
+
+        let header = web3.get_block_header(U64::one()).await.unwrap();
+        assert_eq!(header.transactions, vec![transaction.hash.unwrap()]);
+
+        let block = web3.get_block(U64::one()).await.unwrap();
+        assert_eq!(block.transactions, vec![transaction]);
+        assert!(
+            serde_json::to_string(&header).unwrap().len()
+                < serde_json::to_string(&block).unwrap().len()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_gets_block_receipts() {
+        let web3 = web3();
+        let tx_hash = send_transaction().await.unwrap();
+        let receipt = web3
+            .wait_for_receipt_with_timeout(tx_hash, Duration::from_secs(10))
+            .await
+            .unwrap();
+        let block = BlockTag::Number(*receipt.block_number.clone().unwrap());
+        let receipts = web3.get_block_receipts(block).await.unwrap();
+        let individual_receipts = web3.get_block_receipts_individually(block).await.unwrap();
+
+        assert!(receipts.contains(&receipt));
+        assert_eq!(receipts, individual_receipts);
+    }
+
+    #[test]
+    fn it_pairs_transactions_with_receipts_in_block_order() {
+        let transactions = (1..=3)
+            .map(|nonce| {
+                Transaction::new(
+                    Account::random(),
+                    Some(Account::random()),
+                    U256::from(10),
+                    Some(U256::from(nonce)),
+                    None,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let receipts = transactions.iter().rev().map(receipt).collect();
+        let block = Block::new(
+            U64::one(),
+            H256::zero(),
+            transactions,
+            &[],
+            H256::zero(),
+            U256::zero(),
+            U64::zero(),
+        )
+        .unwrap();
+        let pairs = pair_receipts(&block, receipts).unwrap();
+
+        assert_eq!(pairs.len(), 3);
+
+        for ((tx_hash, receipt), transaction) in pairs.iter().zip(block.transactions.iter()) {
+            assert_eq!(Some(*tx_hash), transaction.hash);
+            assert_eq!(receipt.transaction_hash, *tx_hash);
+        }
+
+        let response = pair_receipts(&block, vec![]);
+        assert!(matches!(response, Err(Web3Error::RpcResponseError(_))));
+    }
 }
@@ -0,0 +1,80 @@
+//! # Cache
+//!
+//! Responses that can never change, kept so they're only fetched once.
+//! Only data pinned to a block hash is cached (e.g. the block with a given
+//! hash or code at it). A block number can be reorged onto another block, and
+//! the latest or pending block moves with the chain, so those are always
+//! fetched.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+use ethereum_types::{Address, H256};
+use lru::LruCache;
+use serde_json::Value;
+use types::block::BlockId;
+
+/// The hash a block id pins a response to, or `None` for block numbers and tags
+pub(crate) fn pinned_hash(block: BlockId) -> Option<H256> {
+    match block {
+        BlockId::Number(_) => None,
+        BlockId::Hash(hash) => Some(hash),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum CacheKey {
+    Block(H256),
+    Code(Address, H256),
+}
+
+/// The most recently used responses, up to a fixed number of entries
+#[derive(Debug)]
+pub(crate) struct Cache(Mutex<LruCache<CacheKey, Value>>);
+
+impl Cache {
+    pub(crate) fn new(size: NonZeroUsize) -> Self {
+        Self(Mutex::new(LruCache::new(size)))
+    }
+
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<Value> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    pub(crate) fn insert(&self, key: CacheKey, response: Value) {
+        self.0.lock().unwrap().put(key, response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::U64;
+    use types::block::BlockTag;
+
+    #[test]
+    fn it_only_pins_a_block_hash() {
+        assert_eq!(pinned_hash(BlockTag::Latest.into()), None);
+        assert_eq!(pinned_hash(BlockTag::Pending.into()), None);
+        assert_eq!(pinned_hash(BlockTag::Number(U64::from(1)).into()), None);
+        assert_eq!(pinned_hash(BlockTag::Earliest.into()), None);
+        assert_eq!(pinned_hash(H256::zero().into()), Some(H256::zero()));
+    }
+
+    #[test]
+    fn it_evicts_the_least_recently_used_response() {
+        let cache = Cache::new(NonZeroUsize::new(2).unwrap());
+        let key = |number: u64| CacheKey::Block(H256::from_low_u64_be(number));
+
+        cache.insert(key(1), Value::from(1));
+        cache.insert(key(2), Value::from(2));
+        cache.get(&key(1));
+        cache.insert(key(3), Value::from(3));
+
+        assert_eq!(cache.get(&key(1)), Some(Value::from(1)));
+        assert_eq!(cache.get(&key(2)), None);
+        assert_eq!(cache.get(&key(3)), Some(Value::from(3)));
+    }
+}
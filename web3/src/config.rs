@@ -0,0 +1,218 @@
+//! # Config
+//!
+//! Configuration knobs for the Web3 client.
+//! Use the builder to override individual settings, leaving the rest at
+//! their defaults.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How long to wait for a response before giving up
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Transport failures are not retried by default
+pub const DEFAULT_MAX_RETRIES: u32 = 0;
+
+/// The wait before the first retry, doubling with each retry after it
+pub const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Maximum number of requests in flight at once
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 256;
+
+/// Maximum size of a request or response body (10 MB)
+pub const DEFAULT_MAX_REQUEST_BODY_SIZE: u32 = 10 * 1024 * 1024;
+
+/// Receipts are returned as soon as the transaction is mined
+pub const DEFAULT_CONFIRMATIONS: u64 = 0;
+
+/// How long to wait for a transaction to be mined before giving up
+pub const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Responses aren't cached by default
+pub const DEFAULT_CACHE_SIZE: usize = 0;
+
+/// How to treat fields in a response that the types don't know about.
+///
+///   * Strict: reject responses with unknown fields, catching schema drift in development.
+///   * Lenient: ignore unknown fields, tolerating node quirks in production.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeserializationMode {
+    Strict,
+    #[default]
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Web3Config {
+    pub timeout: Duration,
+    pub max_retries: u32,
+    pub retry_base_delay: Duration,
+    pub headers: HashMap<String, String>,
+    pub max_concurrent_requests: usize,
+    pub max_request_body_size: u32,
+    pub deserialization_mode: DeserializationMode,
+    /// Run `eth_call` before sending a transaction to catch reverts without spending gas
+    pub preflight: bool,
+    /// Blocks to wait for on top of a transaction's block when waiting for its receipt
+    pub default_confirmations: u64,
+    /// How long to wait for a transaction to be mined, separate from the
+    /// `timeout` of each request made while waiting
+    pub receipt_timeout: Duration,
+    /// Immutable responses (e.g. code at a past block) to keep, 0 to disable caching
+    pub cache_size: usize,
+    /// The chain id signed into transactions, fetched from the node when unset
+    pub chain_id: Option<u64>,
+}
+
+impl Default for Web3Config {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+            headers: HashMap::new(),
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            max_request_body_size: DEFAULT_MAX_REQUEST_BODY_SIZE,
+            deserialization_mode: DeserializationMode::default(),
+            preflight: false,
+            default_confirmations: DEFAULT_CONFIRMATIONS,
+            receipt_timeout: DEFAULT_RECEIPT_TIMEOUT,
+            cache_size: DEFAULT_CACHE_SIZE,
+            chain_id: None,
+        }
+    }
+}
+
+impl Web3Config {
+    /// Start building a config from the defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use web3::config::Web3Config;
+    ///
+    /// let config = Web3Config::builder()
+    ///     .timeout(Duration::from_secs(5))
+    ///     .max_retries(3)
+    ///     .build();
+    /// assert_eq!(config.max_retries, 3);
+    /// ```
+    pub fn builder() -> Web3ConfigBuilder {
+        Web3ConfigBuilder::default()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Web3ConfigBuilder {
+    config: Web3Config,
+}
+
+impl Web3ConfigBuilder {
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.config.max_retries = max_retries;
+        self
+    }
+
+    pub fn retry_base_delay(mut self, retry_base_delay: Duration) -> Self {
+        self.config.retry_base_delay = retry_base_delay;
+        self
+    }
+
+    /// Add a header sent with every request (e.g. an API key)
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.config.headers.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.config.max_concurrent_requests = max_concurrent_requests;
+        self
+    }
+
+    pub fn max_request_body_size(mut self, max_request_body_size: u32) -> Self {
+        self.config.max_request_body_size = max_request_body_size;
+        self
+    }
+
+    pub fn deserialization_mode(mut self, deserialization_mode: DeserializationMode) -> Self {
+        self.config.deserialization_mode = deserialization_mode;
+        self
+    }
+
+    pub fn preflight(mut self, preflight: bool) -> Self {
+        self.config.preflight = preflight;
+        self
+    }
+
+    pub fn default_confirmations(mut self, default_confirmations: u64) -> Self {
+        self.config.default_confirmations = default_confirmations;
+        self
+    }
+
+    pub fn receipt_timeout(mut self, receipt_timeout: Duration) -> Self {
+        self.config.receipt_timeout = receipt_timeout;
+        self
+    }
+
+    pub fn cache_size(mut self, cache_size: usize) -> Self {
+        self.config.cache_size = cache_size;
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.config.chain_id = Some(chain_id);
+        self
+    }
+
+    pub fn build(self) -> Web3Config {
+        self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Web3;
+
+    #[test]
+    fn it_builds_a_config() {
+        let timeout = Duration::from_secs(5);
+        let config = Web3Config::builder()
+            .timeout(timeout)
+            .max_retries(3)
+            .header("x-api-key", "secret")
+            .build();
+
+        assert_eq!(config.timeout, timeout);
+        assert_eq!(config.max_retries, 3);
+        assert_eq!(config.retry_base_delay, DEFAULT_RETRY_BASE_DELAY);
+        assert_eq!(config.headers.get("x-api-key").unwrap(), "secret");
+
+        // unset fields take the defaults
+        assert_eq!(
+            config.max_concurrent_requests,
+            DEFAULT_MAX_CONCURRENT_REQUESTS
+        );
+        assert_eq!(config.max_request_body_size, DEFAULT_MAX_REQUEST_BODY_SIZE);
+        assert_eq!(config.deserialization_mode, DeserializationMode::Lenient);
+        assert!(!config.preflight);
+        assert_eq!(config.default_confirmations, DEFAULT_CONFIRMATIONS);
+        assert_eq!(config.receipt_timeout, DEFAULT_RECEIPT_TIMEOUT);
+        assert_eq!(config.cache_size, DEFAULT_CACHE_SIZE);
+        assert_eq!(config.chain_id, None);
+    }
+
+    #[test]
+    fn new_uses_the_default_config() {
+        let web3 = Web3::new("http://127.0.0.1:8545").unwrap();
+        assert_eq!(web3.config(), &Web3Config::default());
+    }
+}
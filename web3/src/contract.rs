@@ -7,16 +7,36 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 use ethereum_types::Address;
-use ethereum_types::{H256, U256};
+use ethereum_types::{H256, U256, U64};
 use jsonrpsee::rpc_params;
-use types::block::BlockNumber;
+use types::account::StorageRange;
+use types::block::BlockId;
 use types::bytes::Bytes;
 use types::helpers::to_hex;
-use types::transaction::TransactionRequest;
+use types::transaction::{TransactionReceipt, TransactionRequest};
 
+use crate::account::AddressOrAlias;
+use crate::cache::{pinned_hash, CacheKey};
 use crate::error::Result;
 use crate::Web3;
 
+/// A constructor argument, ABI encoded after the contract's bytecode
+pub type AbiToken = ethabi::Token;
+
+/// The outcome of deploying a contract with `Web3::deploy_many`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeployResult {
+    pub tx_hash: H256,
+    pub contract_address: Option<Address>,
+    pub receipt: TransactionReceipt,
+}
+
+impl DeployResult {
+    pub fn is_success(&self) -> bool {
+        self.receipt.status == U64::one()
+    }
+}
+
 impl Web3 {
     /// Deploy a contract to the chain.
     ///
@@ -29,12 +49,7 @@ impl Web3 {
     /// let tx_hash = web3.deploy(account, &contract).await;
     /// assert!(tx_hash.is_ok());
     /// ```
-    pub async fn deploy<'a>(
-        &self,
-        owner: Address,
-        abi: &'a [u8],
-        nonce: Option<U256>,
-    ) -> Result<H256> {
+    pub async fn deploy(&self, owner: Address, abi: &[u8], nonce: Option<U256>) -> Result<H256> {
         let gas = U256::from(1_000_000);
         let gas_price = U256::from(1_000_000);
         let data: Bytes = abi.to_vec().into();
@@ -46,14 +61,64 @@ impl Web3 {
             gas_price,
             data: Some(data),
             nonce,
-            r: None,
-            s: None,
+            ..Default::default()
         };
 
         self.send(transaction_request).await
     }
 
-    /// Get the contract code for an address
+    /// Deploy contracts one after another, in the order given, so each can
+    /// depend on the ones before it.
+    /// Each deploy uses the next nonce after `nonce_start` and waits to be mined
+    /// before the next is sent, failing with `Web3Error::ReceiptTimeout` if it
+    /// isn't within the configured `receipt_timeout`. Deploying stops at the
+    /// first that reverts, which is the last of the results.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let library = (library_bytecode, vec![]);
+    /// let token = (token_bytecode, vec![AbiToken::String("RustCoin".into())]);
+    /// let deployed = web3.deploy_many(owner, vec![library, token], nonce).await.unwrap();
+    /// assert!(deployed.iter().all(DeployResult::is_success));
+    /// ```
+    pub async fn deploy_many(
+        &self,
+        from: Address,
+        artifacts: Vec<(Bytes, Vec<AbiToken>)>,
+        nonce_start: U256,
+    ) -> Result<Vec<DeployResult>> {
+        let mut deployed = Vec::with_capacity(artifacts.len());
+
+        for (index, (bytecode, constructor_args)) in artifacts.into_iter().enumerate() {
+            let mut data = bytecode.to_vec();
+            data.extend(ethabi::encode(&constructor_args));
+
+            let nonce = nonce_start + index;
+            let tx_hash = self.deploy(from, &data, Some(nonce)).await?;
+            let receipt = self
+                .wait_for_receipt_with_timeout(tx_hash, self.config.receipt_timeout)
+                .await?;
+            let result = DeployResult {
+                tx_hash,
+                contract_address: receipt.contract_address,
+                receipt,
+            };
+            let is_success = result.is_success();
+
+            deployed.push(result);
+
+            if !is_success {
+                break;
+            }
+        }
+
+        Ok(deployed)
+    }
+
+    /// Get the contract code for an address.
+    /// Code at a block hash is cached when caching is enabled.
     ///
     /// See https://eth.wiki/json-rpc/API#eth_getCode
     ///
@@ -64,32 +129,122 @@ impl Web3 {
     /// let contract = include_bytes!("./../../contracts/artifacts/contracts/ERC20.sol/RustCoinToken.json").to_vec();
     /// let tx_hash = web3.deploy(account, &contract).await.unwrap();
     /// let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
-    /// let code = web3.code(receipt.contract_address.unwrap(), None).await.unwrap();
+    /// let code = web3.code(receipt.contract_address.unwrap(), BlockTag::Latest).await.unwrap();
     /// assert!(code.is_ok());
     /// ```
     pub async fn code(
         &self,
-        address: Address,
-        block_number: Option<BlockNumber>,
+        address: impl Into<AddressOrAlias>,
+        block: impl Into<BlockId>,
     ) -> Result<Vec<u8>> {
-        let block_number = Web3::get_hex_blocknumber(block_number);
-        let params = rpc_params![to_hex(address), block_number];
-        let response = self.send_rpc("eth_getCode", params).await?;
-        let code: Vec<u8> = serde_json::from_value(response)?;
+        let address = self.resolve(address)?;
+        let block = block.into();
+        let key = pinned_hash(block).map(|hash| CacheKey::Code(address, hash));
+        let params = rpc_params![to_hex(address), block];
+        let response = self.send_cached_rpc(key, "eth_getCode", params).await?;
+        let code: Vec<u8> = self.deserialize(response)?;
 
         Ok(code)
     }
+
+    /// Get a page of a contract's storage as of a transaction in a block,
+    /// starting at the hashed slot `start_key`.
+    /// Pass the returned `next_key` back in as `start_key` to get the next
+    /// page, until it's `None`.
+    ///
+    /// See https://geth.ethereum.org/docs/interacting-with-geth/rpc/ns-debug#debugstoragerangeat
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let range = web3
+    ///     .storage_range_at(block_hash, 0, contract, H256::zero(), 256)
+    ///     .await;
+    /// assert!(range.is_ok());
+    /// ```
+    pub async fn storage_range_at(
+        &self,
+        block: H256,
+        tx_index: u64,
+        address: impl Into<AddressOrAlias>,
+        start_key: H256,
+        max_results: u64,
+    ) -> Result<StorageRange> {
+        let params = rpc_params![
+            block,
+            tx_index,
+            to_hex(self.resolve(address)?),
+            start_key,
+            max_results
+        ];
+        let response = self.send_rpc("debug_storageRangeAt", params).await?;
+        let range: StorageRange = self.deserialize(response)?;
+
+        Ok(range)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::helpers::tests::{deploy_contract, web3};
+    use super::*;
+    use crate::config::Web3Config;
+    use crate::error::Web3Error;
+    use crate::helpers::tests::{
+        deploy_contract, increment_account_1_nonce, mock_node, web3, ACCOUNT_1,
+    };
+    use jsonrpsee::RpcModule;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
     use std::time::Duration;
     use tokio::time::sleep;
+    use types::block::BlockTag;
 
     #[tokio::test]
     async fn it_deploys_a_contract() {
-        let response = deploy_contract(true).await;
+        deploy_contract(true).await;
+    }
+
+    #[tokio::test]
+    async fn it_deploys_contracts_in_order() {
+        let from = *ACCOUNT_1;
+        let nonce_start = increment_account_1_nonce().await;
+        increment_account_1_nonce().await;
+        let simple = || (Bytes::from(vec![0, 1]), vec![]);
+
+        let deployed = web3()
+            .deploy_many(from, vec![simple(), simple()], nonce_start)
+            .await
+            .unwrap();
+        assert_eq!(deployed.len(), 2);
+        assert!(deployed.iter().all(DeployResult::is_success));
+        assert!(deployed[0].contract_address.is_some());
+        assert_ne!(deployed[0].contract_address, deployed[1].contract_address);
+    }
+
+    #[tokio::test]
+    async fn it_stops_deploying_when_a_deploy_is_never_mined() {
+        let tx_hash = H256::random();
+        let mut module = RpcModule::new(());
+        module
+            .register_method("eth_sendTransaction", move |_, _| Ok(tx_hash))
+            .unwrap();
+        module
+            .register_method("eth_getTransactionReceipt", |_, _| {
+                Ok(None::<TransactionReceipt>)
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let config = Web3Config::builder()
+            .receipt_timeout(Duration::from_millis(100))
+            .build();
+        let web3 = crate::Web3::with_config(&url, config).unwrap();
+        let simple = (Bytes::from(vec![0, 1]), vec![]);
+
+        let response = web3
+            .deploy_many(Address::random(), vec![simple], U256::one())
+            .await;
+        assert!(matches!(response, Err(Web3Error::ReceiptTimeout(hash)) if hash == tx_hash));
     }
 
     #[tokio::test]
@@ -101,9 +256,92 @@ mod tests {
         sleep(Duration::from_millis(1000)).await;
 
         let receipt = web3.transaction_receipt(tx_hash).await.unwrap();
-        let response = web3.code(receipt.contract_address.unwrap(), None).await;
+        let response = web3
+            .code(receipt.contract_address.unwrap(), BlockTag::Latest)
+            .await;
 
         // ensure the code matches what was deployed
         assert_eq!(response.unwrap(), [0, 1]);
     }
+
+    #[tokio::test]
+    async fn it_caches_code_at_a_block_hash() {
+        let calls = Arc::new(AtomicU64::new(0));
+        let mut module = RpcModule::new(Arc::clone(&calls));
+        module
+            .register_method("eth_getCode", |_, calls| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(vec![0_u8, 1])
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let config = Web3Config::builder().cache_size(16).build();
+        let web3 = crate::Web3::with_config(&url, config).unwrap();
+        let contract = Address::random();
+        let block = H256::random();
+
+        assert_eq!(web3.code(contract, block).await.unwrap(), [0, 1]);
+        assert_eq!(web3.code(contract, block).await.unwrap(), [0, 1]);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // an alias resolves to the same cached code
+        web3.set_alias("token", contract).unwrap();
+        assert_eq!(web3.code("token", block).await.unwrap(), [0, 1]);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // a block number can be reorged and the latest block moves, so their
+        // code is always fetched
+        for block in [BlockTag::Number(U64::from(1)), BlockTag::Latest] {
+            web3.code(contract, block).await.unwrap();
+            web3.code(contract, block).await.unwrap();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn it_parses_a_storage_range() {
+        let response = serde_json::json!({
+            "storage": {
+                "0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563": {
+                    "key": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "value": "0x000000000000000000000000000000000000000000000000000000000000002a"
+                },
+                "0xb10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6": {
+                    "key": null,
+                    "value": "0x0000000000000000000000000000000000000000000000000000000000000001"
+                }
+            },
+            "nextKey": "0xc2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85b"
+        });
+        let range: StorageRange = web3().deserialize(response).unwrap();
+        let slot = |hash: &str| H256::from_slice(&hex::decode(hash).unwrap());
+
+        assert_eq!(range.storage.len(), 2);
+
+        let first = &range.storage
+            [&slot("290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563")];
+        assert_eq!(first.key, Some(H256::zero()));
+        assert_eq!(first.value, H256::from_low_u64_be(42));
+
+        let second = &range.storage
+            [&slot("b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6")];
+        assert_eq!(second.key, None);
+        assert_eq!(second.value, H256::from_low_u64_be(1));
+
+        assert_eq!(
+            range.next_key,
+            Some(slot(
+                "c2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85b"
+            ))
+        );
+    }
+
+    #[test]
+    fn it_parses_the_last_storage_range() {
+        let response = serde_json::json!({ "storage": {}, "nextKey": null });
+        let range: StorageRange = web3().deserialize(response).unwrap();
+
+        assert!(range.storage.is_empty());
+        assert_eq!(range.next_key, None);
+    }
 }
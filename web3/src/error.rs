@@ -6,32 +6,308 @@
 
 ////////////////////////////////////////////////////////////////////////////////
 
+use ethabi::{ParamType, Token};
+use ethereum_types::H256;
+use jsonrpsee::types::error::{ErrorObjectOwned, INTERNAL_ERROR_CODE, METHOD_NOT_FOUND_CODE};
+use serde_json::Value;
 use thiserror::Error;
+use types::error::{
+    EXECUTION_REVERTED_CODE, GAS_PRICE_TOO_LOW_CODE, INSUFFICIENT_FUNDS_CODE,
+    INVALID_TRANSACTION_CODE, NONCE_TOO_HIGH_CODE, NONCE_TOO_LOW_CODE,
+    REPLACEMENT_UNDERPRICED_CODE, TRANSACTION_TOO_LARGE_CODE,
+};
 
 #[derive(Error, Debug)]
 pub enum Web3Error {
+    #[error("The address book is unusable after a panic while it was being updated")]
+    AliasesPoisoned,
+
+    #[error("Fee bump of {0}x overflows the fee")]
+    BumpOverflow(f64),
+
+    #[error("Fee bump of {0}x is below the minimum of 1.1x")]
+    BumpTooSmall(f64),
+
     #[error("Error creating a new HTTP JSON-RPC client: {0}")]
     ClientError(String),
 
+    #[error("Gas estimation failed: {reason}")]
+    GasEstimationFailed { reason: String },
+
+    #[error("Gas price too low: {0}")]
+    GasPriceTooLow(String),
+
+    #[error("Insufficient funds: {0}")]
+    InsufficientFunds(String),
+
+    #[error("The node failed internally: {0}")]
+    InternalError(String),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Response doesn't match the expected shape: {0}")]
+    InvalidResponse(String),
+
     #[error("Error serializing or deserializing JSON data: {0}")]
     JsonParseError(String),
 
+    #[error("Method not supported by the node: {0}")]
+    MethodNotFound(String),
+
+    #[error("Nonce too high: {0}")]
+    NonceTooHigh(String),
+
+    #[error("Nonce too low: {0}")]
+    NonceTooLow(String),
+
+    #[error("The node rejected the raw transaction: {message}")]
+    RawTransactionRejected {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+
+    #[error("Timed out waiting for the receipt of transaction {0:?}")]
+    ReceiptTimeout(H256),
+
+    #[error("Replacement transaction underpriced: {0}")]
+    ReplacementUnderpriced(String),
+
+    #[error("JSON-RPC error {code}: {message}")]
+    Rpc {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+
     #[error("Error sending a HTTP JSON-RPC call: {0}")]
     RpcRequestError(String),
 
     #[error("Error receiving a HTTP JSON-RPC response: {0}")]
     RpcResponseError(String),
 
+    #[error("The transaction sender has stopped")]
+    SenderStopped,
+
+    #[error("Subscription lagged behind, {0} blocks were dropped")]
+    SubscriptionLagged(u64),
+
+    #[error("Timed out waiting for {0}")]
+    Timeout(String),
+
     #[error("Error signing transaction: {0}")]
     TransactionSigningError(String),
+
+    #[error("Transport error calling {method} on {endpoint}: {source}")]
+    Transport {
+        method: String,
+        endpoint: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error("Transaction too large: {0}")]
+    TransactionTooLarge(String),
+
+    #[error("No address is registered for the alias {0:?}")]
+    UnknownAlias(String),
+
+    #[error("Response contained unknown fields: {0}")]
+    UnknownFields(String),
+
+    #[error("Transaction would revert: {reason}")]
+    WouldRevert { reason: String },
 }
 
 /// Utility result type to be used throughout
 pub type Result<T> = std::result::Result<T, Web3Error>;
 
+/// The selector of Solidity's `Error(string)`, which `revert("...")` and
+/// `require(..., "...")` encode their reason with
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Decode the reason a contract reverted with from its revert data, when it
+/// was reverted with a string.
+/// Custom errors and panics have other selectors, and return `None`.
+///
+/// ```
+/// let data = hex::decode(
+///     "08c379a0\
+///     0000000000000000000000000000000000000000000000000000000000000020\
+///     000000000000000000000000000000000000000000000000000000000000000c\
+///     6f7574206f662066756e64730000000000000000000000000000000000000000",
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     web3::error::decode_revert_reason(&data).as_deref(),
+///     Some("out of funds")
+/// );
+/// ```
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    let arguments = data.strip_prefix(ERROR_SELECTOR.as_slice())?;
+
+    match ethabi::decode(&[ParamType::String], arguments)
+        .ok()?
+        .pop()?
+    {
+        Token::String(reason) => Some(reason),
+        _ => None,
+    }
+}
+
+/// Extract the revert reason from the hex `data` of a JSON-RPC error
+pub fn revert_reason(data: &Value) -> Option<String> {
+    let encoded = hex::decode(data.as_str()?.trim_start_matches("0x")).ok()?;
+
+    decode_revert_reason(&encoded)
+}
+
+impl Web3Error {
+    /// Whether a failed call may succeed if sent again, because the node
+    /// couldn't be reached (including HTTP 429 and 5xx responses), didn't
+    /// respond in time or failed internally.
+    /// Errors about the call itself, such as invalid params or a revert, fail
+    /// the same way every time, and retrying a transaction could send it twice.
+    /// A call that changes the node's state may already have been handled,
+    /// so `send_rpc` only retries those when they never reached the node.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Web3Error::InternalError(_) | Web3Error::Timeout(_) | Web3Error::Transport { .. }
+        )
+    }
+}
+
 /// Generically convert serde errors to Web3Error::JsonParseError
 impl From<serde_json::Error> for Web3Error {
     fn from(error: serde_json::Error) -> Self {
         Web3Error::JsonParseError(error.to_string())
     }
 }
+
+/// Map JSON-RPC error objects from the node to errors the client can match on
+impl From<ErrorObjectOwned> for Web3Error {
+    fn from(error: ErrorObjectOwned) -> Self {
+        let message = error.message().to_string();
+        let data: Option<Value> = error
+            .data()
+            .and_then(|data| serde_json::from_str(data.get()).ok());
+
+        match error.code() {
+            EXECUTION_REVERTED_CODE => Web3Error::WouldRevert {
+                reason: data.as_ref().and_then(revert_reason).unwrap_or(message),
+            },
+            GAS_PRICE_TOO_LOW_CODE => Web3Error::GasPriceTooLow(message),
+            INSUFFICIENT_FUNDS_CODE => Web3Error::InsufficientFunds(message),
+            INTERNAL_ERROR_CODE => Web3Error::InternalError(message),
+            INVALID_TRANSACTION_CODE => Web3Error::RawTransactionRejected {
+                code: INVALID_TRANSACTION_CODE.into(),
+                message,
+                data,
+            },
+            METHOD_NOT_FOUND_CODE => Web3Error::MethodNotFound(message),
+            NONCE_TOO_HIGH_CODE => Web3Error::NonceTooHigh(message),
+            NONCE_TOO_LOW_CODE => Web3Error::NonceTooLow(message),
+            REPLACEMENT_UNDERPRICED_CODE => Web3Error::ReplacementUnderpriced(message),
+            TRANSACTION_TOO_LARGE_CODE => Web3Error::TransactionTooLarge(message),
+            code => Web3Error::Rpc {
+                code: code.into(),
+                message,
+                data,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::error::SERVER_ERROR_CODE;
+
+    #[test]
+    fn it_maps_error_codes() {
+        let error = ErrorObjectOwned::owned(NONCE_TOO_LOW_CODE, "Nonce 1 too low", None::<()>);
+        assert!(matches!(Web3Error::from(error), Web3Error::NonceTooLow(_)));
+
+        let error = ErrorObjectOwned::owned(EXECUTION_REVERTED_CODE, "out of funds", None::<()>);
+        assert!(
+            matches!(Web3Error::from(error), Web3Error::WouldRevert { reason } if reason == "out of funds")
+        );
+
+        let error = ErrorObjectOwned::owned(SERVER_ERROR_CODE, "insufficient funds", None::<()>);
+        assert!(matches!(
+            Web3Error::from(error),
+            Web3Error::Rpc { code: -32000, message, data: None } if message == "insufficient funds"
+        ));
+    }
+
+    #[test]
+    fn it_extracts_the_revert_reason_from_the_error_data() {
+        let data = format!(
+            "0x{}{}",
+            hex::encode(ERROR_SELECTOR),
+            hex::encode(ethabi::encode(&[Token::String("out of funds".into())]))
+        );
+        let error =
+            ErrorObjectOwned::owned(EXECUTION_REVERTED_CODE, "execution reverted", Some(&data));
+        assert!(
+            matches!(Web3Error::from(error), Web3Error::WouldRevert { reason } if reason == "out of funds")
+        );
+
+        let error = ErrorObjectOwned::owned(SERVER_ERROR_CODE, "reverted", Some(&data));
+        match Web3Error::from(error) {
+            Web3Error::Rpc {
+                data: Some(data), ..
+            } => {
+                assert_eq!(revert_reason(&data).as_deref(), Some("out of funds"))
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+
+        // a custom error, with its own selector
+        assert_eq!(revert_reason(&Value::from("0x12345678")), None);
+        assert_eq!(revert_reason(&Value::Null), None);
+    }
+
+    #[test]
+    fn it_decodes_a_revert_reason() {
+        let data = hex::decode(
+            "08c379a0\
+            0000000000000000000000000000000000000000000000000000000000000020\
+            0000000000000000000000000000000000000000000000000000000000000026\
+            45524332303a207472616e7366657220616d6f756e7420657863656564732062\
+            616c616e63650000000000000000000000000000000000000000000000000000",
+        )
+        .unwrap();
+        assert_eq!(
+            decode_revert_reason(&data).as_deref(),
+            Some("ERC20: transfer amount exceeds balance")
+        );
+
+        // truncated, and a panic (Panic(uint256)) rather than a reason
+        assert_eq!(decode_revert_reason(&data[..40]), None);
+        assert_eq!(decode_revert_reason(&[0x4e, 0x48, 0x7b, 0x71]), None);
+    }
+
+    #[test]
+    fn it_classifies_retryable_errors() {
+        let rate_limited = Web3Error::Transport {
+            method: "eth_blockNumber".into(),
+            endpoint: "http://127.0.0.1:8545".into(),
+            source: "HTTP 429 Too Many Requests".into(),
+        };
+        let internal = ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, "failed", None::<()>);
+
+        assert!(rate_limited.is_retryable());
+        assert!(Web3Error::Timeout("eth_blockNumber".into()).is_retryable());
+        assert!(Web3Error::from(internal).is_retryable());
+
+        let nonce_too_low = ErrorObjectOwned::owned(NONCE_TOO_LOW_CODE, "too low", None::<()>);
+        let revert = ErrorObjectOwned::owned(EXECUTION_REVERTED_CODE, "reverted", None::<()>);
+
+        assert!(!Web3Error::from(nonce_too_low).is_retryable());
+        assert!(!Web3Error::from(revert).is_retryable());
+        assert!(!Web3Error::MethodNotFound("eth_foo".into()).is_retryable());
+        assert!(!Web3Error::ReceiptTimeout(H256::zero()).is_retryable());
+    }
+}
@@ -1,23 +1,28 @@
 //! # Helpers
 //!
-//! General purpose utilties that don't have a home :(/66766666666ttt?)
-?df
-////////////////////////////////////////////////sdfdfsdafdsfasd////////////////////////////////
+//! General purpose utilties that don't have a home :(
+
+////////////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use std::net::SocketAddr;
     use std::str::FromStr;
 
     use crate::Web3;
     use ethereum_types::{H160, H256, U256};
+    use jsonrpsee::server::logger::Logger;
+    use jsonrpsee::server::{ServerBuilder, ServerHandle};
+    use jsonrpsee::RpcModule;
     use lazy_static::lazy_static;
     use tokio::sync::Mutex;
     use types::account::Account;
+    use utils::crypto::dev_account;
 
     lazy_static! {
         pub(crate) static ref ACCOUNT_1: Account =
             H160::from_str("0x4a0d457e884ebd9b9773d172ed687417caac4f14").unwrap();
-        pub(crate) static ref ACCOUNT_2: Account = Account::random();
+        pub(crate) static ref ACCOUNT_2: Account = dev_account(1).1;
         pub(crate) static ref ACCOUNT_1_NONCE: Mutex<U256> = Mutex::new(U256::zero());
     }
 
@@ -25,6 +30,47 @@ pub(crate) mod tests {
         Web3::new("http://127.0.0.1:8545").unwrap()
     }
 
+    /// A stand-in for a node, serving `module` over http on a free local port
+    /// until the handle is dropped. Returns the node's url.
+    pub(crate) async fn mock_node<Context: Send + Sync + 'static>(
+        module: RpcModule<Context>,
+    ) -> (String, ServerHandle) {
+        mock_node_with_logger((), module).await
+    }
+
+    /// A `mock_node` that reports each request to `logger`
+    pub(crate) async fn mock_node_with_logger<L: Logger, Context: Send + Sync + 'static>(
+        logger: L,
+        module: RpcModule<Context>,
+    ) -> (String, ServerHandle) {
+        let (address, handle) = serve(logger, module).await;
+
+        (format!("http://{}", address), handle)
+    }
+
+    /// A `mock_node` reached over websockets, for subscriptions
+    pub(crate) async fn mock_ws_node<Context: Send + Sync + 'static>(
+        module: RpcModule<Context>,
+    ) -> (String, ServerHandle) {
+        let (address, handle) = serve((), module).await;
+
+        (format!("ws://{}", address), handle)
+    }
+
+    async fn serve<L: Logger, Context: Send + Sync + 'static>(
+        logger: L,
+        module: RpcModule<Context>,
+    ) -> (SocketAddr, ServerHandle) {
+        let server = ServerBuilder::default()
+            .set_logger(logger)
+            .build("127.0.0.1:0")
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+
+        (address, server.start(module).unwrap())
+    }
+
     pub fn get_contract() -> Vec<u8> {
         include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20_wit.wasm").to_vec()
     }
@@ -0,0 +1,213 @@
+//! # Idempotency
+//!
+//! Guard against sending the same transaction twice.
+//! Each send carries a caller-chosen key. The first send with a key submits
+//! the transaction and records its hash, and later sends with that key return
+//! the recorded hash without submitting again, so retries can't double-spend.
+//! Sends with the same key wait on each other rather than racing to submit.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use ethereum_types::H256;
+use types::transaction::TransactionRequest;
+
+use crate::error::Result;
+use crate::Web3;
+
+/// Identifies a logical send across retries
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IdempotencyKey(String);
+
+impl IdempotencyKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for IdempotencyKey {
+    fn from(key: &str) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<String> for IdempotencyKey {
+    fn from(key: String) -> Self {
+        Self::new(key)
+    }
+}
+
+/// Where the hashes of completed sends are kept.
+/// Implement this to share keys between processes or keep them across restarts.
+pub trait IdempotencyStore: Send + Sync {
+    /// The hash of the transaction sent with a key, if any
+    fn get(&self, key: &IdempotencyKey) -> Option<H256>;
+
+    /// Record the hash of the transaction sent with a key
+    fn insert(&self, key: IdempotencyKey, tx_hash: H256);
+}
+
+/// Keys kept in memory for the life of the client
+#[derive(Debug, Default)]
+pub struct MemoryStore(Mutex<HashMap<IdempotencyKey, H256>>);
+
+impl IdempotencyStore for MemoryStore {
+    fn get(&self, key: &IdempotencyKey) -> Option<H256> {
+        self.0.lock().unwrap().get(key).copied()
+    }
+
+    fn insert(&self, key: IdempotencyKey, tx_hash: H256) {
+        self.0.lock().unwrap().insert(key, tx_hash);
+    }
+}
+
+/// Completed sends, plus a lock per key for sends in flight
+pub(crate) struct Idempotency {
+    store: Arc<dyn IdempotencyStore>,
+    in_flight: Mutex<HashMap<IdempotencyKey, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl Idempotency {
+    pub(crate) fn new(store: Arc<dyn IdempotencyStore>) -> Self {
+        Self {
+            store,
+            in_flight: Default::default(),
+        }
+    }
+
+    fn lock(&self, key: &IdempotencyKey) -> Arc<tokio::sync::Mutex<()>> {
+        let mut in_flight = self.in_flight.lock().unwrap();
+
+        Arc::clone(in_flight.entry(key.clone()).or_default())
+    }
+
+    /// Drop the lock of a key once nobody else is waiting on it
+    fn release(&self, key: &IdempotencyKey, lock: Arc<tokio::sync::Mutex<()>>) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+
+        // one reference in the map and one held here
+        if Arc::strong_count(&lock) == 2 {
+            in_flight.remove(key);
+        }
+    }
+}
+
+impl Default for Idempotency {
+    fn default() -> Self {
+        Self::new(Arc::new(MemoryStore::default()))
+    }
+}
+
+impl Web3 {
+    /// Use a different store for idempotency keys (in memory by default).
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use web3::idempotency::MemoryStore;
+    ///
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545")
+    ///     .unwrap()
+    ///     .with_idempotency_store(MemoryStore::default());
+    /// ```
+    pub fn with_idempotency_store(mut self, store: impl IdempotencyStore + 'static) -> Self {
+        self.idempotency = Arc::new(Idempotency::new(Arc::new(store)));
+        self
+    }
+
+    /// Send a transaction at most once per key.
+    /// A repeated key returns the hash of the first send instead of sending again.
+    /// Failed sends aren't recorded, so they can be retried with the same key.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let key = IdempotencyKey::from("order-1234");
+    /// let tx_hash = web3.send_transaction_idempotent(request.clone(), key.clone()).await?;
+    /// let retried = web3.send_transaction_idempotent(request, key).await?;
+    /// assert_eq!(tx_hash, retried);
+    /// ```
+    pub async fn send_transaction_idempotent(
+        &self,
+        transaction_request: TransactionRequest,
+        key: IdempotencyKey,
+    ) -> Result<H256> {
+        let lock = self.idempotency.lock(&key);
+        let guard = lock.lock().await;
+
+        let tx_hash = match self.idempotency.store.get(&key) {
+            Some(tx_hash) => Ok(tx_hash),
+            None => self
+                .send(transaction_request)
+                .await
+                .inspect(|tx_hash| self.idempotency.store.insert(key.clone(), *tx_hash)),
+        };
+
+        drop(guard);
+        self.idempotency.release(&key, lock);
+
+        tx_hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use jsonrpsee::server::ServerHandle;
+    use jsonrpsee::RpcModule;
+    use types::account::Account;
+
+    use super::*;
+    use crate::helpers::tests::mock_node;
+
+    /// A node that counts the transactions it's sent, hashing each by its count
+    async fn counting_node() -> (Web3, Arc<AtomicU64>, ServerHandle) {
+        let sent = Arc::new(AtomicU64::new(0));
+        let mut module = RpcModule::new(Arc::clone(&sent));
+
+        module
+            .register_method("eth_sendTransaction", |_, sent| {
+                let count = sent.fetch_add(1, Ordering::SeqCst) + 1;
+
+                Ok(H256::from_low_u64_be(count))
+            })
+            .unwrap();
+
+        let (url, handle) = mock_node(module).await;
+
+        (Web3::new(&url).unwrap(), sent, handle)
+    }
+
+    #[tokio::test]
+    async fn it_sends_once_per_idempotency_key() {
+        let (web3, sent, _handle) = counting_node().await;
+        let transfer = TransactionRequest {
+            to: Some(Account::random()),
+            ..Default::default()
+        };
+        let key = IdempotencyKey::from("transfer-1");
+
+        let (first, second) = tokio::join!(
+            web3.send_transaction_idempotent(transfer.clone(), key.clone()),
+            web3.send_transaction_idempotent(transfer.clone(), key),
+        );
+        assert_eq!(first.unwrap(), second.unwrap());
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+
+        // a new key sends again
+        let third = web3
+            .send_transaction_idempotent(transfer, "transfer-2".into())
+            .await
+            .unwrap();
+        assert_eq!(third, H256::from_low_u64_be(2));
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+        assert!(web3.idempotency.in_flight.lock().unwrap().is_empty());
+    }
+}
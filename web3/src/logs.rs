@@ -0,0 +1,239 @@
+//! # Logs
+//!
+//! Retrieve the logs emitted by contracts.
+//! Logs are ordered by block number and then by their index within the block,
+//! so a `LogCursor` marks an exact position that an indexer can resume from.
+//!
+//! see https://ethereum.org/en/developers/docs/smart-contracts/anatomy/#events-and-logs
+
+////////////////////////////////////////////////////////////////////////////////
+
+use ethereum_types::{U256, U64};
+use jsonrpsee::rpc_params;
+use serde::{Deserialize, Serialize};
+use types::block::BlockTag;
+use types::transaction::{Log, LogFilter};
+
+use crate::error::{Result, Web3Error};
+use crate::Web3;
+
+/// The position of the last processed log
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct LogCursor {
+    pub block_number: U64,
+    pub log_index: U256,
+}
+
+impl TryFrom<&Log> for LogCursor {
+    type Error = Web3Error;
+
+    fn try_from(log: &Log) -> Result<Self> {
+        match (log.block_number, log.log_index) {
+            (Some(block_number), Some(log_index)) => Ok(LogCursor {
+                block_number,
+                log_index,
+            }),
+            _ => Err(Web3Error::RpcResponseError(format!(
+                "log is missing its position: {:?}",
+                log
+            ))),
+        }
+    }
+}
+
+impl Web3 {
+    /// Retrieve the logs matching a filter.
+    /// Fails without calling the node if the filter combines a block hash with a range.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_getLogs
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let logs = web3.get_logs(&LogFilter::default()).await;
+    /// assert!(logs.is_ok());
+    /// ```
+    pub async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>> {
+        filter
+            .validate()
+            .map_err(|e| Web3Error::RpcRequestError(e.to_string()))?;
+
+        let params = rpc_params![filter];
+        let response = self.send_rpc("eth_getLogs", params).await?;
+        let logs: Vec<Log> = self.deserialize(response)?;
+
+        Ok(logs)
+    }
+
+    /// Retrieve up to `limit` logs after the cursor, returning them with the
+    /// cursor to pass to the next call.
+    ///
+    /// The query restarts at the cursor's block, so logs later in that block
+    /// are not missed, and logs at or before the cursor are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let (logs, cursor) = web3.get_logs_after(LogFilter::default(), None, 100).await.unwrap();
+    /// let (more_logs, cursor) = web3.get_logs_after(LogFilter::default(), cursor, 100).await.unwrap();
+    /// ```
+    pub async fn get_logs_after(
+        &self,
+        mut filter: LogFilter,
+        cursor: Option<LogCursor>,
+        limit: usize,
+    ) -> Result<(Vec<Log>, Option<LogCursor>)> {
+        if let Some(cursor) = cursor {
+            filter.from_block = Some(BlockTag::Number(cursor.block_number));
+        }
+
+        let logs = self.get_logs(&filter).await?;
+
+        page_logs(logs, cursor, limit)
+    }
+}
+
+/// Order logs by position and take up to `limit` of those after the cursor
+fn page_logs(
+    logs: Vec<Log>,
+    cursor: Option<LogCursor>,
+    limit: usize,
+) -> Result<(Vec<Log>, Option<LogCursor>)> {
+    let mut positioned = logs
+        .into_iter()
+        .map(|log| Ok((LogCursor::try_from(&log)?, log)))
+        .collect::<Result<Vec<_>>>()?;
+
+    positioned.sort_by_key(|(position, _)| *position);
+
+    let page = positioned
+        .into_iter()
+        .filter(|(position, _)| cursor.is_none_or(|cursor| *position > cursor))
+        .take(limit)
+        .collect::<Vec<_>>();
+    let next_cursor = page.last().map(|(position, _)| *position).or(cursor);
+    let logs = page.into_iter().map(|(_, log)| log).collect();
+
+    Ok((logs, next_cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::mock_node;
+    use ethereum_types::{H160, H256};
+    use jsonrpsee::RpcModule;
+    use types::account::Account;
+    use types::bytes::Bytes;
+
+    fn log(block_number: u64, log_index: u64) -> Log {
+        Log {
+            address: H160::zero(),
+            block_hash: None,
+            block_number: Some(U64::from(block_number)),
+            data: Bytes::new(),
+            log_index: Some(U256::from(log_index)),
+            log_type: None,
+            removed: None,
+            topics: vec![],
+            transaction_hash: None,
+            transaction_index: Some(U64::zero()),
+            transaction_log_index: None,
+        }
+    }
+
+    #[test]
+    fn it_pages_logs_across_a_block_boundary() {
+        let logs = vec![log(2, 0), log(1, 1), log(1, 0), log(2, 1), log(1, 2)];
+
+        // the first page ends part way through block 2
+        let (first_page, cursor) = page_logs(logs.clone(), None, 4).unwrap();
+        assert_eq!(first_page, vec![log(1, 0), log(1, 1), log(1, 2), log(2, 0)]);
+
+        // the node returns everything from the cursor's block onwards
+        let from_cursor_block = logs
+            .into_iter()
+            .filter(|log| log.block_number >= cursor.map(|cursor| cursor.block_number))
+            .collect();
+        let (second_page, next_cursor) = page_logs(from_cursor_block, cursor, 4).unwrap();
+        assert_eq!(second_page, vec![log(2, 1)]);
+
+        // nothing left, so the cursor stays in place
+        let (third_page, last_cursor) = page_logs(vec![log(2, 1)], next_cursor, 4).unwrap();
+        assert!(third_page.is_empty());
+        assert_eq!(last_cursor, next_cursor);
+    }
+
+    #[test]
+    fn it_rejects_logs_without_a_position() {
+        let mut pending_log = log(1, 0);
+        pending_log.log_index = None;
+
+        assert!(page_logs(vec![pending_log], None, 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn it_gets_the_logs_of_a_contract_over_a_block_range() {
+        let (contract, other) = (Account::random(), Account::random());
+        let transfer = H256::from_low_u64_be(1);
+        let logs = (1..=6u64)
+            .map(|block_number| {
+                let mut log = log(block_number, 0);
+                log.address = if block_number % 2 == 0 {
+                    contract
+                } else {
+                    other
+                };
+                log.topics = vec![transfer];
+                log.data = Bytes::from(vec![block_number as u8]);
+                log
+            })
+            .collect::<Vec<_>>();
+        let mut module = RpcModule::new(logs);
+        module
+            .register_method("eth_getLogs", |params, logs| {
+                let filter: LogFilter = params.one()?;
+                let in_range = |log: &&Log| {
+                    let from = filter.from_block.and_then(|tag| tag.number());
+                    let to = filter.to_block.and_then(|tag| tag.number());
+                    from.is_none_or(|from| log.block_number >= Some(from))
+                        && to.is_none_or(|to| log.block_number <= Some(to))
+                };
+
+                Ok(logs
+                    .iter()
+                    .filter(in_range)
+                    .filter(|log| filter.matches(log))
+                    .cloned()
+                    .collect::<Vec<_>>())
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = Web3::new(&url).unwrap();
+
+        let filter = LogFilter {
+            from_block: Some(BlockTag::Number(U64::from(2))),
+            to_block: Some(BlockTag::Number(U64::from(5))),
+            address: Some(contract.into()),
+            topics: Some(vec![Some(vec![H256::zero(), transfer].into())]),
+            ..Default::default()
+        };
+        let logs = web3.get_logs(&filter).await.unwrap();
+        let positions = logs
+            .iter()
+            .map(|log| (log.address, log.block_number))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            positions,
+            vec![
+                (contract, Some(U64::from(2))),
+                (contract, Some(U64::from(4)))
+            ]
+        );
+        assert_eq!(logs[1].data, Bytes::from(vec![4]));
+    }
+}
@@ -0,0 +1,231 @@
+//! # Metrics
+//!
+//! Per-method call counts, latencies and error rates for the JSON-RPC calls a
+//! client makes, to help diagnose slow or unreliable providers.
+//! Only compiled with the `metrics` feature.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::error::{Result, Web3Error};
+use crate::Web3;
+
+/// The upper bound of each latency bucket, in milliseconds.
+/// Latencies above the last bound fall into an overflow bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 10] = [5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000];
+
+/// Metrics for every method called, keyed by method name
+pub type MetricsSnapshot = BTreeMap<String, MethodMetrics>;
+
+/// How a call ended.
+///
+///   * Success: the node returned a result.
+///   * Rejected: the node refused the call with an error (e.g. a nonce that's too low).
+///   * Failed: no usable response (e.g. a transport error, a timeout or an
+///     internal error of the node).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    Rejected,
+    Failed,
+}
+
+impl From<&Result<Value>> for Outcome {
+    fn from(response: &Result<Value>) -> Self {
+        match response {
+            Ok(_) => Outcome::Success,
+            Err(error) => error.into(),
+        }
+    }
+}
+
+impl From<&Web3Error> for Outcome {
+    fn from(error: &Web3Error) -> Self {
+        match error {
+            Web3Error::GasPriceTooLow(_)
+            | Web3Error::InsufficientFunds(_)
+            | Web3Error::MethodNotFound(_)
+            | Web3Error::NonceTooHigh(_)
+            | Web3Error::NonceTooLow(_)
+            | Web3Error::RawTransactionRejected { .. }
+            | Web3Error::ReplacementUnderpriced(_)
+            | Web3Error::Rpc { .. }
+            | Web3Error::TransactionTooLarge(_)
+            | Web3Error::WouldRevert { .. } => Outcome::Rejected,
+            // the node answered, but failed rather than refused the call
+            _ => Outcome::Failed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// The number of calls in each bucket of `LATENCY_BUCKETS_MS`, plus the overflow bucket
+    pub counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl LatencyHistogram {
+    fn observe(&mut self, latency: Duration) {
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| latency <= Duration::from_millis(*bound))
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+
+        self.counts[bucket] += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+
+    pub fn mean(&self) -> Duration {
+        let count = self.counts.iter().sum::<u64>();
+
+        match count {
+            0 => Duration::ZERO,
+            _ => self.total / count as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MethodMetrics {
+    pub calls: u64,
+    pub successes: u64,
+    pub rejections: u64,
+    pub failures: u64,
+    pub latency: LatencyHistogram,
+}
+
+impl MethodMetrics {
+    /// The fraction of calls that were rejected or failed
+    pub fn error_rate(&self) -> f64 {
+        match self.calls {
+            0 => 0.0,
+            calls => (self.rejections + self.failures) as f64 / calls as f64,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    methods: Mutex<MetricsSnapshot>,
+}
+
+impl Metrics {
+    pub(crate) fn record(&self, method: &str, latency: Duration, outcome: Outcome) {
+        let mut methods = self.methods.lock().unwrap_or_else(|e| e.into_inner());
+        let metrics = methods.entry(method.into()).or_default();
+
+        metrics.calls += 1;
+        metrics.latency.observe(latency);
+
+        match outcome {
+            Outcome::Success => metrics.successes += 1,
+            Outcome::Rejected => metrics.rejections += 1,
+            Outcome::Failed => metrics.failures += 1,
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> MetricsSnapshot {
+        self.methods
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl Web3 {
+    /// The metrics of every call made by this client and its clones so far.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// web3.get_block_number().await.unwrap();
+    ///
+    /// let metrics = web3.metrics_snapshot();
+    /// println!("{:?}", metrics["eth_blockNumber"].latency.mean());
+    /// ```
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::rpc_params;
+
+    #[test]
+    fn it_records_outcomes_and_latencies() {
+        let metrics = Metrics::default();
+        metrics.record("eth_call", Duration::from_millis(3), Outcome::Success);
+        metrics.record("eth_call", Duration::from_millis(40), Outcome::Rejected);
+        metrics.record("eth_call", Duration::from_secs(10), Outcome::Failed);
+
+        let snapshot = metrics.snapshot();
+        let eth_call = &snapshot["eth_call"];
+
+        assert_eq!(eth_call.calls, 3);
+        assert_eq!(
+            (eth_call.successes, eth_call.rejections, eth_call.failures),
+            (1, 1, 1)
+        );
+        assert!((eth_call.error_rate() - 2.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(eth_call.latency.counts[0], 1);
+        assert_eq!(eth_call.latency.counts[3], 1);
+        assert_eq!(eth_call.latency.counts[LATENCY_BUCKETS_MS.len()], 1);
+        assert_eq!(eth_call.latency.max, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn it_categorizes_responses() {
+        let rejected = Err(Web3Error::NonceTooLow("1".into()));
+        let failed = Err(Web3Error::RpcRequestError("connection refused".into()));
+
+        assert_eq!(Outcome::from(&Ok(Value::Null)), Outcome::Success);
+        assert_eq!(Outcome::from(&rejected), Outcome::Rejected);
+        assert_eq!(Outcome::from(&failed), Outcome::Failed);
+
+        let rpc = Web3Error::Rpc {
+            code: -32602,
+            message: "invalid params".into(),
+            data: None,
+        };
+        let raw_transaction = Web3Error::RawTransactionRejected {
+            code: -32003,
+            message: "invalid transaction".into(),
+            data: None,
+        };
+        let internal = Web3Error::InternalError("database closed".into());
+
+        assert_eq!(Outcome::from(&rpc), Outcome::Rejected);
+        assert_eq!(Outcome::from(&raw_transaction), Outcome::Rejected);
+        assert_eq!(Outcome::from(&internal), Outcome::Failed);
+    }
+
+    #[tokio::test]
+    async fn it_counts_calls_per_method() {
+        // nothing is listening, so every call fails
+        let web3 = Web3::new("http://127.0.0.1:1").unwrap();
+
+        for _ in 0..3 {
+            let _ = web3.send_rpc("eth_blockNumber", rpc_params![]).await;
+        }
+
+        let _ = web3.clone().send_rpc("eth_chainId", rpc_params![]).await;
+
+        let snapshot = web3.metrics_snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot["eth_blockNumber"].calls, 3);
+        assert_eq!(snapshot["eth_blockNumber"].failures, 3);
+        assert_eq!(snapshot["eth_chainId"].calls, 1);
+        assert!((snapshot["eth_chainId"].error_rate() - 1.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,213 @@
+//! # Node
+//!
+//! Query the state of the node itself rather than the chain.
+//! A node is ready to serve traffic once it's connected to the network and
+//! has caught up with the head of the chain.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use ethereum_types::U64;
+use jsonrpsee::rpc_params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::Result;
+use crate::Web3;
+
+/// How far along a syncing node is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    pub starting_block: U64,
+    pub current_block: U64,
+    pub highest_block: U64,
+}
+
+impl Web3 {
+    /// Whether the node is listening for network connections.
+    ///
+    /// See https://eth.wiki/json-rpc/API#net_listening
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let listening = web3.net_listening().await;
+    /// assert!(listening.is_ok());
+    /// ```
+    pub async fn net_listening(&self) -> Result<bool> {
+        let response = self.send_rpc("net_listening", rpc_params![]).await?;
+        let listening: bool = self.deserialize(response)?;
+
+        Ok(listening)
+    }
+
+    /// The sync progress of the node, or `None` once it has caught up.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_syncing
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let progress = web3.syncing().await.unwrap();
+    /// assert!(progress.is_none());
+    /// ```
+    pub async fn syncing(&self) -> Result<Option<SyncProgress>> {
+        let response = self.send_rpc("eth_syncing", rpc_params![]).await?;
+
+        // nodes return `false` rather than `null` once synced
+        if response == Value::Bool(false) {
+            return Ok(None);
+        }
+
+        let progress: SyncProgress = self.deserialize(response)?;
+
+        Ok(Some(progress))
+    }
+
+    /// The id of the node's chain, signed into transactions for replay protection.
+    /// The configured id is used if set, otherwise it's fetched once and cached.
+    ///
+    /// See https://eips.ethereum.org/EIPS/eip-695
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let chain_id = web3.chain_id().await.unwrap();
+    /// assert_eq!(chain_id, 1337);
+    /// ```
+    pub async fn chain_id(&self) -> Result<u64> {
+        // held while fetching, so concurrent callers share a single request
+        let mut cached = self.chain_id.lock().await;
+
+        if let Some(chain_id) = *cached {
+            return Ok(chain_id);
+        }
+
+        let response = self.send_rpc("eth_chainId", rpc_params![]).await?;
+        let chain_id: U64 = self.deserialize(response)?;
+        *cached = Some(chain_id.as_u64());
+
+        Ok(chain_id.as_u64())
+    }
+
+    /// Override the cached chain id, or clear it with `None` so the next call
+    /// to `chain_id` fetches it again. Clones of the client share the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// web3.set_chain_id(Some(1)).await;
+    /// assert_eq!(web3.chain_id().await.unwrap(), 1);
+    /// ```
+    pub async fn set_chain_id(&self, chain_id: Option<u64>) {
+        *self.chain_id.lock().await = chain_id;
+    }
+
+    /// Whether the node is listening and not syncing, and so ready for traffic.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let ready = web3.is_ready().await.unwrap();
+    /// ```
+    pub async fn is_ready(&self) -> Result<bool> {
+        let (listening, syncing) = tokio::try_join!(self.net_listening(), self.syncing())?;
+
+        Ok(listening && syncing.is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpsee::server::ServerHandle;
+    use jsonrpsee::RpcModule;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::config::Web3Config;
+    use crate::helpers::tests::mock_node;
+
+    async fn node(syncing: Value) -> (Web3, ServerHandle) {
+        let mut module = RpcModule::new(syncing);
+
+        module
+            .register_method("net_listening", |_, _| Ok(true))
+            .unwrap();
+        module
+            .register_method("eth_syncing", |_, syncing| Ok(syncing.clone()))
+            .unwrap();
+
+        let (url, handle) = mock_node(module).await;
+
+        (Web3::new(&url).unwrap(), handle)
+    }
+
+    #[tokio::test]
+    async fn it_fetches_the_chain_id_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut module = RpcModule::new(calls.clone());
+        module
+            .register_method("eth_chainId", |_, calls| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(U64::from(1337))
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+
+        let web3 = Web3::new(&url).unwrap();
+        assert_eq!(web3.chain_id().await.unwrap(), 1337);
+        assert_eq!(web3.chain_id().await.unwrap(), 1337);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // a configured chain id is never fetched
+        let config = Web3Config::builder().chain_id(1).build();
+        let web3 = Web3::with_config(&url, config).unwrap();
+        assert_eq!(web3.chain_id().await.unwrap(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // clearing the cache fetches it again
+        web3.set_chain_id(None).await;
+        assert_eq!(web3.chain_id().await.unwrap(), 1337);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        web3.set_chain_id(Some(5)).await;
+        assert_eq!(web3.clone().chain_id().await.unwrap(), 5);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn it_is_not_ready_while_syncing() {
+        let (web3, _handle) = node(json!({
+            "startingBlock": "0x0",
+            "currentBlock": "0x10",
+            "highestBlock": "0x20"
+        }))
+        .await;
+
+        assert_eq!(
+            web3.syncing().await.unwrap(),
+            Some(SyncProgress {
+                starting_block: U64::from(0),
+                current_block: U64::from(0x10),
+                highest_block: U64::from(0x20),
+            })
+        );
+        assert!(!web3.is_ready().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn it_is_ready_once_synced() {
+        let (web3, _handle) = node(json!(false)).await;
+
+        assert!(web3.net_listening().await.unwrap());
+        assert_eq!(web3.syncing().await.unwrap(), None);
+        assert!(web3.is_ready().await.unwrap());
+    }
+}
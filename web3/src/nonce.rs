@@ -0,0 +1,185 @@
+//! # Nonces
+//!
+//! Assign nonces on the client rather than threading them through every send.
+//! Each account sent from gets a `NonceManager`, which fetches the account's
+//! nonce from the node on its first send and takes the next nonce locally
+//! after that. Sends from an account take turns, so concurrent sends never
+//! share a nonce. A failed send leaves its nonce for the next one, and nonce
+//! errors resync the account from the node.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use ethereum_types::H256;
+use tokio::sync::Mutex;
+use types::account::Account;
+use types::transaction::TransactionRequest;
+use utils::crypto::SecretKeyExt;
+use utils::SecretKey;
+
+use crate::error::Result;
+use crate::sender::NonceManager;
+use crate::Web3;
+
+/// The nonce manager of each account sent from
+#[derive(Default)]
+pub(crate) struct Nonces(DashMap<Account, Arc<Mutex<NonceManager>>>);
+
+impl Web3 {
+    /// Sign and send a transaction, assigning the next nonce of the key's account.
+    /// Any `from` or `nonce` in the request is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let (key, _) = utils::crypto::dev_account(0);
+    /// let (first, second) = tokio::join!(
+    ///     web3.send_with_managed_nonce(transaction_request.clone(), key),
+    ///     web3.send_with_managed_nonce(transaction_request, key),
+    /// );
+    /// ```
+    pub async fn send_with_managed_nonce(
+        &self,
+        transaction_request: TransactionRequest,
+        key: SecretKey,
+    ) -> Result<H256> {
+        let account = key.address();
+        let nonce_manager = self
+            .nonces
+            .0
+            .entry(account)
+            .or_insert_with(|| Arc::new(Mutex::new(NonceManager::new(account, key))))
+            .clone();
+        let mut nonce_manager = nonce_manager.lock().await;
+
+        nonce_manager.submit(self, transaction_request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use jsonrpsee::core::Error as JsonRpseeError;
+    use jsonrpsee::server::ServerHandle;
+    use jsonrpsee::types::error::{CallError, ErrorObject};
+    use jsonrpsee::RpcModule;
+    use types::error::{INSUFFICIENT_FUNDS_CODE, NONCE_TOO_LOW_CODE};
+    use utils::crypto::dev_account;
+
+    use ethereum_types::{U256, U64};
+    use types::transaction::Transaction;
+
+    use super::*;
+    use crate::error::Web3Error;
+    use crate::helpers::tests::mock_node;
+
+    type Sent = Arc<Mutex<Vec<U256>>>;
+
+    /// A node with no transactions from the account yet, that records the nonce
+    /// of each raw transaction it accepts. Transfers of `UNAFFORDABLE` fail.
+    async fn node() -> (Web3, Sent, ServerHandle) {
+        let sent: Sent = Arc::new(Mutex::new(vec![]));
+        let mut module = RpcModule::new(sent.clone());
+
+        module
+            .register_method("eth_chainId", |_, _| Ok(U64::from(1337)))
+            .unwrap();
+        module
+            .register_method("eth_getTransactionCount", |_, sent| {
+                Ok(sent
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .max()
+                    .copied()
+                    .unwrap_or_default())
+            })
+            .unwrap();
+        module
+            .register_method("eth_sendRawTransaction", |params, sent| {
+                let raw = params.one::<String>()?;
+                let signed_transaction = Transaction::from_raw_hex(&raw).unwrap();
+                let transaction: Transaction = signed_transaction.try_into().unwrap();
+                let nonce = transaction.nonce.unwrap();
+                let mut sent = sent.lock().unwrap();
+                let error = |code, message: &str| -> std::result::Result<H256, JsonRpseeError> {
+                    let error = ErrorObject::owned(code, message, None::<()>);
+                    Err(CallError::Custom(error).into())
+                };
+
+                if transaction.value == U256::from(UNAFFORDABLE) {
+                    return error(INSUFFICIENT_FUNDS_CODE, "Insufficient funds");
+                }
+
+                if sent.contains(&nonce) {
+                    return error(NONCE_TOO_LOW_CODE, "Nonce too low");
+                }
+
+                sent.push(nonce);
+
+                Ok(transaction.transaction_hash().unwrap())
+            })
+            .unwrap();
+
+        let (url, handle) = mock_node(module).await;
+
+        (Web3::new(&url).unwrap(), sent, handle)
+    }
+
+    const UNAFFORDABLE: u64 = 1_000;
+
+    fn transfer(value: u64) -> TransactionRequest {
+        TransactionRequest {
+            to: Some(Account::random()),
+            value: Some(U256::from(value)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn it_assigns_each_concurrent_send_its_own_nonce() {
+        let (web3, sent, _handle) = node().await;
+        let (key, _) = dev_account(0);
+
+        let (first, second, third) = tokio::join!(
+            web3.send_with_managed_nonce(transfer(10), key),
+            web3.send_with_managed_nonce(transfer(10), key),
+            web3.send_with_managed_nonce(transfer(10), key),
+        );
+        assert!(first.is_ok() && second.is_ok() && third.is_ok());
+
+        let mut nonces = sent.lock().unwrap().clone();
+        nonces.sort();
+        assert_eq!(nonces, vec![U256::from(1), U256::from(2), U256::from(3)]);
+    }
+
+    #[tokio::test]
+    async fn it_reuses_the_nonce_of_a_failed_send() {
+        let (web3, sent, _handle) = node().await;
+        let (key, _) = dev_account(0);
+
+        web3.send_with_managed_nonce(transfer(10), key)
+            .await
+            .unwrap();
+        let response = web3
+            .send_with_managed_nonce(transfer(UNAFFORDABLE), key)
+            .await;
+        assert!(matches!(response, Err(Web3Error::InsufficientFunds(_))));
+
+        web3.send_with_managed_nonce(transfer(10), key)
+            .await
+            .unwrap();
+        assert_eq!(*sent.lock().unwrap(), vec![U256::from(1), U256::from(2)]);
+
+        // another client uses the next nonce, so it's resynced from the node
+        sent.lock().unwrap().push(U256::from(3));
+        web3.send_with_managed_nonce(transfer(10), key)
+            .await
+            .unwrap();
+        assert_eq!(sent.lock().unwrap().last(), Some(&U256::from(4)));
+    }
+}
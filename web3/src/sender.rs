@@ -0,0 +1,270 @@
+//! # Sender
+//!
+//! Send transactions concurrently from a single account.
+//! A background task owns the account's nonce and submits one transaction at a
+//! time, so transactions land in order without gaps or reused nonces.
+//! Nonce errors resync the nonce from the node and retry.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use ethereum_types::{H256, U256};
+use tokio::sync::{mpsc, oneshot};
+use types::account::Account;
+use types::error::TypeError;
+use types::transaction::{Transaction, TransactionRequest};
+use utils::crypto::private_key_address;
+use utils::SecretKey;
+
+use crate::error::{Result, Web3Error};
+use crate::Web3;
+
+pub const MAX_NONCE_RETRIES: u32 = 3;
+
+/// Transactions waiting to be submitted
+const QUEUE_CAPACITY: usize = 256;
+
+type Submission = (TransactionRequest, oneshot::Sender<Result<H256>>);
+
+/// A handle to a sender task. Clone it to send from several tasks at once.
+/// The sender task stops once every handle has been dropped.
+#[derive(Debug, Clone)]
+pub struct TxSender {
+    account: Account,
+    submissions: mpsc::Sender<Submission>,
+}
+
+impl TxSender {
+    pub fn account(&self) -> Account {
+        self.account
+    }
+
+    /// Sign and send a transaction from the sender's account, assigning the next nonce.
+    /// Any `from` or `nonce` in the request is replaced.
+    pub async fn send(&self, transaction_request: TransactionRequest) -> Result<H256> {
+        let (responder, response) = oneshot::channel();
+
+        self.submissions
+            .send((transaction_request, responder))
+            .await
+            .map_err(|_| Web3Error::SenderStopped)?;
+
+        response.await.map_err(|_| Web3Error::SenderStopped)?
+    }
+}
+
+/// Assigns the nonces of an account's transactions, one submission at a time.
+/// A failed send leaves its nonce for the next one.
+pub(crate) struct NonceManager {
+    account: Account,
+    key: SecretKey,
+    next_nonce: Option<U256>,
+}
+
+impl NonceManager {
+    pub(crate) fn new(account: Account, key: SecretKey) -> Self {
+        Self {
+            account,
+            key,
+            next_nonce: None,
+        }
+    }
+
+    async fn run(mut self, web3: Web3, mut submissions: mpsc::Receiver<Submission>) {
+        while let Some((transaction_request, responder)) = submissions.recv().await {
+            let response = self.submit(&web3, transaction_request).await;
+
+            // the caller may no longer be waiting for the response
+            let _ = responder.send(response);
+        }
+    }
+
+    /// Sign and send a transaction with the account's next nonce, resyncing
+    /// the nonce from the node and retrying on nonce errors
+    pub(crate) async fn submit(
+        &mut self,
+        web3: &Web3,
+        transaction_request: TransactionRequest,
+    ) -> Result<H256> {
+        let mut attempt = 0;
+
+        loop {
+            let nonce = self.next_nonce(web3).await?;
+            let response = self
+                .send_raw(web3, transaction_request.clone(), nonce)
+                .await;
+
+            match response {
+                Ok(transaction_hash) => {
+                    self.next_nonce = Some(nonce + 1);
+                    return Ok(transaction_hash);
+                }
+                Err(Web3Error::NonceTooLow(_) | Web3Error::NonceTooHigh(_))
+                    if attempt < MAX_NONCE_RETRIES =>
+                {
+                    attempt += 1;
+                    self.next_nonce = None;
+                    log::warn!(
+                        "Resyncing the nonce of {:?} (attempt {})",
+                        self.account,
+                        attempt
+                    );
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// The nonce for the next transaction, fetched from the node when not known
+    async fn next_nonce(&mut self, web3: &Web3) -> Result<U256> {
+        match self.next_nonce {
+            Some(nonce) => Ok(nonce),
+            // the node reports the last nonce used
+            None => Ok(web3.get_transaction_count(self.account).await? + 1),
+        }
+    }
+
+    async fn send_raw(
+        &self,
+        web3: &Web3,
+        mut transaction_request: TransactionRequest,
+        nonce: U256,
+    ) -> Result<H256> {
+        transaction_request.from = Some(self.account);
+        transaction_request.nonce = Some(nonce);
+
+        let transaction: Transaction = transaction_request
+            .try_into()
+            .map_err(|e: TypeError| Web3Error::TransactionSigningError(e.to_string()))?;
+        let signed_transaction = web3.sign_transaction(transaction, self.key).await?;
+
+        web3.send_raw(signed_transaction).await
+    }
+}
+
+impl Web3 {
+    /// Start a task that sends transactions from the account of `key`,
+    /// returning a handle to it.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let (key, _) = utils::crypto::dev_account(0);
+    /// let sender = web3.spawn_sender(key);
+    ///
+    /// let sends = (0..10).map(|_| sender.send(transaction_request.clone()));
+    /// let transaction_hashes = futures::future::join_all(sends).await;
+    /// ```
+    pub fn spawn_sender(&self, key: SecretKey) -> TxSender {
+        let account = private_key_address(&key);
+        let (submissions, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let nonce_manager = NonceManager::new(account, key);
+
+        tokio::spawn(nonce_manager.run(self.clone(), receiver));
+
+        TxSender {
+            account,
+            submissions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::mock_node;
+    use ethereum_types::U64;
+    use jsonrpsee::server::ServerHandle;
+    use jsonrpsee::types::error::{CallError, ErrorObject};
+    use jsonrpsee::RpcModule;
+    use std::sync::{Arc, Mutex};
+    use types::error::NONCE_TOO_LOW_CODE;
+    use utils::crypto::dev_account;
+
+    type Nonces = Arc<Mutex<Vec<U256>>>;
+
+    /// A node that records the nonce of each raw transaction it accepts
+    async fn node() -> (Web3, Nonces, ServerHandle) {
+        let nonces: Nonces = Arc::new(Mutex::new(vec![]));
+        let mut module = RpcModule::new(nonces.clone());
+
+        module
+            .register_method("eth_chainId", |_, _| Ok(U64::from(1337)))
+            .unwrap();
+        module
+            .register_method("eth_getTransactionCount", |_, nonces| {
+                Ok(nonces.lock().unwrap().last().copied().unwrap_or_default())
+            })
+            .unwrap();
+        module
+            .register_method("eth_sendRawTransaction", |params, nonces| {
+                let raw = params.one::<String>()?;
+                let signed_transaction = Transaction::from_raw_hex(&raw).unwrap();
+                let transaction: Transaction = signed_transaction.try_into().unwrap();
+                let nonce = transaction.nonce.unwrap();
+                let mut nonces = nonces.lock().unwrap();
+
+                if matches!(nonces.last(), Some(last) if nonce <= *last) {
+                    let error = ErrorObject::owned(NONCE_TOO_LOW_CODE, "Nonce too low", None::<()>);
+                    return Err(CallError::Custom(error).into());
+                }
+
+                nonces.push(nonce);
+
+                Ok(transaction.transaction_hash().unwrap())
+            })
+            .unwrap();
+
+        let (url, handle) = mock_node(module).await;
+
+        (Web3::new(&url).unwrap(), nonces, handle)
+    }
+
+    fn transfer() -> TransactionRequest {
+        TransactionRequest {
+            to: Some(Account::random()),
+            value: Some(U256::from(10)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn it_sends_concurrently_without_nonce_gaps() {
+        let (web3, nonces, _handle) = node().await;
+        let (key, account) = dev_account(0);
+        let sender = web3.spawn_sender(key);
+        assert_eq!(sender.account(), account);
+
+        let sends = (0..10)
+            .map(|_| {
+                let sender = sender.clone();
+                tokio::spawn(async move { sender.send(transfer()).await })
+            })
+            .collect::<Vec<_>>();
+
+        for send in sends {
+            assert!(send.await.unwrap().is_ok());
+        }
+
+        let expected = (1..=10).map(U256::from).collect::<Vec<_>>();
+        assert_eq!(*nonces.lock().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn it_resyncs_the_nonce_after_a_nonce_error() {
+        let (web3, nonces, _handle) = node().await;
+        let (key, _) = dev_account(0);
+        let sender = web3.spawn_sender(key);
+
+        assert!(sender.send(transfer()).await.is_ok());
+
+        // another client uses the next nonce
+        nonces.lock().unwrap().push(U256::from(2));
+
+        assert!(sender.send(transfer()).await.is_ok());
+        assert_eq!(
+            *nonces.lock().unwrap(),
+            vec![U256::from(1), U256::from(2), U256::from(3)]
+        );
+    }
+}
@@ -0,0 +1,811 @@
+//! # Subscriptions
+//!
+//! Subscribe to new blocks by polling the node, or with `eth_subscribe` when
+//! the client was created with a `ws://` or `wss://` url.
+//! Blocks are buffered in a bounded channel so a slow consumer can't cause
+//! unbounded memory growth. When the buffer overflows, the oldest blocks are
+//! dropped and the consumer is told how many it missed.
+//!
+//! Pending transactions and the logs touching an account are only available
+//! over WebSocket.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use ethereum_types::{H256, U64};
+use futures::{Stream, StreamExt};
+use jsonrpsee::core::client::{Subscription, SubscriptionClientT};
+use jsonrpsee::rpc_params;
+use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
+use serde::Deserialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time;
+use types::account::Account;
+use types::block::Block;
+use types::transaction::{Log, LogFilter, Transaction};
+
+use crate::error::{Result, Web3Error};
+use crate::Web3;
+
+pub const DEFAULT_CAPACITY: usize = 64;
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// The topics after the event signature, which hold an event's indexed params
+const INDEXED_TOPICS: usize = 3;
+
+/// What to do when the consumer falls behind and blocks are dropped.
+///
+///   * DropOldest: yield a `NewHead::Lagged` marker and keep going.
+///   * Error: yield a `Web3Error::SubscriptionLagged` error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LagPolicy {
+    #[default]
+    DropOldest,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct SubscriptionConfig {
+    /// The maximum number of blocks buffered for the consumer
+    pub capacity: usize,
+    pub poll_interval: Duration,
+    pub lag_policy: LagPolicy,
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            lag_policy: LagPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NewHead {
+    Block(Box<Block>),
+    /// The consumer was too slow and `missed` blocks were dropped
+    Lagged {
+        missed: u64,
+    },
+}
+
+/// The fields of an `eth_subscribe` new head notification that are needed
+#[derive(Deserialize, Debug)]
+struct Head {
+    number: U64,
+}
+
+/// A stream of new blocks. Polling stops when this is dropped, and a WebSocket
+/// subscription is unsubscribed from.
+pub struct NewHeads {
+    receiver: broadcast::Receiver<Block>,
+    lag_policy: LagPolicy,
+    task: JoinHandle<()>,
+    /// Dropped to tell a WebSocket subscription to unsubscribe and end its task
+    unsubscribe: Option<oneshot::Sender<()>>,
+}
+
+impl NewHeads {
+    fn new(
+        receiver: broadcast::Receiver<Block>,
+        lag_policy: LagPolicy,
+        task: JoinHandle<()>,
+    ) -> Self {
+        Self {
+            receiver,
+            lag_policy,
+            task,
+            unsubscribe: None,
+        }
+    }
+
+    /// Wait for the next block, returning `None` once the subscription has ended.
+    pub async fn next(&mut self) -> Option<Result<NewHead>> {
+        match self.receiver.recv().await {
+            Ok(block) => Some(Ok(NewHead::Block(Box::new(block)))),
+            Err(RecvError::Lagged(missed)) => match self.lag_policy {
+                LagPolicy::DropOldest => Some(Ok(NewHead::Lagged { missed })),
+                LagPolicy::Error => Some(Err(Web3Error::SubscriptionLagged(missed))),
+            },
+            Err(RecvError::Closed) => None,
+        }
+    }
+}
+
+impl Drop for NewHeads {
+    fn drop(&mut self) {
+        // a WebSocket subscription ends itself once it has unsubscribed
+        if self.unsubscribe.take().is_none() {
+            self.task.abort();
+        }
+    }
+}
+
+/// A pending transaction, as a full body or just its hash
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum TxOrHash {
+    Hash(H256),
+    Transaction(Box<Transaction>),
+}
+
+/// A stream of transactions entering the node's mempool.
+/// The subscription is unsubscribed from when this is dropped.
+pub struct PendingTransactions {
+    receiver: mpsc::Receiver<Result<TxOrHash>>,
+    /// Dropped to tell the subscription to unsubscribe and end its task
+    _unsubscribe: oneshot::Sender<()>,
+}
+
+impl PendingTransactions {
+    /// Wait for the next pending transaction, returning `None` once the subscription has ended.
+    pub async fn next(&mut self) -> Option<Result<TxOrHash>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for PendingTransactions {
+    type Item = Result<TxOrHash>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// A stream of the logs that name an account in an indexed topic.
+/// The subscriptions are unsubscribed from when this is dropped.
+pub struct AccountActivity {
+    receiver: mpsc::Receiver<Result<Log>>,
+    /// Dropped to tell the subscriptions to unsubscribe and end their task
+    _unsubscribe: oneshot::Sender<()>,
+}
+
+impl AccountActivity {
+    /// Wait for the next log, returning `None` once the subscriptions have ended.
+    pub async fn next(&mut self) -> Option<Result<Log>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for AccountActivity {
+    type Item = Result<Log>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Web3 {
+    /// Subscribe to blocks produced after this call.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let mut new_heads = web3.subscribe_new_heads(SubscriptionConfig::default()).await.unwrap();
+    ///
+    /// while let Some(Ok(new_head)) = new_heads.next().await {
+    ///     println!("{:?}", new_head);
+    /// }
+    /// ```
+    pub async fn subscribe_new_heads(&self, config: SubscriptionConfig) -> Result<NewHeads> {
+        // a broadcast channel can't be built without room for a block
+        if config.capacity == 0 {
+            return Err(Web3Error::InvalidConfig(
+                "subscription capacity must be at least 1".into(),
+            ));
+        }
+
+        if self.endpoint().starts_with("ws://") || self.endpoint().starts_with("wss://") {
+            return self.subscribe_new_heads_ws(config).await;
+        }
+
+        let web3 = self.clone();
+        let mut next_block = *web3.get_block_number().await? + 1;
+        let (sender, receiver) = broadcast::channel(config.capacity);
+
+        let task = tokio::spawn(async move {
+            let mut interval = time::interval(config.poll_interval);
+
+            loop {
+                interval.tick().await;
+
+                let latest_block = match web3.get_block_number().await {
+                    Ok(block_number) => *block_number,
+                    Err(error) => {
+                        log::warn!("Could not poll for new blocks: {}", error);
+                        continue;
+                    }
+                };
+
+                while next_block <= latest_block {
+                    let Ok(block) = web3.get_block(next_block).await else {
+                        break;
+                    };
+
+                    // all receivers have been dropped
+                    if sender.send(block).is_err() {
+                        return;
+                    }
+
+                    next_block += U64::one();
+                }
+            }
+        });
+
+        Ok(NewHeads::new(receiver, config.lag_policy, task))
+    }
+
+    /// Subscribe to new heads with `eth_subscribe`, fetching the block of each
+    /// head the node notifies.
+    async fn subscribe_new_heads_ws(&self, config: SubscriptionConfig) -> Result<NewHeads> {
+        let web3 = self.clone();
+        let client = self.ws_client().await?;
+        let mut subscription: Subscription<Head> = client
+            .subscribe("eth_subscribe", rpc_params!["newHeads"], "eth_unsubscribe")
+            .await
+            .map_err(|e| Web3Error::RpcRequestError(e.to_string()))?;
+        let (sender, receiver) = broadcast::channel(config.capacity);
+        let (unsubscribe, mut unsubscribed) = oneshot::channel::<()>();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let head = tokio::select! {
+                    head = subscription.next() => head,
+                    _ = &mut unsubscribed => break,
+                };
+
+                let head = match head {
+                    Some(Ok(head)) => head,
+                    Some(Err(error)) => {
+                        log::warn!("Could not read a new head: {}", error);
+                        continue;
+                    }
+                    // the node ended the subscription
+                    None => return,
+                };
+
+                match web3.get_block(head.number).await {
+                    Ok(block) => {
+                        if sender.send(block).is_err() {
+                            break;
+                        }
+                    }
+                    Err(error) => log::warn!("Could not get block {}: {}", head.number, error),
+                }
+            }
+
+            if let Err(error) = subscription.unsubscribe().await {
+                log::warn!("Could not unsubscribe from new heads: {}", error);
+            }
+
+            // the client is kept until the subscription has ended
+            drop(client);
+        });
+
+        let mut new_heads = NewHeads::new(receiver, config.lag_policy, task);
+        new_heads.unsubscribe = Some(unsubscribe);
+
+        Ok(new_heads)
+    }
+
+    /// Subscribe to transactions entering the node's mempool over WebSocket.
+    /// With `full`, each transaction's body is yielded, and otherwise its hash.
+    /// Nodes that only send hashes have the body of each fetched.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("ws://127.0.0.1:8545").unwrap();
+    /// let mut pending = web3.subscribe_pending_transactions(true).await.unwrap();
+    ///
+    /// while let Some(Ok(TxOrHash::Transaction(transaction))) = pending.next().await {
+    ///     println!("{:?}", transaction);
+    /// }
+    /// ```
+    pub async fn subscribe_pending_transactions(&self, full: bool) -> Result<PendingTransactions> {
+        let web3 = self.clone();
+        let client = self.ws_client().await?;
+        let mut subscription: Subscription<TxOrHash> = client
+            .subscribe(
+                "eth_subscribe",
+                rpc_params!["newPendingTransactions", full],
+                "eth_unsubscribe",
+            )
+            .await
+            .map_err(|e| Web3Error::RpcRequestError(e.to_string()))?;
+        let (sender, receiver) = mpsc::channel(DEFAULT_CAPACITY);
+        let (unsubscribe, mut unsubscribed) = oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            loop {
+                let pending = tokio::select! {
+                    pending = subscription.next() => pending,
+                    _ = &mut unsubscribed => break,
+                };
+
+                let pending = match pending {
+                    // the node ignored the request for full bodies
+                    Some(Ok(TxOrHash::Hash(hash))) if full => {
+                        match web3.get_transaction_by_hash(hash).await {
+                            Ok(Some(transaction)) => {
+                                Ok(TxOrHash::Transaction(Box::new(transaction)))
+                            }
+                            // the transaction was dropped before it could be fetched
+                            Ok(None) => continue,
+                            Err(error) => Err(error),
+                        }
+                    }
+                    Some(Ok(pending)) => Ok(pending),
+                    Some(Err(error)) => Err(Web3Error::RpcResponseError(error.to_string())),
+                    // the node ended the subscription
+                    None => return,
+                };
+
+                if sender.send(pending).await.is_err() {
+                    break;
+                }
+            }
+
+            if let Err(error) = subscription.unsubscribe().await {
+                log::warn!("Could not unsubscribe from pending transactions: {}", error);
+            }
+
+            // the client is kept until the subscription has ended
+            drop(client);
+        });
+
+        Ok(PendingTransactions {
+            receiver,
+            _unsubscribe: unsubscribe,
+        })
+    }
+
+    /// Subscribe over WebSocket to the logs of any contract that name an
+    /// account in an indexed topic, such as token transfers to or from it.
+    ///
+    /// A filter's topic positions must all match, so each indexed position is
+    /// subscribed to separately. A log naming the account in several positions
+    /// (e.g. a transfer to itself) is yielded once.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("ws://127.0.0.1:8545").unwrap();
+    /// let mut activity = web3.subscribe_account_activity(account).await.unwrap();
+    ///
+    /// while let Some(Ok(log)) = activity.next().await {
+    ///     println!("{:?}", log);
+    /// }
+    /// ```
+    pub async fn subscribe_account_activity(&self, account: Account) -> Result<AccountActivity> {
+        let client = self.ws_client().await?;
+        let topic = H256::from(account);
+        let mut subscriptions = vec![];
+
+        for position in 1..=INDEXED_TOPICS {
+            let mut topics = vec![None; position];
+            topics.push(Some(topic.into()));
+            let filter = LogFilter {
+                topics: Some(topics),
+                ..Default::default()
+            };
+            let subscription: Subscription<Log> = client
+                .subscribe(
+                    "eth_subscribe",
+                    rpc_params!["logs", filter],
+                    "eth_unsubscribe",
+                )
+                .await
+                .map_err(|e| Web3Error::RpcRequestError(e.to_string()))?;
+
+            subscriptions.push((position, subscription));
+        }
+
+        let (sender, receiver) = mpsc::channel(DEFAULT_CAPACITY);
+        let (unsubscribe, mut unsubscribed) = oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            let mut logs = futures::stream::select_all(subscriptions.iter_mut().map(
+                |(position, subscription)| {
+                    let position = *position;
+                    subscription.map(move |log| (position, log))
+                },
+            ));
+
+            loop {
+                let log = tokio::select! {
+                    log = logs.next() => log,
+                    _ = &mut unsubscribed => break,
+                };
+
+                let log = match log {
+                    // the subscription to an earlier position has already yielded it
+                    Some((position, Ok(log)))
+                        if (1..position).any(|earlier| log.topics.get(earlier) == Some(&topic)) =>
+                    {
+                        continue
+                    }
+                    Some((_, Ok(log))) => Ok(log),
+                    Some((_, Err(error))) => Err(Web3Error::RpcResponseError(error.to_string())),
+                    // the node ended the subscriptions
+                    None => return,
+                };
+
+                if sender.send(log).await.is_err() {
+                    break;
+                }
+            }
+
+            drop(logs);
+
+            for (_, subscription) in subscriptions {
+                if let Err(error) = subscription.unsubscribe().await {
+                    log::warn!("Could not unsubscribe from account activity: {}", error);
+                }
+            }
+
+            // the client is kept until the subscriptions have ended
+            drop(client);
+        });
+
+        Ok(AccountActivity {
+            receiver,
+            _unsubscribe: unsubscribe,
+        })
+    }
+
+    async fn ws_client(&self) -> Result<WsClient> {
+        WsClientBuilder::default()
+            .build(self.endpoint())
+            .await
+            .map_err(|e| Web3Error::ClientError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::{mock_ws_node, web3};
+    use ethereum_types::U256;
+    use jsonrpsee::server::ServerHandle;
+    use jsonrpsee::RpcModule;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::Notify;
+    use types::bytes::Bytes;
+    use types::transaction::TransactionRequest;
+
+    fn block(number: u64) -> Block {
+        Block::new(
+            U64::from(number),
+            H256::zero(),
+            vec![],
+            &[],
+            H256::zero(),
+            U256::zero(),
+            U64::zero(),
+        )
+        .unwrap()
+    }
+
+    fn new_heads(capacity: usize, lag_policy: LagPolicy) -> (broadcast::Sender<Block>, NewHeads) {
+        let (sender, receiver) = broadcast::channel(capacity);
+        let task = tokio::spawn(async {});
+
+        (sender, NewHeads::new(receiver, lag_policy, task))
+    }
+
+    #[tokio::test]
+    async fn it_rejects_a_subscription_without_capacity() {
+        let config = SubscriptionConfig {
+            capacity: 0,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            web3().subscribe_new_heads(config).await,
+            Err(Web3Error::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_slow_consumer_is_told_it_lagged() {
+        let (sender, mut new_heads) = new_heads(2, LagPolicy::DropOldest);
+
+        // the consumer doesn't read while 5 blocks are produced
+        (1..=5).for_each(|number| {
+            sender.send(block(number)).unwrap();
+        });
+
+        // only the newest blocks were buffered
+        let new_head = new_heads.next().await.unwrap().unwrap();
+        assert!(matches!(new_head, NewHead::Lagged { missed: 3 }));
+
+        for number in 4..=5 {
+            match new_heads.next().await.unwrap().unwrap() {
+                NewHead::Block(block) => assert_eq!(block.number, U64::from(number)),
+                new_head => panic!("expected a block, got {:?}", new_head),
+            }
+        }
+
+        drop(sender);
+        assert!(new_heads.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn a_slow_consumer_errors_when_configured() {
+        let (sender, mut new_heads) = new_heads(1, LagPolicy::Error);
+        sender.send(block(1)).unwrap();
+        sender.send(block(2)).unwrap();
+
+        let response = new_heads.next().await.unwrap();
+        assert!(matches!(response, Err(Web3Error::SubscriptionLagged(1))));
+    }
+
+    #[tokio::test]
+    async fn it_subscribes_to_new_heads() {
+        let response = web3()
+            .subscribe_new_heads(SubscriptionConfig::default())
+            .await;
+        assert!(response.is_ok());
+    }
+
+    /// A node that notifies one new head to each subscriber, and signals
+    /// when a subscriber unsubscribes
+    async fn subscribing_node() -> (String, Arc<Notify>, ServerHandle) {
+        let unsubscribed = Arc::new(Notify::new());
+        let mut module = RpcModule::new(unsubscribed.clone());
+
+        module
+            .register_method("eth_getBlockByNumber", |params, _| {
+                let mut seq = params.sequence();
+                let number = seq.next::<U64>()?;
+
+                Ok(block(number.as_u64()))
+            })
+            .unwrap();
+        module
+            .register_subscription(
+                "eth_subscribe",
+                "eth_subscription",
+                "eth_unsubscribe",
+                |_, mut sink, unsubscribed| {
+                    sink.accept()?;
+
+                    tokio::spawn(async move {
+                        // send a head, then wait for the subscriber to unsubscribe
+                        let heads = futures::stream::iter([json!({ "number": "0x1" })])
+                            .chain(futures::stream::pending());
+                        sink.pipe_from_stream(heads).await;
+                        unsubscribed.notify_one();
+                    });
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        let (url, handle) = mock_ws_node(module).await;
+
+        (url, unsubscribed, handle)
+    }
+
+    #[tokio::test]
+    async fn it_subscribes_to_new_heads_over_websocket() {
+        let (url, unsubscribed, _handle) = subscribing_node().await;
+        let web3 = Web3::new(&url).unwrap();
+        let mut new_heads = web3
+            .subscribe_new_heads(SubscriptionConfig::default())
+            .await
+            .unwrap();
+
+        match new_heads.next().await.unwrap().unwrap() {
+            NewHead::Block(block) => assert_eq!(block.number, U64::from(1)),
+            new_head => panic!("expected a block, got {:?}", new_head),
+        }
+
+        // dropping the stream unsubscribes
+        drop(new_heads);
+        time::timeout(Duration::from_secs(5), unsubscribed.notified())
+            .await
+            .unwrap();
+    }
+
+    /// The mempool of a node that announces each transaction sent to it
+    struct Mempool {
+        /// Whether full bodies are sent to subscribers that ask for them
+        sends_full_bodies: bool,
+        announcements: broadcast::Sender<Transaction>,
+        transactions: Mutex<HashMap<H256, Transaction>>,
+    }
+
+    async fn pending_node(sends_full_bodies: bool) -> (Web3, ServerHandle) {
+        let mut module = RpcModule::new(Mempool {
+            sends_full_bodies,
+            announcements: broadcast::channel(DEFAULT_CAPACITY).0,
+            transactions: Mutex::new(HashMap::new()),
+        });
+
+        module
+            .register_method("eth_sendTransaction", |params, mempool| {
+                let transaction_request = params.one::<TransactionRequest>()?;
+                let mut transaction: Transaction = transaction_request.try_into().unwrap();
+                let tx_hash = transaction.hash().unwrap();
+
+                mempool
+                    .transactions
+                    .lock()
+                    .unwrap()
+                    .insert(tx_hash, transaction.clone());
+                mempool.announcements.send(transaction).unwrap();
+
+                Ok(tx_hash)
+            })
+            .unwrap();
+        module
+            .register_method("eth_getTransactionByHash", |params, mempool| {
+                let tx_hash = params.one::<H256>()?;
+
+                Ok(mempool.transactions.lock().unwrap().get(&tx_hash).cloned())
+            })
+            .unwrap();
+        module
+            .register_subscription(
+                "eth_subscribe",
+                "eth_subscription",
+                "eth_unsubscribe",
+                |params, mut sink, mempool| {
+                    let full = params
+                        .parse::<(String, bool)>()
+                        .is_ok_and(|(_, full)| full && mempool.sends_full_bodies);
+                    let mut announcements = mempool.announcements.subscribe();
+                    sink.accept()?;
+
+                    tokio::spawn(async move {
+                        while let Ok(transaction) = announcements.recv().await {
+                            let sent = match full {
+                                true => sink.send(&transaction),
+                                false => sink.send(&transaction.hash),
+                            };
+
+                            if !matches!(sent, Ok(true)) {
+                                break;
+                            }
+                        }
+                    });
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        let (url, handle) = mock_ws_node(module).await;
+
+        (Web3::new(&url).unwrap(), handle)
+    }
+
+    async fn send_transfer(web3: &Web3) -> H256 {
+        let transfer = TransactionRequest {
+            from: Some(Account::random()),
+            to: Some(Account::random()),
+            value: Some(U256::from(10)),
+            nonce: Some(U256::one()),
+            ..Default::default()
+        };
+
+        web3.send(transfer).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_subscribes_to_pending_transaction_hashes() {
+        let (web3, _handle) = pending_node(true).await;
+        let mut pending = web3.subscribe_pending_transactions(false).await.unwrap();
+        let tx_hash = send_transfer(&web3).await;
+
+        let pending = pending.next().await.unwrap().unwrap();
+        assert_eq!(pending, TxOrHash::Hash(tx_hash));
+    }
+
+    #[tokio::test]
+    async fn it_subscribes_to_full_pending_transactions() {
+        let (web3, _handle) = pending_node(true).await;
+        let mut pending = web3.subscribe_pending_transactions(true).await.unwrap();
+        let tx_hash = send_transfer(&web3).await;
+
+        match pending.next().await.unwrap().unwrap() {
+            TxOrHash::Transaction(transaction) => assert_eq!(transaction.hash, Some(tx_hash)),
+            pending => panic!("expected a full transaction, got {:?}", pending),
+        }
+    }
+
+    #[tokio::test]
+    async fn it_fetches_pending_transactions_from_nodes_that_only_send_hashes() {
+        let (web3, _handle) = pending_node(false).await;
+        let mut pending = web3.subscribe_pending_transactions(true).await.unwrap();
+        let tx_hash = send_transfer(&web3).await;
+
+        match pending.next().await.unwrap().unwrap() {
+            TxOrHash::Transaction(transaction) => assert_eq!(transaction.hash, Some(tx_hash)),
+            pending => panic!("expected a full transaction, got {:?}", pending),
+        }
+    }
+
+    /// A node that sends each logs subscriber the logs matching its filter
+    async fn logging_node(logs: Vec<Log>) -> (Web3, ServerHandle) {
+        let mut module = RpcModule::new(logs);
+
+        module
+            .register_subscription(
+                "eth_subscribe",
+                "eth_subscription",
+                "eth_unsubscribe",
+                |params, mut sink, logs| {
+                    let (_, filter) = params.parse::<(String, LogFilter)>()?;
+                    let matching = logs
+                        .iter()
+                        .filter(|log| filter.matches(log))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    sink.accept()?;
+
+                    tokio::spawn(async move {
+                        // send the logs, then wait for the subscriber to unsubscribe
+                        let logs =
+                            futures::stream::iter(matching).chain(futures::stream::pending());
+                        sink.pipe_from_stream(logs).await;
+                    });
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        let (url, handle) = mock_ws_node(module).await;
+
+        (Web3::new(&url).unwrap(), handle)
+    }
+
+    #[tokio::test]
+    async fn it_subscribes_to_the_logs_naming_an_account() {
+        let watched = Account::random();
+        let transfer = |from: Account, to: Account| Log {
+            address: Account::random(),
+            block_hash: None,
+            block_number: None,
+            data: Bytes::new(),
+            log_index: None,
+            log_type: None,
+            removed: None,
+            topics: vec![H256::from_low_u64_be(1), from.into(), to.into()],
+            transaction_hash: None,
+            transaction_index: None,
+            transaction_log_index: None,
+        };
+        let unrelated = transfer(Account::random(), Account::random());
+        let inbound = transfer(Account::random(), watched);
+        let to_itself = transfer(watched, watched);
+        let (web3, _handle) =
+            logging_node(vec![unrelated, inbound.clone(), to_itself.clone()]).await;
+        let mut activity = web3.subscribe_account_activity(watched).await.unwrap();
+
+        let mut logs = vec![
+            activity.next().await.unwrap().unwrap(),
+            activity.next().await.unwrap().unwrap(),
+        ];
+        logs.sort_by_key(|log| log.topics[1] == H256::from(watched));
+        assert_eq!(logs, vec![inbound, to_itself]);
+
+        // the unrelated transfer never arrives, and the one to itself only once
+        let next = time::timeout(Duration::from_millis(100), activity.next()).await;
+        assert!(next.is_err());
+    }
+}
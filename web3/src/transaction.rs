@@ -6,15 +6,78 @@
 
 ////////////////////////////////////////////////////////////////////////////////
 
-use ethereum_types::H256;
+use std::time::Duration;
+
+use ethereum_types::{H256, U256};
 use jsonrpsee::rpc_params;
+use serde::Deserialize;
 use serde_json::to_value;
+use types::block::{BlockId, BlockTag};
 use types::bytes::Bytes;
-use types::transaction::{TransactionReceipt, TransactionRequest};
+use types::error::TypeError;
+use types::helpers::strip_0x;
+use types::transaction::{
+    AccessList, ExecutionResult, SignedTransaction, Transaction, TransactionReceipt,
+    TransactionRequest, TransactionType,
+};
+use utils::SecretKey;
 
-use crate::error::Result;
+use crate::account::AddressOrAlias;
+use crate::error::{revert_reason, Result, Web3Error};
 use crate::Web3;
 
+/// How often to poll the node while waiting for a receipt
+pub const RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The smallest fee multiplier nodes accept for a replacement (geth's default 10% price bump)
+pub const MIN_FEE_BUMP: f64 = 1.1;
+
+/// The error nodes return when a transaction can't run within any gas limit
+const GAS_ALLOWANCE_EXCEEDED: &str = "gas required exceeds allowance";
+
+/// Nodes report a transaction that can't run within any gas limit as a
+/// generic error
+fn estimation_error(error: Web3Error) -> Web3Error {
+    match revert_error(error) {
+        Web3Error::Rpc { message, .. } if message.contains(GAS_ALLOWANCE_EXCEEDED) => {
+            Web3Error::GasEstimationFailed { reason: message }
+        }
+        error => error,
+    }
+}
+
+/// Some nodes report a revert under a generic error code, with its reason
+/// ABI-encoded in the error's data
+fn revert_error(error: Web3Error) -> Web3Error {
+    match error {
+        Web3Error::Rpc {
+            data: Some(ref data),
+            ..
+        } => match revert_reason(data) {
+            Some(reason) => Web3Error::WouldRevert { reason },
+            None => error,
+        },
+        error => error,
+    }
+}
+
+/// A gas estimate and the block it was estimated against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasEstimate {
+    pub gas: U256,
+    pub block: BlockTag,
+}
+
+/// The response of `eth_createAccessList`
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct AccessListResponse {
+    access_list: AccessList,
+    gas_used: U256,
+    #[serde(default)]
+    error: Option<String>,
+}
+
 impl Web3 {
     /// Create a new message call transaction or deploy a contract.
     ///
@@ -42,48 +105,322 @@ impl Web3 {
     /// let tx_hash = web3.send(transaction_request).await;
     /// ```
     pub async fn send(&self, transaction_request: TransactionRequest) -> Result<H256> {
-        let transaction_request = to_value(&transaction_request)?;
+        if self.config.preflight {
+            self.preflight(&transaction_request).await?;
+        }
+
+        // serialized straight into the params to keep the fields in spec order
         let params = rpc_params![transaction_request];
         let response = self.send_rpc("eth_sendTransaction", params).await?;
-        let tx_hash: H256 = serde_json::from_value(response)?;
+        let tx_hash: H256 = self.deserialize(response)?;
 
         Ok(tx_hash)
     }
 
-    /// Send a raw transaction
+    /// Send a transaction signed locally (e.g. with `sign_transaction`), so the
+    /// node never sees the key. The transaction is sent as a hex encoded
+    /// EIP-2718 envelope.
+    ///
+    /// Transactions the node refuses (e.g. an invalid signature) fail with
+    /// `RawTransactionRejected`, keeping the node's error code and data, while
+    /// nonce and funding problems keep their own errors so they can be
+    /// handled. Errors reaching the node are left as they are.
     ///
     /// See https://eth.wiki/json-rpc/API#eth_sendrawtransaction
     ///
     /// # Examples
     ///
     /// ```ignore
-    /// use types::transaction::TransactionRequest;
+    /// use types::transaction::Transaction;
     ///
     /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
-    /// let from = web3.get_all_accounts().await.unwrap()[0];
-    /// let to = web3.get_all_accounts().await.unwrap()[1];
-    /// let gas = U256::from(1_000_000);
-    /// let gas_price = U256::from(1);
-    /// let data = include_bytes!("./../../contracts/artifacts/contracts/ERC20.sol/RustCoinToken.json").to_vec();
-    /// let transaction_request = TransactionRequest {
-    ///     from: None,
-    ///     to: Some(to),
-    ///     value: None,
-    ///     gas,
-    ///     gas_price,
-    ///     data: Some(data.into()),
-    ///     };
-    /// let tx_hash = web3.send(transaction_request).await;
+    /// let transaction = Transaction::from_request(transaction_request, nonce).unwrap();
+    /// let signed_transaction = web3.sign_transaction(transaction, secret_key).await.unwrap();
+    /// let tx_hash = web3.send_raw(signed_transaction).await;
     /// ```
-    pub async fn send_raw(&self, transaction_request: Bytes) -> Result<H256> {
-        let transaction_request = to_value(&transaction_request)?;
-        let params = rpc_params![transaction_request];
-        let response = self.send_rpc("eth_sendRawTransaction", params).await?;
-        let tx_hash: H256 = serde_json::from_value(response)?;
+    pub async fn send_raw(&self, signed_transaction: SignedTransaction) -> Result<H256> {
+        if self.config.preflight {
+            let transaction: Transaction = signed_transaction
+                .clone()
+                .try_into()
+                .map_err(|e: TypeError| Web3Error::RpcRequestError(e.to_string()))?;
+
+            self.preflight(&transaction.into()).await?;
+        }
+
+        let envelope = signed_transaction
+            .to_envelope()
+            .map_err(|e| Web3Error::TransactionSigningError(e.to_string()))?;
+        let params = rpc_params![format!("0x{}", hex::encode(envelope))];
+        let response = self
+            .send_rpc("eth_sendRawTransaction", params)
+            .await
+            .map_err(|error| match error {
+                Web3Error::Rpc {
+                    code,
+                    message,
+                    data,
+                } => Web3Error::RawTransactionRejected {
+                    code,
+                    message,
+                    data,
+                },
+                error => error,
+            })?;
+        let tx_hash: H256 = self.deserialize(response)?;
 
         Ok(tx_hash)
     }
 
+    /// Speed up a pending transaction by resending it with the same nonce and
+    /// its fees multiplied by `multiplier`, which must be at least `MIN_FEE_BUMP`.
+    /// Dynamic fee transactions have both their max fee and priority fee bumped.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let signed_transaction = web3.sign_transaction(transaction, secret_key).await.unwrap();
+    /// web3.send_raw(signed_transaction.clone()).await.unwrap();
+    /// let tx_hash = web3.bump_fee(&signed_transaction, 1.25, &secret_key).await;
+    /// ```
+    pub async fn bump_fee(
+        &self,
+        original: &SignedTransaction,
+        multiplier: f64,
+        key: &SecretKey,
+    ) -> Result<H256> {
+        // fees are multiplied in thousandths, rounding up so a bump is never short
+        let per_mille = (multiplier * 1000.0).round();
+
+        if per_mille.is_nan() || per_mille < (MIN_FEE_BUMP * 1000.0).round() {
+            return Err(Web3Error::BumpTooSmall(multiplier));
+        }
+
+        let bump = |fee: U256| {
+            fee.checked_mul(U256::from(per_mille as u64))
+                .and_then(|fee| fee.checked_add(U256::from(999)))
+                .map(|fee| fee / 1000)
+                .ok_or(Web3Error::BumpOverflow(multiplier))
+        };
+        let mut transaction: Transaction = original
+            .clone()
+            .try_into()
+            .map_err(|e: TypeError| Web3Error::TransactionSigningError(e.to_string()))?;
+
+        match transaction.transaction_type() {
+            TransactionType::DynamicFee | TransactionType::SetCode => {
+                transaction.max_fee_per_gas = transaction.max_fee_per_gas.map(bump).transpose()?;
+                transaction.max_priority_fee_per_gas =
+                    transaction.max_priority_fee_per_gas.map(bump).transpose()?;
+            }
+            TransactionType::Legacy | TransactionType::AccessList => {
+                transaction.gas_price = bump(transaction.gas_price)?;
+            }
+        }
+
+        let signed_transaction = self.sign_transaction(transaction, *key).await?;
+
+        self.send_raw(signed_transaction).await
+    }
+
+    /// Execute a message call without creating a transaction on the chain,
+    /// returning the bytes decoded from the hex output (`0x` for none).
+    /// The request's `from` is the caller, for reads that depend on it.
+    /// The block is a number, tag or hash, and a block of `None` is the
+    /// latest block.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_call
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let output = web3.call(&transaction_request, BlockTag::Latest).await;
+    /// assert!(output.is_ok());
+    /// ```
+    pub async fn call(
+        &self,
+        transaction_request: &TransactionRequest,
+        block: impl Into<BlockId>,
+    ) -> Result<Bytes> {
+        let params = rpc_params![transaction_request, block.into()];
+        let response = self
+            .send_rpc("eth_call", params)
+            .await
+            .map_err(revert_error)?;
+        let output: String = self.deserialize(response)?;
+        let output = hex::decode(strip_0x(&output)).map_err(|e| {
+            Web3Error::RpcResponseError(format!("eth_call output {}: {}", output, e))
+        })?;
+
+        Ok(output.into())
+    }
+
+    /// Execute a message call as `from`, for reads that depend on the caller
+    /// (e.g. an allowance or a role check).
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let output = web3.call_as(owner, token, data, BlockTag::Latest).await;
+    /// assert!(output.is_ok());
+    /// ```
+    pub async fn call_as(
+        &self,
+        from: impl Into<AddressOrAlias>,
+        to: impl Into<AddressOrAlias>,
+        data: Bytes,
+        block: impl Into<BlockId>,
+    ) -> Result<Bytes> {
+        let transaction_request = TransactionRequest {
+            from: Some(self.resolve(from)?),
+            to: Some(self.resolve(to)?),
+            data: Some(data),
+            ..Default::default()
+        };
+
+        self.call(&transaction_request, block).await
+    }
+
+    /// Estimate the gas a transaction needs against the pending block, falling
+    /// back to the latest block for nodes that don't keep pending state.
+    /// The estimate records which block was used.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_estimateGas
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let estimate = web3.estimate_gas(&transaction_request).await.unwrap();
+    /// println!("{} gas at the {} block", estimate.gas, estimate.block);
+    /// ```
+    pub async fn estimate_gas(
+        &self,
+        transaction_request: &TransactionRequest,
+    ) -> Result<GasEstimate> {
+        match self
+            .estimate_gas_at(transaction_request, BlockTag::Pending)
+            .await
+        {
+            Err(error @ (Web3Error::Rpc { .. } | Web3Error::RpcRequestError(_))) => {
+                log::warn!("Estimating gas against the latest block: {}", error);
+                self.estimate_gas_at(transaction_request, BlockTag::Latest)
+                    .await
+            }
+            estimate => estimate,
+        }
+    }
+
+    async fn estimate_gas_at(
+        &self,
+        transaction_request: &TransactionRequest,
+        block: BlockTag,
+    ) -> Result<GasEstimate> {
+        let params = rpc_params![transaction_request, block];
+        let response = self
+            .send_rpc("eth_estimateGas", params)
+            .await
+            .map_err(estimation_error)?;
+        let gas = self.deserialize(response)?;
+
+        Ok(GasEstimate { gas, block })
+    }
+
+    /// Estimate the gas of several transactions against `block` in a single
+    /// batch request, returning the estimate of each in order.
+    /// A transaction that can't be estimated only fails its own estimate.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let estimates = web3.estimate_gas_batch(&requests, BlockTag::Latest).await.unwrap();
+    /// let total = estimates.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+    /// ```
+    pub async fn estimate_gas_batch(
+        &self,
+        transaction_requests: &[TransactionRequest],
+        block: BlockTag,
+    ) -> Result<Vec<Result<U256>>> {
+        if transaction_requests.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut batch = self.typed_batch();
+        let calls = transaction_requests
+            .iter()
+            .map(|transaction_request| {
+                batch.add::<U256, _>("eth_estimateGas", rpc_params![transaction_request, block])
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut results = batch.send().await?;
+
+        Ok(calls
+            .into_iter()
+            .map(|call| results.take(call).map_err(estimation_error))
+            .collect())
+    }
+
+    /// Retrieve the current gas price in wei.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_gasPrice
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let gas_price = web3.gas_price().await.unwrap();
+    /// ```
+    pub async fn gas_price(&self) -> Result<U256> {
+        let response = self.send_rpc("eth_gasPrice", rpc_params![]).await?;
+        let gas_price = self.deserialize(response)?;
+
+        Ok(gas_price)
+    }
+
+    /// Build a transfer that's ready to send, with the gas price from the node
+    /// and the gas from `estimate_gas`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let transaction_request = web3
+    ///     .suggest_transaction_request(from, to, U256::from(10))
+    ///     .await
+    ///     .unwrap();
+    /// let tx_hash = web3.send(transaction_request).await.unwrap();
+    /// ```
+    pub async fn suggest_transaction_request(
+        &self,
+        from: impl Into<AddressOrAlias>,
+        to: impl Into<AddressOrAlias>,
+        value: U256,
+    ) -> Result<TransactionRequest> {
+        let mut transaction_request = TransactionRequest {
+            from: Some(self.resolve(from)?),
+            to: Some(self.resolve(to)?),
+            value: Some(value),
+            ..Default::default()
+        };
+
+        transaction_request.gas_price = self.gas_price().await?;
+        transaction_request.gas = self.estimate_gas(&transaction_request).await?.gas;
+
+        Ok(transaction_request)
+    }
+
+    /// Check that a transaction won't revert before it's sent, returning
+    /// `Web3Error::WouldRevert` if it will.
+    async fn preflight(&self, transaction_request: &TransactionRequest) -> Result<()> {
+        self.call(transaction_request, BlockTag::Pending).await?;
+
+        Ok(())
+    }
+
     /// Retrieve a transaction receipt by transaction hash.
     ///
     /// See https://eth.wiki/json-rpc/API#eth_getTransactionReceipt
@@ -114,23 +451,212 @@ impl Web3 {
         let tx_hash = to_value(tx_hash)?;
         let params = rpc_params![tx_hash];
         let response = self.send_rpc("eth_getTransactionReceipt", params).await?;
-        let receipt = serde_json::from_value(response)?;
+        let receipt = self.deserialize(response)?;
 
         Ok(receipt)
     }
+
+    /// Retrieve a transaction by its hash, or `None` if the node doesn't know it.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_getTransactionByHash
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let tx_hash = web3.send(transaction_request).await.unwrap();
+    /// let transaction = web3.get_transaction_by_hash(tx_hash).await.unwrap();
+    /// ```
+    pub async fn get_transaction_by_hash(&self, tx_hash: H256) -> Result<Option<Transaction>> {
+        let response = self
+            .send_rpc("eth_getTransactionByHash", rpc_params![tx_hash])
+            .await?;
+        let transaction: Option<Transaction> = self.deserialize(response)?;
+
+        Ok(transaction)
+    }
+
+    /// Retrieve a transaction receipt by transaction hash, or `None` if the
+    /// transaction hasn't been mined yet.
+    ///
+    /// See https://eth.wiki/json-rpc/API#eth_getTransactionReceipt
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let tx_hash = web3.send(transaction_request).await.unwrap();
+    /// let receipt = web3.get_transaction_receipt(tx_hash).await.unwrap();
+    /// ```
+    pub async fn get_transaction_receipt(
+        &self,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReceipt>> {
+        let response = self
+            .send_rpc("eth_getTransactionReceipt", rpc_params![tx_hash])
+            .await?;
+        let receipt: Option<TransactionReceipt> = self.deserialize(response)?;
+
+        Ok(receipt)
+    }
+
+    /// Wait for a transaction to be mined and then confirmed by `confirmations`
+    /// more blocks, falling back to `Web3Config.default_confirmations`.
+    ///
+    /// Use `wait_for_receipt_with_timeout` to give up on transactions that are never mined.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let tx_hash = web3.send(transaction_request).await.unwrap();
+    /// let receipt = web3.wait_for_receipt(tx_hash, Some(12)).await;
+    /// ```
+    pub async fn wait_for_receipt(
+        &self,
+        tx_hash: H256,
+        confirmations: Option<u64>,
+    ) -> Result<TransactionReceipt> {
+        let confirmations = confirmations.unwrap_or(self.config.default_confirmations);
+        let mut interval = tokio::time::interval(RECEIPT_POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let receipt = self.get_transaction_receipt(tx_hash).await?;
+
+            // pending transactions have no receipt, or a receipt without a block
+            let Some(receipt) = receipt else {
+                continue;
+            };
+            let Some(block_number) = receipt.block_number.clone() else {
+                continue;
+            };
+
+            let latest = self.get_block_number().await?;
+
+            if latest.0 >= block_number.0 + confirmations {
+                return Ok(receipt);
+            }
+        }
+    }
+
+    /// Wait for a receipt with the configured `default_confirmations`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let config = Web3Config::builder().default_confirmations(12).build();
+    /// let web3 = web3::Web3::with_config("http://127.0.0.1:8545", config).unwrap();
+    /// let receipt = web3.wait_for_receipt_default(tx_hash).await;
+    /// ```
+    pub async fn wait_for_receipt_default(&self, tx_hash: H256) -> Result<TransactionReceipt> {
+        self.wait_for_receipt(tx_hash, None).await
+    }
+
+    /// Wait for a receipt with the configured `default_confirmations`, giving up
+    /// with `Web3Error::ReceiptTimeout` once `timeout` has passed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let tx_hash = web3.send(transaction_request).await.unwrap();
+    /// let receipt = web3
+    ///     .wait_for_receipt_with_timeout(tx_hash, Duration::from_secs(30))
+    ///     .await;
+    /// ```
+    pub async fn wait_for_receipt_with_timeout(
+        &self,
+        tx_hash: H256,
+        timeout: Duration,
+    ) -> Result<TransactionReceipt> {
+        tokio::time::timeout(timeout, self.wait_for_receipt(tx_hash, None))
+            .await
+            .map_err(|_| Web3Error::ReceiptTimeout(tx_hash))?
+    }
+
+    /// Simulate a bundle of transactions, in order, against the state at a block.
+    ///
+    /// Each transaction sees the effects of the ones before it, but nothing is
+    /// committed. A result is returned for every transaction.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let results = web3.simulate_bundle(&[fund, transfer], BlockTag::Latest).await.unwrap();
+    /// assert!(results.iter().all(ExecutionResult::is_success));
+    /// ```
+    pub async fn simulate_bundle(
+        &self,
+        transaction_requests: &[TransactionRequest],
+        block: BlockTag,
+    ) -> Result<Vec<ExecutionResult>> {
+        let params = rpc_params![transaction_requests, block];
+        let response = self.send_rpc("eth_simulateBundle", params).await?;
+        let execution_results = self.deserialize(response)?;
+
+        Ok(execution_results)
+    }
+
+    /// Generate an access list for a transaction, along with the gas it would use.
+    ///
+    /// See https://github.com/ethereum/execution-apis (eth_createAccessList)
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let web3 = web3::Web3::new("http://127.0.0.1:8545").unwrap();
+    /// let (access_list, gas_used) = web3
+    ///     .create_access_list(transaction_request, BlockTag::Latest)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn create_access_list(
+        &self,
+        transaction_request: TransactionRequest,
+        block: BlockTag,
+    ) -> Result<(AccessList, U256)> {
+        let params = rpc_params![transaction_request, block];
+        let response = self.send_rpc("eth_createAccessList", params).await?;
+        let AccessListResponse {
+            access_list,
+            gas_used,
+            error,
+        } = self.deserialize(response)?;
+
+        // the node reports execution failures alongside the partial access list
+        if let Some(error) = error {
+            return Err(Web3Error::RpcResponseError(error));
+        }
+
+        Ok((access_list, gas_used))
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::config::Web3Config;
     use crate::helpers::tests::{
-        deploy_contract, increment_account_1_nonce, web3, ACCOUNT_1, ACCOUNT_1_NONCE, ACCOUNT_2,
+        deploy_contract, get_contract, increment_account_1_nonce, mock_node, web3, ACCOUNT_1,
+        ACCOUNT_2,
     };
-    use ethereum_types::U256;
-    use std::time::Duration;
+    use ethereum_types::U64;
+    use jsonrpsee::core::Error as JsonRpseeError;
+    use jsonrpsee::server::ServerHandle;
+    use jsonrpsee::types::error::{CallError, ErrorObject};
+    use jsonrpsee::RpcModule;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
     use tokio::time::sleep;
-    use types::{account::Account, transaction::Transaction};
-    use utils::crypto::keypair;
+    use types::account::Account;
+    use types::block::BlockNumber;
+    use types::error::INVALID_TRANSACTION_CODE;
+    use types::transaction::Transaction;
+    use utils::crypto::{dev_account, keypair};
 
     async fn transaction() -> Transaction {
         let nonce = increment_account_1_nonce().await;
@@ -178,13 +704,65 @@ pub mod tests {
         assert!(response.is_ok());
     }
 
+    #[tokio::test]
+    async fn it_bumps_the_fee_of_a_transaction() {
+        let sent = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut module = RpcModule::new(sent.clone());
+        module
+            .register_method("eth_chainId", |_, _| Ok(U64::from(1337)))
+            .unwrap();
+        module
+            .register_method("eth_sendRawTransaction", |params, sent| {
+                let signed_transaction =
+                    Transaction::from_raw_hex(&params.one::<String>()?).unwrap();
+                let transaction: Transaction = signed_transaction.try_into().unwrap();
+                sent.lock().unwrap().push(transaction.clone());
+
+                Ok(transaction.transaction_hash().unwrap())
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = Web3::new(&url).unwrap();
+        let (key, account) = dev_account(0);
+        let mut transaction = Transaction::new(
+            account,
+            Some(*ACCOUNT_2),
+            U256::from(10),
+            Some(U256::from(7)),
+            None,
+        )
+        .unwrap();
+        transaction.gas_price = U256::from(100);
+        transaction.hash().unwrap();
+        let original = web3.sign_transaction(transaction, key).await.unwrap();
+
+        let response = web3.bump_fee(&original, 1.05, &key).await;
+        assert!(matches!(response, Err(Web3Error::BumpTooSmall(_))));
+        assert!(sent.lock().unwrap().is_empty());
+
+        web3.bump_fee(&original, MIN_FEE_BUMP, &key).await.unwrap();
+        let bumped = sent.lock().unwrap().pop().unwrap();
+        assert_eq!(bumped.nonce, Some(U256::from(7)));
+        assert_eq!(bumped.gas_price, U256::from(110));
+
+        let mut transaction: Transaction = original.try_into().unwrap();
+        transaction.gas_price = U256::MAX;
+        transaction.hash().unwrap();
+        let expensive = web3.sign_transaction(transaction, key).await.unwrap();
+        let response = web3.bump_fee(&expensive, MIN_FEE_BUMP, &key).await;
+        assert!(matches!(response, Err(Web3Error::BumpOverflow(_))));
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn it_sends_a_raw_transfer_transaction() {
         let (secret_key, _) = keypair();
         let transaction = transaction().await;
-        let signed_transaction = web3().sign_transaction(transaction, secret_key).unwrap();
-        let encoded = bincode::serialize(&signed_transaction).unwrap();
-        let response = web3().send_raw(encoded.into()).await;
+        let signed_transaction = web3()
+            .sign_transaction(transaction, secret_key)
+            .await
+            .unwrap();
+        let response = web3().send_raw(signed_transaction).await;
         assert!(response.is_ok());
     }
 
@@ -204,9 +782,523 @@ pub mod tests {
         ))
         .unwrap();
         let transaction = function_call_transaction(contract_address, function_call.into()).await;
-        let signed_transaction = web3().sign_transaction(transaction, secret_key).unwrap();
-        let encoded = bincode::serialize(&signed_transaction).unwrap();
-        let response = web3().send_raw(encoded.into()).await;
+        let signed_transaction = web3()
+            .sign_transaction(transaction, secret_key)
+            .await
+            .unwrap();
+        let response = web3().send_raw(signed_transaction).await;
         assert!(response.is_ok());
     }
+
+    /// A node that only accepts raw transactions sent by `signer`
+    async fn raw_transaction_node(signer: Account) -> (Web3, ServerHandle) {
+        let mut module = RpcModule::new(signer);
+
+        module
+            .register_method("eth_chainId", |_, _| Ok(U64::from(1337)))
+            .unwrap();
+        module
+            .register_method("eth_sendRawTransaction", |params, signer| {
+                let raw = params.one::<String>()?;
+                let transaction: Transaction =
+                    Transaction::from_raw_hex(&raw).unwrap().try_into().unwrap();
+
+                if transaction.from != *signer {
+                    let error =
+                        ErrorObject::owned(INVALID_TRANSACTION_CODE, "invalid sender", None::<()>);
+                    return Err(CallError::Custom(error).into());
+                }
+
+                Ok(transaction.transaction_hash().unwrap())
+            })
+            .unwrap();
+
+        let (url, handle) = mock_node(module).await;
+
+        (Web3::new(&url).unwrap(), handle)
+    }
+
+    #[tokio::test]
+    async fn it_sends_a_locally_signed_transaction() {
+        let (key, signer) = dev_account(0);
+        let (web3, _handle) = raw_transaction_node(signer).await;
+        let transaction = Transaction::new(
+            signer,
+            Some(Account::random()),
+            U256::from(10),
+            Some(U256::from(1)),
+            None,
+        )
+        .unwrap();
+
+        let signed_transaction = web3.sign_transaction(transaction, key).await.unwrap();
+        assert!(web3.send_raw(signed_transaction).await.is_ok());
+
+        let (other_key, _) = dev_account(1);
+        let transaction = Transaction::new(
+            signer,
+            Some(Account::random()),
+            U256::from(10),
+            Some(U256::from(1)),
+            None,
+        )
+        .unwrap();
+        let signed_transaction = web3.sign_transaction(transaction, other_key).await.unwrap();
+        assert!(matches!(
+            web3.send_raw(signed_transaction).await,
+            Err(Web3Error::RawTransactionRejected { code, message, .. })
+                if code == i64::from(INVALID_TRANSACTION_CODE) && message == "invalid sender"
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_keeps_transport_errors_sending_a_raw_transaction() {
+        let (key, signer) = dev_account(0);
+        // a configured chain id so signing doesn't reach for the node
+        let config = Web3Config::builder().chain_id(1337).build();
+        let web3 = Web3::with_config("http://127.0.0.1:1", config).unwrap();
+        let transaction = Transaction::new(
+            signer,
+            Some(Account::random()),
+            U256::from(10),
+            Some(U256::from(1)),
+            None,
+        )
+        .unwrap();
+
+        let signed_transaction = web3.sign_transaction(transaction, key).await.unwrap();
+        assert!(matches!(
+            web3.send_raw(signed_transaction).await,
+            Err(Web3Error::Transport { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_calls_as_an_account() {
+        // a view that reads its caller, answering 1 for the admin and 0 for anyone else
+        let admin = Account::random();
+        let mut module = RpcModule::new(admin);
+        module
+            .register_method("eth_call", |params, admin| {
+                let (transaction_request, _): (TransactionRequest, BlockTag) = params.parse()?;
+                let is_admin = transaction_request.from == Some(*admin)
+                    && transaction_request.data == Some(Bytes::from(vec![0x01]));
+
+                Ok(format!("0x0{}", u8::from(is_admin)))
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = Web3::new(&url).unwrap();
+        let call_as = |from| web3.call_as(from, *ACCOUNT_2, vec![0x01].into(), BlockTag::Latest);
+
+        assert_eq!(call_as(admin).await.unwrap(), Bytes::from(vec![1]));
+        assert_eq!(
+            call_as(Account::random()).await.unwrap(),
+            Bytes::from(vec![0])
+        );
+    }
+
+    #[tokio::test]
+    async fn it_surfaces_the_revert_reason_of_a_call() {
+        // reverts every call under the generic server error code
+        let mut module = RpcModule::new(());
+        module
+            .register_method(
+                "eth_call",
+                |_, _| -> std::result::Result<String, JsonRpseeError> {
+                    let data = format!(
+                        "0x08c379a0{}",
+                        hex::encode(ethabi::encode(&[ethabi::Token::String(
+                            "ERC20: transfer amount exceeds balance".into()
+                        )]))
+                    );
+                    let error = ErrorObject::owned(-32000, "execution reverted", Some(data));
+                    Err(CallError::Custom(error).into())
+                },
+            )
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = Web3::new(&url).unwrap();
+        let transfer = TransactionRequest {
+            to: Some(*ACCOUNT_2),
+            data: Some(vec![0xa9, 0x05, 0x9c, 0xbb].into()),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            web3.call(&transfer, BlockTag::Latest).await,
+            Err(Web3Error::WouldRevert { reason }) if reason == "ERC20: transfer amount exceeds balance"
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_decodes_call_output() {
+        // echoes the call's data as hex, answering junk for a lone 0xff
+        let mut module = RpcModule::new(());
+        module
+            .register_method("eth_call", |params, _| {
+                let (transaction_request, _): (TransactionRequest, BlockTag) = params.parse()?;
+                let data = transaction_request.data.unwrap_or_default();
+
+                match data.as_ref() {
+                    [0xff] => Ok("0xnothex".to_string()),
+                    data => Ok(format!("0x{}", hex::encode(data))),
+                }
+            })
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = &Web3::new(&url).unwrap();
+        let call = |data: Vec<u8>| {
+            let transaction_request = TransactionRequest {
+                to: Some(*ACCOUNT_2),
+                data: Some(data.into()),
+                ..Default::default()
+            };
+
+            async move { web3.call(&transaction_request, None::<BlockNumber>).await }
+        };
+
+        assert_eq!(call(vec![]).await.unwrap(), Bytes::new());
+        assert_eq!(
+            call(vec![0x01, 0xab]).await.unwrap(),
+            Bytes::from(vec![0x01, 0xab])
+        );
+        assert!(matches!(
+            call(vec![0xff]).await,
+            Err(Web3Error::RpcResponseError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_simulates_a_bundle() {
+        let web3 = web3();
+        let (funded, recipient) = (Account::random(), Account::random());
+        let fund = TransactionRequest {
+            from: Some(*ACCOUNT_1),
+            to: Some(funded),
+            value: Some(U256::from(100)),
+            ..Default::default()
+        };
+        let transfer = TransactionRequest {
+            from: Some(funded),
+            to: Some(recipient),
+            value: Some(U256::from(50)),
+            ..Default::default()
+        };
+
+        let results = web3
+            .simulate_bundle(std::slice::from_ref(&transfer), BlockTag::Latest)
+            .await
+            .unwrap();
+        assert!(!results[0].is_success());
+
+        let results = web3
+            .simulate_bundle(&[fund, transfer], BlockTag::Latest)
+            .await
+            .unwrap();
+        assert!(results.iter().all(ExecutionResult::is_success));
+    }
+
+    #[tokio::test]
+    async fn it_catches_a_revert_in_preflight() {
+        let config = Web3Config::builder().preflight(true).build();
+        let web3 = Web3::with_config("http://127.0.0.1:8545", config).unwrap();
+
+        // an account without funds can't make a transfer
+        let transfer = TransactionRequest {
+            from: Some(Account::random()),
+            to: Some(*ACCOUNT_2),
+            value: Some(U256::from(10)),
+            ..Default::default()
+        };
+        let response = web3.send(transfer).await;
+        assert!(matches!(response, Err(Web3Error::WouldRevert { .. })));
+
+        let transaction_request: TransactionRequest = transaction().await.into();
+        let response = web3.send(transaction_request).await;
+        assert!(response.is_ok());
+    }
+
+    /// A node that mines a transaction in block 1 and then advances a block
+    /// every time it's asked for the block number, counting the requests
+    async fn mining_node(config: Web3Config) -> (Web3, Arc<AtomicU64>, ServerHandle) {
+        let block_number = Arc::new(AtomicU64::new(0));
+        let mut module = RpcModule::new(block_number.clone());
+
+        module
+            .register_method("eth_blockNumber", |_, block_number| {
+                let block_number = block_number.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(U64::from(block_number))
+            })
+            .unwrap();
+        module
+            .register_method("eth_getTransactionReceipt", |params, _| {
+                Ok(json!({
+                    "blockHash": H256::from_low_u64_be(1),
+                    "blockNumber": "0x1",
+                    "contractAddress": null,
+                    "transactionHash": params.one::<H256>()?,
+                }))
+            })
+            .unwrap();
+
+        let (url, handle) = mock_node(module).await;
+
+        (
+            Web3::with_config(&url, config).unwrap(),
+            block_number,
+            handle,
+        )
+    }
+
+    /// A node that hasn't mined anything until it's been asked for a receipt `pending` times
+    async fn pending_node(pending: u64) -> (Web3, ServerHandle) {
+        let mut module = RpcModule::new(AtomicU64::new(0));
+
+        module
+            .register_method("eth_blockNumber", |_, _| Ok(U64::from(1)))
+            .unwrap();
+        module
+            .register_method("eth_getTransactionReceipt", move |params, requests| {
+                if requests.fetch_add(1, Ordering::SeqCst) < pending {
+                    return Ok(Value::Null);
+                }
+
+                Ok(json!({
+                    "blockHash": H256::from_low_u64_be(1),
+                    "blockNumber": "0x1",
+                    "contractAddress": null,
+                    "transactionHash": params.one::<H256>()?,
+                }))
+            })
+            .unwrap();
+
+        let (url, handle) = mock_node(module).await;
+
+        (Web3::new(&url).unwrap(), handle)
+    }
+
+    #[tokio::test]
+    async fn it_gets_no_receipt_for_a_pending_transaction() {
+        let (web3, _handle) = pending_node(1).await;
+        let tx_hash = H256::from_low_u64_be(10);
+
+        assert_eq!(web3.get_transaction_receipt(tx_hash).await.unwrap(), None);
+
+        let receipt = web3.get_transaction_receipt(tx_hash).await.unwrap();
+        assert_eq!(receipt.unwrap().transaction_hash, tx_hash);
+    }
+
+    #[tokio::test]
+    async fn it_gives_up_waiting_for_a_receipt_after_the_timeout() {
+        let (web3, _handle) = pending_node(u64::MAX).await;
+        let tx_hash = H256::from_low_u64_be(10);
+
+        let response = web3
+            .wait_for_receipt_with_timeout(tx_hash, Duration::from_millis(100))
+            .await;
+        assert!(matches!(response, Err(Web3Error::ReceiptTimeout(hash)) if hash == tx_hash));
+
+        let (web3, _handle) = pending_node(1).await;
+        let receipt = web3
+            .wait_for_receipt_with_timeout(tx_hash, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(receipt.transaction_hash, tx_hash);
+    }
+
+    #[tokio::test]
+    async fn it_waits_for_the_default_confirmations() {
+        let config = Web3Config::builder().default_confirmations(2).build();
+        let (web3, block_number, _handle) = mining_node(config).await;
+        let tx_hash = H256::from_low_u64_be(10);
+
+        let receipt = web3.wait_for_receipt_default(tx_hash).await.unwrap();
+        assert_eq!(receipt.transaction_hash, tx_hash);
+        assert_eq!(block_number.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn it_overrides_the_default_confirmations() {
+        let config = Web3Config::builder().default_confirmations(12).build();
+        let (web3, block_number, _handle) = mining_node(config).await;
+        let tx_hash = H256::from_low_u64_be(10);
+
+        let receipt = web3.wait_for_receipt(tx_hash, Some(1)).await.unwrap();
+        assert_eq!(receipt.transaction_hash, tx_hash);
+        assert_eq!(block_number.load(Ordering::SeqCst), 2);
+    }
+
+    /// A node without pending state that can't fit transfers to `unaffordable`,
+    /// with a gas price of `GAS_PRICE`
+    async fn estimating_node(unaffordable: Account) -> (Web3, ServerHandle) {
+        let mut module = RpcModule::new(unaffordable);
+
+        module
+            .register_method("eth_gasPrice", |_, _| Ok(U256::from(GAS_PRICE)))
+            .unwrap();
+        module
+            .register_method("eth_estimateGas", |params, unaffordable| {
+                let (transaction_request, block): (TransactionRequest, String) = params.parse()?;
+                let error = |message: &str| -> std::result::Result<U256, JsonRpseeError> {
+                    let error = ErrorObject::owned(-32000, message, None::<()>);
+                    Err(CallError::Custom(error).into())
+                };
+
+                if transaction_request.to == Some(*unaffordable) {
+                    return error("gas required exceeds allowance (30000000)");
+                }
+
+                match block.as_str() {
+                    "pending" => error("missing trie node"),
+                    _ => Ok(U256::from(21_000)),
+                }
+            })
+            .unwrap();
+
+        let (url, handle) = mock_node(module).await;
+
+        (Web3::new(&url).unwrap(), handle)
+    }
+
+    const GAS_PRICE: u64 = 7;
+
+    #[tokio::test]
+    async fn it_gets_the_gas_price() {
+        let (web3, _handle) = estimating_node(Account::random()).await;
+
+        assert_eq!(web3.gas_price().await.unwrap(), U256::from(GAS_PRICE));
+    }
+
+    #[tokio::test]
+    async fn it_suggests_a_ready_to_send_transfer() {
+        let (web3, _handle) = estimating_node(Account::random()).await;
+        let (from, to) = (Account::random(), Account::random());
+
+        let transaction_request = web3
+            .suggest_transaction_request(from, to, U256::from(10))
+            .await
+            .unwrap();
+        assert_eq!(transaction_request.from, Some(from));
+        assert_eq!(transaction_request.to, Some(to));
+        assert_eq!(transaction_request.value, Some(U256::from(10)));
+        assert_eq!(transaction_request.gas_price, U256::from(GAS_PRICE));
+        assert_eq!(transaction_request.gas, U256::from(21_000));
+
+        web3.set_alias("treasury", from).unwrap();
+        let transaction_request = web3
+            .suggest_transaction_request("treasury", to, U256::from(10))
+            .await
+            .unwrap();
+        assert_eq!(transaction_request.from, Some(from));
+        assert!(matches!(
+            web3.suggest_transaction_request(from, "vault", U256::from(10))
+                .await,
+            Err(Web3Error::UnknownAlias(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_estimates_the_gas_to_deploy_a_contract() {
+        let transaction_request = TransactionRequest {
+            from: Some(*ACCOUNT_1),
+            data: Some(get_contract().into()),
+            ..Default::default()
+        };
+        let estimate = web3().estimate_gas(&transaction_request).await.unwrap();
+        assert!(!estimate.gas.is_zero());
+    }
+
+    #[tokio::test]
+    async fn it_falls_back_to_the_latest_block_to_estimate_gas() {
+        let (web3, _handle) = estimating_node(Account::random()).await;
+        let transfer = TransactionRequest {
+            to: Some(Account::random()),
+            ..Default::default()
+        };
+
+        let estimate = web3.estimate_gas(&transfer).await.unwrap();
+        assert_eq!(estimate.gas, U256::from(21_000));
+        assert_eq!(estimate.block, BlockTag::Latest);
+    }
+
+    #[tokio::test]
+    async fn it_fails_to_estimate_gas_beyond_the_allowance() {
+        let unaffordable = Account::random();
+        let (web3, _handle) = estimating_node(unaffordable).await;
+        let transfer = TransactionRequest {
+            to: Some(unaffordable),
+            ..Default::default()
+        };
+
+        let estimate = web3.estimate_gas(&transfer).await;
+        assert!(matches!(
+            estimate,
+            Err(Web3Error::GasEstimationFailed { reason }) if reason.contains("exceeds allowance")
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_estimates_the_gas_of_a_batch_of_transactions() {
+        let unaffordable = Account::random();
+        let (web3, _handle) = estimating_node(unaffordable).await;
+        let transfer = |to| TransactionRequest {
+            to: Some(to),
+            ..Default::default()
+        };
+        let transaction_requests = [
+            transfer(Account::random()),
+            transfer(unaffordable),
+            transfer(Account::random()),
+        ];
+
+        let estimates = web3
+            .estimate_gas_batch(&transaction_requests, BlockTag::Latest)
+            .await
+            .unwrap();
+        assert_eq!(estimates.len(), 3);
+        assert_eq!(estimates[0].as_ref().unwrap(), &U256::from(21_000));
+        assert!(matches!(
+            &estimates[1],
+            Err(Web3Error::GasEstimationFailed { reason }) if reason.contains("exceeds allowance")
+        ));
+        assert_eq!(estimates[2].as_ref().unwrap(), &U256::from(21_000));
+    }
+
+    #[test]
+    fn it_parses_an_access_list_response() {
+        let response = serde_json::json!({
+            "accessList": [
+                {
+                    "address": "0xa02457e5dfd32bda5fc7e1f1b008aa5979568150",
+                    "storageKeys": [
+                        "0x0000000000000000000000000000000000000000000000000000000000000081",
+                        "0x0000000000000000000000000000000000000000000000000000000000000000"
+                    ]
+                },
+                {
+                    "address": "0x4a0d457e884ebd9b9773d172ed687417caac4f14",
+                    "storageKeys": []
+                }
+            ],
+            "gasUsed": "0x125f8"
+        });
+        let AccessListResponse {
+            access_list,
+            gas_used,
+            error,
+        } = web3().deserialize(response).unwrap();
+
+        assert_eq!(access_list.len(), 2);
+        assert_eq!(
+            format!("{:?}", access_list[0].address),
+            "0xa02457e5dfd32bda5fc7e1f1b008aa5979568150"
+        );
+        assert_eq!(access_list[0].storage_keys[0], H256::from_low_u64_be(0x81));
+        assert_eq!(access_list[0].storage_keys[1], H256::zero());
+        assert_eq!(access_list[1].address, *ACCOUNT_1);
+        assert!(access_list[1].storage_keys.is_empty());
+        assert_eq!(gas_used, U256::from(0x125f8));
+        assert_eq!(error, None);
+    }
 }
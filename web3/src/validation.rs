@@ -0,0 +1,147 @@
+//! # Validation
+//!
+//! Check the shape of RPC responses against a small, JSON-Schema-like
+//! description of what each method returns, to catch node incompatibilities
+//! early. Only compiled in debug builds with the `validate-responses` feature,
+//! so release builds pay nothing for it.
+
+////////////////////////////////////////////////////////////////////////////////
+
+use serde_json::Value;
+
+use crate::error::{Result, Web3Error};
+
+/// The shape of a JSON value
+#[derive(Debug, Clone, Copy)]
+enum Schema {
+    /// A hex encoded integer (e.g. `0x1a`)
+    Quantity,
+    /// Hex encoded bytes of exactly this many bytes (e.g. a 32 byte hash)
+    Data(usize),
+    Bool,
+    /// `null` or the inner schema
+    Nullable(&'static Schema),
+    /// An object with at least these fields, which may have others
+    Object(&'static [(&'static str, Schema)]),
+}
+
+const RECEIPT: Schema = Schema::Object(&[("transactionHash", Schema::Data(32))]);
+
+/// The schema of a method's response, if there is one to check
+fn schema(method: &str) -> Option<Schema> {
+    match method {
+        "eth_blockNumber"
+        | "eth_chainId"
+        | "eth_estimateGas"
+        | "eth_gasPrice"
+        | "eth_getBalance"
+        | "eth_getBalanceByBlock"
+        | "eth_getTransactionCount" => Some(Schema::Quantity),
+        "eth_sendRawTransaction" | "eth_sendTransaction" => Some(Schema::Data(32)),
+        "eth_getTransactionReceipt" => Some(Schema::Nullable(&RECEIPT)),
+        "net_listening" => Some(Schema::Bool),
+        _ => None,
+    }
+}
+
+/// Check a response has the shape its method returns
+pub(crate) fn validate(method: &str, response: &Value) -> Result<()> {
+    match schema(method) {
+        Some(schema) => check(&schema, response, "result").map_err(|mismatch| {
+            Web3Error::InvalidResponse(format!("{} returned {}", method, mismatch))
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Find where a value strays from its schema, describing it by its path
+fn check(schema: &Schema, value: &Value, path: &str) -> std::result::Result<(), String> {
+    let hex = |value: &Value| {
+        value
+            .as_str()
+            .and_then(|value| value.strip_prefix("0x"))
+            .filter(|digits| digits.chars().all(|digit| digit.is_ascii_hexdigit()))
+            .map(str::len)
+    };
+    let matches = match schema {
+        Schema::Quantity => matches!(hex(value), Some(1..)),
+        Schema::Data(bytes) => hex(value) == Some(bytes * 2),
+        Schema::Bool => value.is_boolean(),
+        Schema::Nullable(_) if value.is_null() => true,
+        Schema::Nullable(schema) => return check(schema, value, path),
+        Schema::Object(fields) => {
+            let object = value
+                .as_object()
+                .ok_or_else(|| format!("{} {} instead of an object", path, value))?;
+
+            return fields.iter().try_for_each(|(name, schema)| {
+                let path = format!("{}.{}", path, name);
+
+                match object.get(*name) {
+                    Some(field) => check(schema, field, &path),
+                    None => Err(format!("no {}", path)),
+                }
+            });
+        }
+    };
+
+    if matches {
+        Ok(())
+    } else {
+        Err(format!("{} {} instead of {:?}", path, value, schema))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpsee::rpc_params;
+    use jsonrpsee::RpcModule;
+    use serde_json::json;
+    use types::account::Account;
+
+    use super::*;
+    use crate::helpers::tests::mock_node;
+    use crate::Web3;
+
+    #[test]
+    fn it_checks_the_shape_of_a_response() {
+        let hash = format!("0x{}", "ab".repeat(32));
+
+        assert!(validate("eth_getBalance", &json!("0x64")).is_ok());
+        assert!(validate("eth_getBalance", &json!("0x")).is_err());
+        assert!(validate("eth_getBalance", &json!("100")).is_err());
+        assert!(validate("eth_sendRawTransaction", &json!(hash)).is_ok());
+        assert!(validate("eth_sendRawTransaction", &json!("0xab")).is_err());
+        assert!(validate("eth_getTransactionReceipt", &Value::Null).is_ok());
+        assert!(validate(
+            "eth_getTransactionReceipt",
+            &json!({ "transactionHash": hash })
+        )
+        .is_ok());
+
+        let response = validate("eth_getTransactionReceipt", &json!({ "status": "0x1" }));
+        assert!(
+            matches!(response, Err(Web3Error::InvalidResponse(reason)) if reason.contains("result.transactionHash"))
+        );
+
+        // methods without a schema aren't checked
+        assert!(validate("eth_getCode", &json!([0, 97, 115, 109])).is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_rejects_a_malformed_response() {
+        let mut module = RpcModule::new(());
+        module
+            .register_method("eth_getBalance", |_, _| Ok(100))
+            .unwrap();
+        let (url, _handle) = mock_node(module).await;
+        let web3 = Web3::new(&url).unwrap();
+
+        let response = web3
+            .send_rpc("eth_getBalance", rpc_params![Account::random()])
+            .await;
+        assert!(
+            matches!(response, Err(Web3Error::InvalidResponse(reason)) if reason.starts_with("eth_getBalance"))
+        );
+    }
+}